@@ -0,0 +1,59 @@
+//! Compares sending frames one by one with [`Socket::send_raw_packets`]
+//!
+//! Run with `cargo bench --bench send -- <interface>`, opening the socket requires root
+
+use std::time;
+
+use cursock::*;
+
+const FRAMES: usize = 100000;
+const BATCH: usize = 1024;
+
+fn main() {
+    let interface: String = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with('-'))
+        .unwrap_or("lo".to_string());
+
+    let socket: Socket = match Socket::new(&interface) {
+        Ok(socket) => socket,
+        Err(err) => {
+            eprintln!("skipped, can't open socket on {}: {}", interface, err);
+            return;
+        }
+    };
+
+    let mut frame: Vec<u8> = vec![0; 60];
+    frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x9a]);
+
+    let start: time::Instant = time::Instant::now();
+    for _ in 0..FRAMES {
+        socket.send_raw_packet(&frame).expect("send error");
+    }
+    report("send_raw_packet", FRAMES, start.elapsed());
+
+    let frames: Vec<&[u8]> = vec![&frame; BATCH];
+
+    let start: time::Instant = time::Instant::now();
+    let mut sent: usize = 0;
+    let mut batches: usize = 0;
+    while sent < FRAMES {
+        let count: usize = (FRAMES - sent).min(BATCH);
+        sent += socket
+            .send_raw_packets(&frames[..count])
+            .expect("send error");
+        batches += 1;
+    }
+    report("send_raw_packets", batches, start.elapsed());
+}
+
+fn report(name: &str, calls: usize, elapsed: time::Duration) {
+    println!(
+        "{:<17} {} frames in {:?} ({:.0} frames/s), {} calls",
+        name,
+        FRAMES,
+        elapsed,
+        FRAMES as f64 / elapsed.as_secs_f64(),
+        calls
+    )
+}
@@ -1,4 +1,4 @@
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
 use std::ffi::CString;
 use std::io;
 use std::net;
@@ -8,88 +8,356 @@ use crate::*;
 pub struct Adapter {
     #[cfg(target_os = "windows")]
     guid: String,
-    #[cfg(target_os = "linux")]
+    #[cfg(target_os = "windows")]
+    index: u32,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
     index: i32,
+    #[cfg(target_os = "macos")]
+    index: u32,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    hw_type: u16,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    hw_addr: Vec<u8>,
     name: String,
     ipv4: Option<net::Ipv4Addr>,
+    netmask: Option<net::Ipv4Addr>,
+    // none for loopback and point-to-point interfaces
+    broadcast: Option<net::Ipv4Addr>,
     ipv6: Option<net::Ipv6Addr>,
+    // every ipv6 address of the interface, including link-local ones
+    ipv6_addresses: Vec<net::Ipv6Addr>,
     gateway: Option<net::Ipv4Addr>,
     mac: Mac,
+    description: Option<String>,
+    is_up: bool,
+    is_loopback: bool,
+}
+
+/// duplex mode of the link
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Duplex {
+    Half,
+    Full,
+    Unknown,
+}
+
+/// operational link state, see [`Adapter::get_link_settings`]
+#[derive(Clone, Debug)]
+pub struct LinkSettings {
+    speed: Option<u32>,
+    duplex: Duplex,
+    autoneg: Option<bool>,
+}
+
+/// npcap device, as it's returned from pcap_findalldevs
+#[cfg(target_os = "windows")]
+pub struct PcapDevice {
+    name: String,
+    description: Option<String>,
+    addresses: Vec<net::IpAddr>,
 }
 
 impl Adapter {
     /// initializes struct using interface id
     ///
+    /// Returns InvalidInput error, if there is no such interface
+    ///
     /// # Examples
-    /// ```
-    /// let interface = Adapter::get_by_id(10, IpVer::V4).expect("error finding adapter");
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// let interface = Adapter::get_by_id(10).expect("error finding adapter");
     /// ```
     #[cfg(target_os = "windows")]
     pub fn get_by_id(id: u32) -> io::Result<Self> {
-        let (ipv4, ipv6, gateway, mac, guid, name) = get_interface_info(id)?;
-
-        Ok(Self {
-            name,
-            ipv4,
-            ipv6,
-            gateway,
-            mac,
-            guid,
-        })
+        get_interface_info(id)
     }
 
     /// initializes struct using interface name
     ///
+    /// Returns InvalidInput error, if there is no such interface
+    ///
     /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// let adapter = Adapter::get_by_ifname("wlan0").expect("error finding adapter");
+    /// ```
+    ///
     /// ```
-    /// let adapter = Adapter::get_by_ifname("wlan0", IpVer::V4).expect("error finding adapter");
+    /// use std::io;
+    /// use cursock::*;
+    ///
+    /// #[cfg(any(target_os = "linux", target_os = "macos"))]
+    /// {
+    ///     let err = Adapter::get_by_ifname("bogus0").err().expect("interface exists");
     ///
+    ///     assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    ///     assert_eq!(err.to_string(), "interface bogus0 not found")
+    /// }
     /// ```
-    #[cfg(target_os = "linux")]
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
     pub fn get_by_ifname(if_name: &str) -> io::Result<Self> {
-        let cstr_if_name = CString::new(if_name)
-            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
-
-        let (index, ipv4, ipv6, mac) = get_interface_info(cstr_if_name)?;
-        let gateway = get_file_default_gateway();
-
-        Ok(Self {
-            index,
-            name: if_name.to_string(),
-            ipv4,
-            ipv6,
-            gateway,
-            mac,
-        })
+        get_interface_info(if_name)
+    }
+
+    /// Reads negotiated speed (in Mb/s), duplex and auto-negotiation state of the link
+    ///
+    /// Uses ETHTOOL_GLINKSETTINGS on linux, on windows only speed is available
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// let adapter = Adapter::get_by_ifname("eth0").expect("error finding adapter");
+    /// let settings = adapter.get_link_settings().expect("ethtool error");
+    ///
+    /// if settings.get_duplex() == &Duplex::Half {
+    ///     println!("{} works in half duplex mode", adapter.get_name())
+    /// }
+    /// ```
+    pub fn get_link_settings(&self) -> io::Result<LinkSettings> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            get_link_settings(&self.name)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            get_link_settings(&self.guid)
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "windows")))]
+        {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+
+    /// Reads current mtu of the interface, frames are at most mtu + [`ETH_HEADER_SIZE`] bytes long
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let adapter = Adapter::get_by_ifname("lo").expect("error finding adapter");
+    ///
+    ///     assert!(adapter.get_mtu().expect("mtu error") >= 1280)
+    /// }
+    /// ```
+    pub fn get_mtu(&self) -> io::Result<u32> {
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+        {
+            get_mtu(&self.name)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            get_mtu(&self.guid)
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
     }
 
     getters!(
         pub get_ipv4(ipv4) -> Option<net::Ipv4Addr>;
+        pub get_netmask(netmask) -> Option<net::Ipv4Addr>;
+        pub get_broadcast(broadcast) -> Option<net::Ipv4Addr>;
         pub get_ipv6(ipv6) -> Option<net::Ipv6Addr>;
+        pub get_ipv6_addresses(ipv6_addresses) -> [net::Ipv6Addr];
         pub get_gateway(gateway) -> Option<net::Ipv4Addr>;
         pub get_mac(mac) -> Mac;
         pub get_name(name) -> str;
+        pub get_description(description) -> Option<String>;
+        pub get_is_up(is_up) -> bool;
+        pub get_is_loopback(is_loopback) -> bool;
     );
 
+    /// Returns network of the ipv4 address of the interface
+    ///
+    /// # Examples
+    /// ```
+    /// use std::net::Ipv4Addr;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let adapter = Adapter::get_by_ifname("lo").expect("error finding adapter");
+    ///
+    ///     assert_eq!(adapter.get_netmask(), &Some(Ipv4Addr::new(255, 0, 0, 0)));
+    ///     assert_eq!(adapter.get_broadcast(), &None);
+    ///     assert_eq!(adapter.get_ipv4_cidr().map(|cidr| cidr.to_string()), Some("127.0.0.0/8".to_string()))
+    /// }
+    /// ```
+    pub fn get_ipv4_cidr(&self) -> Option<Ipv4Cidr> {
+        let prefix: u32 = u32::from(self.netmask?).leading_ones();
+
+        Ipv4Cidr::new(self.ipv4?, prefix as u8, true).ok()
+    }
+
+    /// Returns link-local ipv6 address of the interface
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// for adapter in list_interfaces().expect("list error") {
+    ///     if let Some(ipv6) = adapter.get_ipv6_link_local() {
+    ///         assert_eq!(ipv6.segments()[0] & 0xffc0, 0xfe80);
+    ///         assert!(adapter.get_ipv6_addresses().contains(ipv6))
+    ///     }
+    /// }
+    /// ```
+    pub fn get_ipv6_link_local(&self) -> Option<&net::Ipv6Addr> {
+        self.ipv6_addresses.iter().find(|addr| is_link_local(addr))
+    }
+
     #[cfg(target_os = "windows")]
     getters!(
         pub get_guid(guid) -> str;
+        pub get_index(index) -> u32;
     );
 
-    #[cfg(target_os = "linux")]
+    #[cfg(target_os = "macos")]
+    getters!(
+        pub get_index(index) -> u32;
+    );
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
     getters!(
         pub get_index(index) -> i32;
+        pub get_hw_type(hw_type) -> u16;
+        pub get_hw_addr(hw_addr) -> [u8];
+    );
+}
+
+impl LinkSettings {
+    getters!(
+        pub get_speed(speed) -> Option<u32>;
+        pub get_duplex(duplex) -> Duplex;
+        pub get_autoneg(autoneg) -> Option<bool>;
+    );
+}
+
+#[cfg(target_os = "windows")]
+impl PcapDevice {
+    getters!(
+        pub get_name(name) -> str;
+        pub get_description(description) -> Option<String>;
+        pub get_addresses(addresses) -> [net::IpAddr];
     );
+
+    /// Returns guid of the device, which is a part of its \\Device\\NPF_{GUID} name
+    pub fn get_guid(&self) -> Option<&str> {
+        self.name.strip_prefix("\\Device\\NPF_")
+    }
+}
+
+/// Lists devices available for capturing through npcap
+///
+/// # Examples
+/// ```no_run
+/// use cursock::*;
+///
+/// for device in list_pcap_devices().expect("pcap error") {
+///     println!("{} - {:?}", device.get_name(), device.get_description())
+/// }
+/// ```
+#[cfg(target_os = "windows")]
+pub fn list_pcap_devices() -> io::Result<Vec<PcapDevice>> {
+    let mut error_buffer: [i8; 256] = [0; 256];
+    let mut all_devices: *mut ccs::pcap_if = std::ptr::null_mut();
+
+    let result: i32 = unsafe { ccs::pcap_findalldevs(&mut all_devices, error_buffer.as_mut_ptr()) };
+
+    if result != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            format!(
+                "Can\'t list devices due to {}",
+                str_from_cstr(error_buffer.as_ptr())
+            ),
+        ));
+    }
+
+    let mut devices: Vec<PcapDevice> = Vec::new();
+
+    let mut cur_device = all_devices;
+    while !cur_device.is_null() {
+        let cur_device_r = unsafe { &*cur_device };
+
+        let description = match cur_device_r.description.is_null() {
+            true => None,
+            false => Some(str_from_cstr(cur_device_r.description)),
+        };
+
+        let mut addresses: Vec<net::IpAddr> = Vec::new();
+
+        let mut cur_addr = cur_device_r.addresses;
+        while !cur_addr.is_null() {
+            let cur_addr_r = unsafe { &*cur_addr };
+
+            if !cur_addr_r.addr.is_null() {
+                let sockaddr = unsafe { &*cur_addr_r.addr };
+
+                match sockaddr.sa_family as usize {
+                    ccs::AF_INET => {
+                        let sockaddr = unsafe { &*(cur_addr_r.addr as *const ccs::sockaddr_in) };
+
+                        addresses.push(net::IpAddr::V4(net::Ipv4Addr::from(
+                            sockaddr.sin_addr.s_addr.to_ne_bytes(),
+                        )))
+                    }
+                    ccs::AF_INET6 => {
+                        let sockaddr = unsafe { &*(cur_addr_r.addr as *const ccs::sockaddr_in6) };
+
+                        addresses.push(net::IpAddr::V6(net::Ipv6Addr::from(unsafe {
+                            sockaddr.sin6_addr.s6_addr
+                        })))
+                    }
+                    _ => {}
+                }
+            }
+
+            cur_addr = cur_addr_r.next
+        }
+
+        devices.push(PcapDevice {
+            name: str_from_cstr(cur_device_r.name),
+            description,
+            addresses,
+        });
+
+        cur_device = cur_device_r.next
+    }
+
+    unsafe { ccs::pcap_freealldevs(all_devices) };
+
+    Ok(devices)
 }
 
-impl ToString for Adapter {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for Adapter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let ip = self
             .ipv4
             .map(net::IpAddr::V4)
             .or_else(|| self.ipv6.map(net::IpAddr::V6));
 
-        format!(
+        write!(
+            f,
             "{} ({} - {:?} - {:?})",
             self.name, self.mac, ip, self.gateway
         )
@@ -102,49 +370,246 @@ impl Clone for Adapter {
         Self {
             name: self.name.clone(),
             ipv4: self.ipv4,
+            netmask: self.netmask,
+            broadcast: self.broadcast,
             ipv6: self.ipv6,
+            ipv6_addresses: self.ipv6_addresses.clone(),
             gateway: self.gateway,
-            mac: self.mac.clone(),
+            mac: self.mac,
+            description: self.description.clone(),
+            is_up: self.is_up,
+            is_loopback: self.is_loopback,
             guid: self.guid.clone(),
+            index: self.index,
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            ipv4: self.ipv4,
+            netmask: self.netmask,
+            broadcast: self.broadcast,
+            ipv6: self.ipv6,
+            ipv6_addresses: self.ipv6_addresses.clone(),
+            gateway: self.gateway,
+            mac: self.mac,
+            description: self.description.clone(),
+            is_up: self.is_up,
+            is_loopback: self.is_loopback,
+            index: self.index,
+            hw_type: self.hw_type,
+            hw_addr: self.hw_addr.clone(),
         }
     }
 
-    #[cfg(target_os = "linux")]
+    #[cfg(target_os = "macos")]
     fn clone(&self) -> Self {
         Self {
             name: self.name.clone(),
             ipv4: self.ipv4,
+            netmask: self.netmask,
+            broadcast: self.broadcast,
             ipv6: self.ipv6,
+            ipv6_addresses: self.ipv6_addresses.clone(),
             gateway: self.gateway,
-            mac: self.mac.clone(),
+            mac: self.mac,
+            description: self.description.clone(),
+            is_up: self.is_up,
+            is_loopback: self.is_loopback,
             index: self.index,
         }
     }
 
-    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    #[cfg(not(any(
+        target_os = "windows",
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos"
+    )))]
     fn clone(&self) -> Self {
         Self {
             name: self.name.clone(),
             ipv4: self.ipv4.clone(),
+            netmask: self.netmask.clone(),
+            broadcast: self.broadcast.clone(),
             ipv6: self.ipv6.clone(),
+            ipv6_addresses: self.ipv6_addresses.clone(),
             gateway: self.gateway.clone(),
-            mac: self.mac.clone(),
+            mac: self.mac,
+            description: self.description.clone(),
+            is_up: self.is_up,
+            is_loopback: self.is_loopback,
         }
     }
 }
 
-#[cfg(target_os = "linux")]
-fn get_interface_info(
-    if_name: CString,
-) -> io::Result<(i32, Option<net::Ipv4Addr>, Option<net::Ipv6Addr>, Mac)> {
+/// global address is preferred over link-local one
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "windows",
+    target_os = "macos"
+))]
+fn preferred_ipv6(addresses: &[net::Ipv6Addr]) -> Option<net::Ipv6Addr> {
+    addresses
+        .iter()
+        .find(|addr| !is_link_local(addr))
+        .or_else(|| addresses.first())
+        .copied()
+}
+
+fn is_link_local(addr: &net::Ipv6Addr) -> bool {
+    addr.segments()[0] & 0xffc0 == 0xfe80
+}
+
+/// Lists all network interfaces, including ones which are down
+///
+/// # Examples
+/// ```no_run
+/// use cursock::*;
+///
+/// for adapter in list_interfaces().expect("list error") {
+///     println!("{} {:?} up: {}", adapter, adapter.get_description(), adapter.get_is_up())
+/// }
+///
+/// let loopback = list_interfaces()
+///     .expect("list error")
+///     .into_iter()
+///     .find(|adapter| *adapter.get_is_loopback());
+/// ```
+pub fn list_interfaces() -> io::Result<Vec<Adapter>> {
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+    {
+        let mut names: Vec<String> = Vec::new();
+
+        // every interface has at least AF_PACKET (AF_LINK on macos) entry
+        for_each_ifaddr(|ifaddr| {
+            let name: String = str_from_cstr(ifaddr.ifa_name);
+            if !names.contains(&name) {
+                names.push(name)
+            }
+        })?;
+
+        let mut adapters: Vec<Adapter> = Vec::with_capacity(names.len());
+        for name in names {
+            match get_interface_info(&name) {
+                Ok(adapter) => adapters.push(adapter),
+                // interface was removed while listing
+                Err(err) if err.kind() == io::ErrorKind::InvalidInput => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(adapters)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let mut adapters: Vec<Adapter> = Vec::new();
+
+        for_each_adapter_addresses(|addresses| adapters.push(adapter_from_addresses(addresses)))?;
+
+        Ok(adapters)
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "windows",
+        target_os = "macos"
+    )))]
+    {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not supported yet!", std::env::consts::OS),
+        ))
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_interface_info(name: &str) -> io::Result<Adapter> {
     let socketv4 = unsafe { ccs::socket(ccs::AF_INET, ccs::SOCK_DGRAM, 0) };
     if socketv4 < 0 {
         return Err(io::Error::last_os_error());
     }
 
+    let adapter: io::Result<Adapter> = query_interface_info(name, socketv4);
+
+    unsafe { ccs::close(socketv4) };
+
+    // ioctls fail with ENODEV, if there is no such interface
+    adapter.map_err(|err| match err.raw_os_error() {
+        Some(ccs::ENODEV) => interface_not_found(name),
+        _ => err,
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn query_interface_info(name: &str, socketv4: i32) -> io::Result<Adapter> {
+    let mut if_request: ccs::ifreq = new_ifreq(name)?;
+
+    let ifindex: i32 = get_if_index(socketv4, &mut if_request)?;
+
+    let ipv4 = get_if_ipv4(socketv4, &mut if_request, ccs::SIOCGIFADDR).ok();
+    let netmask = match ipv4 {
+        Some(_) => get_if_ipv4(socketv4, &mut if_request, ccs::SIOCGIFNETMASK).ok(),
+        None => None,
+    };
+
+    let ipv6_addresses: Vec<net::Ipv6Addr> = get_if_ipv6(name)?;
+
+    let flags: i16 = get_if_flags(socketv4, &mut if_request)?;
+
+    // address can have no broadcast address, even if the interface supports broadcast
+    let broadcast = match ipv4.is_some() && flags & ccs::IFF_BROADCAST != 0 {
+        true => get_if_ipv4(socketv4, &mut if_request, ccs::SIOCGIFBRDADDR)
+            .ok()
+            .filter(|broadcast| !broadcast.is_unspecified()),
+        false => None,
+    };
+
+    let (hw_type, hw_addr) = get_if_hwaddr(socketv4, &mut if_request)?;
+
+    let mut mac: [u8; MAC_LEN] = [0; MAC_LEN];
+    let mac_len: usize = hw_addr.len().min(MAC_LEN);
+    mac[..mac_len].copy_from_slice(&hw_addr[..mac_len]);
+
+    let gateway = get_file_default_gateway();
+
+    Ok(Adapter {
+        index: ifindex,
+        hw_type,
+        hw_addr,
+        name: name.to_string(),
+        ipv4,
+        netmask,
+        broadcast,
+        ipv6: preferred_ipv6(&ipv6_addresses),
+        ipv6_addresses,
+        gateway,
+        mac: Mac::from(mac),
+        description: get_if_alias(name),
+        is_up: flags & ccs::IFF_UP != 0,
+        is_loopback: flags & ccs::IFF_LOOPBACK != 0,
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn new_ifreq(name: &str) -> io::Result<ccs::ifreq> {
+    let if_name = CString::new(name)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+
+    if if_name.as_bytes_with_nul().len() > ccs::IFNAMSIZ {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("interface name {} is too long", name),
+        ));
+    }
+
     let ifru: ccs::ifreq_data = ccs::ifreq_data { ifru_ifindex: 0 };
     let mut if_request: ccs::ifreq = ccs::ifreq {
-        ifr_name: [0; 16],
+        ifr_name: [0; ccs::IFNAMSIZ],
         ifr_ifru: ifru,
     };
 
@@ -154,23 +619,85 @@ fn get_interface_info(
         if_name.as_bytes_with_nul().len(),
     );
 
-    let ifindex: i32 = get_if_index(socketv4, &mut if_request)?;
+    Ok(if_request)
+}
 
-    let ipv4 = get_if_ipv4(socketv4, &mut if_request).ok();
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_link_settings(name: &str) -> io::Result<LinkSettings> {
+    const HEADER_WORDS: usize = std::mem::size_of::<ccs::ethtool_link_settings>() / 4;
+    // kernel reports up to 127 words per each of supported, advertising and lp_advertising masks
+    let mut buffer: Vec<u32> = vec![0; HEADER_WORDS + 3 * 127];
 
-    let socketv6 = unsafe { ccs::socket(ccs::AF_INET6, ccs::SOCK_DGRAM, 0) };
-    if socketv6 < 0 {
+    let mut if_request: ccs::ifreq = new_ifreq(name)?;
+    if_request.ifr_ifru.ifru_data = buffer.as_mut_ptr() as *mut i8;
+
+    let socket = unsafe { ccs::socket(ccs::AF_INET, ccs::SOCK_DGRAM, 0) };
+    if socket < 0 {
         return Err(io::Error::last_os_error());
     }
 
-    let ipv6 = get_if_ipv6(socketv6, &mut if_request).ok();
+    let settings: *mut ccs::ethtool_link_settings =
+        buffer.as_mut_ptr() as *mut ccs::ethtool_link_settings;
+
+    // first request is a handshake, kernel responds with negative count of mask words
+    unsafe { (*settings).cmd = ccs::ETHTOOL_GLINKSETTINGS };
+    let mut err: i32 = unsafe { ccs::ioctl(socket, ccs::SIOCETHTOOL, &mut if_request) };
+
+    if err != -1 && unsafe { (*settings).link_mode_masks_nwords } < 0 {
+        unsafe {
+            (*settings).cmd = ccs::ETHTOOL_GLINKSETTINGS;
+            (*settings).link_mode_masks_nwords = -(*settings).link_mode_masks_nwords;
+        }
+        err = unsafe { ccs::ioctl(socket, ccs::SIOCETHTOOL, &mut if_request) };
+    }
 
-    let mac: Mac = get_if_mac(socketv4, &mut if_request)?;
+    let error = io::Error::last_os_error();
+    unsafe { ccs::close(socket) };
 
-    Ok((ifindex, ipv4, ipv6, mac))
+    if err == -1 {
+        return Err(error);
+    }
+
+    let settings: &ccs::ethtool_link_settings = unsafe { &*settings };
+
+    Ok(LinkSettings {
+        speed: match settings.speed {
+            0 | ccs::SPEED_UNKNOWN => None,
+            speed => Some(speed),
+        },
+        duplex: match settings.duplex {
+            ccs::DUPLEX_HALF => Duplex::Half,
+            ccs::DUPLEX_FULL => Duplex::Full,
+            _ => Duplex::Unknown,
+        },
+        autoneg: Some(settings.autoneg == ccs::AUTONEG_ENABLE),
+    })
 }
 
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_mtu(name: &str) -> io::Result<u32> {
+    let mut if_request: ccs::ifreq = new_ifreq(name)?;
+
+    let socket = unsafe { ccs::socket(ccs::AF_INET, ccs::SOCK_DGRAM, 0) };
+    if socket < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let err: i32 = unsafe { ccs::ioctl(socket, ccs::SIOCGIFMTU, &mut if_request) };
+    let error = io::Error::last_os_error();
+    unsafe { ccs::close(socket) };
+
+    if err == -1 {
+        return Err(match error.raw_os_error() {
+            Some(ccs::ENODEV) => interface_not_found(name),
+            _ => error,
+        });
+    }
+
+    Ok(unsafe { if_request.ifr_ifru.ifru_mtu } as u32)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
 fn get_if_index(socket: i32, ifr: *mut ccs::ifreq) -> io::Result<i32> {
     let err: i32 = unsafe { ccs::ioctl(socket, ccs::SIOCGIFINDEX, ifr) };
     if err == -1 {
@@ -182,9 +709,14 @@ fn get_if_index(socket: i32, ifr: *mut ccs::ifreq) -> io::Result<i32> {
     Ok(index)
 }
 
-#[cfg(target_os = "linux")]
-fn get_if_ipv4(socket: i32, ifr: *mut ccs::ifreq) -> io::Result<net::Ipv4Addr> {
-    let err: i32 = unsafe { ccs::ioctl(socket, ccs::SIOCGIFADDR, ifr) };
+#[cfg(any(target_os = "linux", target_os = "android"))]
+/// reads ipv4 address, netmask or broadcast address, they are at the same place of ifreq
+fn get_if_ipv4(
+    socket: i32,
+    ifr: *mut ccs::ifreq,
+    request: ccs::IoctlRequest,
+) -> io::Result<net::Ipv4Addr> {
+    let err: i32 = unsafe { ccs::ioctl(socket, request, ifr) };
 
     if err == -1 {
         return Err(io::Error::last_os_error());
@@ -193,51 +725,123 @@ fn get_if_ipv4(socket: i32, ifr: *mut ccs::ifreq) -> io::Result<net::Ipv4Addr> {
     let addr: *const ccs::sockaddr_in =
         unsafe { &(*ifr).ifr_ifru.ifru_addr as *const ccs::sockaddr } as *const ccs::sockaddr_in;
 
-    Ok(net::Ipv4Addr::from(unsafe { (*addr).sin_addr.s_addr.to_ne_bytes() }))
+    Ok(net::Ipv4Addr::from(unsafe {
+        (*addr).sin_addr.s_addr.to_ne_bytes()
+    }))
 }
 
-#[cfg(target_os = "linux")]
-fn get_if_ipv6(socket: i32, ifr: *mut ccs::ifreq) -> io::Result<net::Ipv6Addr> {
-    let err: i32 = unsafe { ccs::ioctl(socket, ccs::SIOCGIFADDR, ifr) };
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_if_flags(socket: i32, ifr: *mut ccs::ifreq) -> io::Result<i16> {
+    let err: i32 = unsafe { ccs::ioctl(socket, ccs::SIOCGIFFLAGS, ifr) };
 
     if err == -1 {
         return Err(io::Error::last_os_error());
     }
 
-    let addr = unsafe {
-        (*(&(*ifr).ifr_ifru.ifru_addr as *const _ as *const ccs::sockaddr_in6))
-            .sin6_addr
-            .s6_addr
-    };
+    Ok(unsafe { (*ifr).ifr_ifru.ifru_flags })
+}
+
+/// linux has no interface descriptions, but an alias can be set with `ip link set <name> alias <text>`
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_if_alias(name: &str) -> Option<String> {
+    let alias: String = std::fs::read_to_string(format!("/sys/class/net/{}/ifalias", name)).ok()?;
+    let alias: &str = alias.trim();
+
+    match alias.is_empty() {
+        true => None,
+        false => Some(alias.to_string()),
+    }
+}
+
+/// SIOCGIFADDR only works for ipv4, so ipv6 addresses are taken from getifaddrs
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_if_ipv6(name: &str) -> io::Result<Vec<net::Ipv6Addr>> {
+    let mut addresses: Vec<net::Ipv6Addr> = Vec::new();
+
+    for_each_ifaddr(|ifaddr| {
+        if ifaddr.ifa_addr.is_null() || str_from_cstr(ifaddr.ifa_name) != name {
+            return;
+        }
+
+        let sockaddr: &ccs::sockaddr = unsafe { &*ifaddr.ifa_addr };
+        if sockaddr.sa_family != ccs::AF_INET6 as u16 {
+            return;
+        }
+
+        let sockaddr: &ccs::sockaddr_in6 =
+            unsafe { &*(ifaddr.ifa_addr as *const ccs::sockaddr_in6) };
+
+        addresses.push(net::Ipv6Addr::from(unsafe { sockaddr.sin6_addr.s6_addr }))
+    })?;
+
+    Ok(addresses)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+fn for_each_ifaddr<F>(mut closure: F) -> io::Result<()>
+where
+    F: FnMut(&ccs::ifaddrs),
+{
+    let mut ifaddrs: *mut ccs::ifaddrs = std::ptr::null_mut();
+
+    if unsafe { ccs::getifaddrs(&mut ifaddrs) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut cur_addr: *mut ccs::ifaddrs = ifaddrs;
+    while !cur_addr.is_null() {
+        let cur_addr_r: &ccs::ifaddrs = unsafe { &*cur_addr };
+
+        closure(cur_addr_r);
+
+        cur_addr = cur_addr_r.ifa_next;
+    }
+
+    unsafe { ccs::freeifaddrs(ifaddrs) };
 
-    Ok(net::Ipv6Addr::from(addr))
+    Ok(())
 }
 
-#[cfg(target_os = "linux")]
-fn get_if_mac(socket: i32, ifr: *mut ccs::ifreq) -> io::Result<Mac> {
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_if_hwaddr(socket: i32, ifr: *mut ccs::ifreq) -> io::Result<(u16, Vec<u8>)> {
     let err: i32 = unsafe { ccs::ioctl(socket, ccs::SIOCGIFHWADDR, ifr) };
 
     if err == -1 {
         return Err(io::Error::last_os_error());
     }
 
-    let sa_data: [i8; 14] = unsafe { (*ifr).ifr_ifru.ifru_hwaddr.sa_data };
+    let hwaddr: ccs::sockaddr = unsafe { (*ifr).ifr_ifru.ifru_hwaddr };
 
-    let mut mac: [u8; MAC_LEN] = [0; MAC_LEN];
+    let mut hw_addr: Vec<u8> = vec![0; hw_addr_len(hwaddr.sa_family)];
 
-    memcpy(mac.as_mut_ptr(), sa_data.as_ptr(), MAC_LEN);
+    memcpy(hw_addr.as_mut_ptr(), hwaddr.sa_data.as_ptr(), hw_addr.len());
 
-    Ok(Mac::from(mac))
+    Ok((hwaddr.sa_family, hw_addr))
 }
 
-#[cfg(target_os = "linux")]
+/// link-layer address length for ARPHRD_* type, limited by sockaddr_ll.sll_addr size
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn hw_addr_len(hw_type: u16) -> usize {
+    let len: usize = match hw_type {
+        ccs::ARPHRD_NONE | ccs::ARPHRD_PPP | ccs::ARPHRD_RAWIP => 0,
+        ccs::ARPHRD_TUNNEL | ccs::ARPHRD_SIT | ccs::ARPHRD_IPGRE => IPV4_LEN,
+        ccs::ARPHRD_TUNNEL6 | ccs::ARPHRD_IEEE1394 => IPV6_LEN,
+        ccs::ARPHRD_IEEE802154 => 8,
+        ccs::ARPHRD_INFINIBAND => 20,
+        _ => MAC_LEN,
+    };
+
+    len.min(8)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
 fn get_file_default_gateway() -> Option<net::Ipv4Addr> {
     use std::{fs, io::BufRead};
 
     let file = fs::File::open("/proc/net/route").ok()?;
     let reader = io::BufReader::new(file);
 
-    for line in reader.lines().flatten() {
+    for line in reader.lines().map_while(Result::ok) {
         let mut fields = line.split('\t');
         let interface = fields.next();
         let destination = fields.next();
@@ -255,19 +859,221 @@ fn get_file_default_gateway() -> Option<net::Ipv4Addr> {
     None
 }
 
+/// macos has no SIOCGIFHWADDR, so everything is taken from getifaddrs,
+/// where AF_LINK entry carries mac address of the interface.
+/// Default gateway would need a routing socket, so it's left unknown
+#[cfg(target_os = "macos")]
+fn get_interface_info(name: &str) -> io::Result<Adapter> {
+    let c_name: CString = CString::new(name)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+
+    let index: u32 = unsafe { ccs::if_nametoindex(c_name.as_ptr()) };
+    if index == 0 {
+        return Err(interface_not_found(name));
+    }
+
+    let mut flags: u32 = 0;
+    let mut ipv4: Option<net::Ipv4Addr> = None;
+    let mut netmask: Option<net::Ipv4Addr> = None;
+    let mut broadcast: Option<net::Ipv4Addr> = None;
+    let mut ipv6_addresses: Vec<net::Ipv6Addr> = Vec::new();
+    let mut mac: [u8; MAC_LEN] = [0; MAC_LEN];
+
+    for_each_ifaddr(|ifaddr| {
+        if str_from_cstr(ifaddr.ifa_name) != name {
+            return;
+        }
+
+        flags = ifaddr.ifa_flags;
+
+        if ifaddr.ifa_addr.is_null() {
+            return;
+        }
+
+        match unsafe { (*ifaddr.ifa_addr).sa_family } {
+            ccs::AF_INET if ipv4.is_none() => {
+                let sockaddr: &ccs::sockaddr_in =
+                    unsafe { &*(ifaddr.ifa_addr as *const ccs::sockaddr_in) };
+
+                ipv4 = Some(net::Ipv4Addr::from(sockaddr.sin_addr.s_addr.to_ne_bytes()));
+                netmask = sockaddr_ipv4(ifaddr.ifa_netmask);
+
+                if ifaddr.ifa_flags & ccs::IFF_BROADCAST != 0 {
+                    broadcast = sockaddr_ipv4(ifaddr.ifa_dstaddr)
+                        .filter(|broadcast| !broadcast.is_unspecified());
+                }
+            }
+            ccs::AF_INET6 => {
+                let sockaddr: &ccs::sockaddr_in6 =
+                    unsafe { &*(ifaddr.ifa_addr as *const ccs::sockaddr_in6) };
+
+                let mut addr: [u8; 16] = unsafe { sockaddr.sin6_addr.s6_addr };
+                // kernel embeds scope id into the second segment of link-local addresses
+                if is_link_local(&net::Ipv6Addr::from(addr)) {
+                    addr[2] = 0;
+                    addr[3] = 0;
+                }
+
+                ipv6_addresses.push(net::Ipv6Addr::from(addr))
+            }
+            ccs::AF_LINK => {
+                let sockaddr: &ccs::sockaddr_dl =
+                    unsafe { &*(ifaddr.ifa_addr as *const ccs::sockaddr_dl) };
+
+                // address follows the name and can go past sdl_data, which is only the minimal size
+                if sockaddr.sdl_alen as usize == MAC_LEN {
+                    let address: *const u8 = unsafe {
+                        (sockaddr.sdl_data.as_ptr() as *const u8).add(sockaddr.sdl_nlen as usize)
+                    };
+
+                    memcpy(mac.as_mut_ptr(), address, MAC_LEN);
+                }
+            }
+            _ => {}
+        }
+    })?;
+
+    Ok(Adapter {
+        index,
+        name: name.to_string(),
+        ipv4,
+        netmask,
+        broadcast,
+        ipv6: preferred_ipv6(&ipv6_addresses),
+        ipv6_addresses,
+        gateway: None,
+        mac: Mac::from(mac),
+        description: None,
+        is_up: flags & ccs::IFF_UP != 0,
+        is_loopback: flags & ccs::IFF_LOOPBACK != 0,
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn get_mtu(name: &str) -> io::Result<u32> {
+    if name.len() >= ccs::IFNAMSIZ {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("interface name {} is too long", name),
+        ));
+    }
+
+    let mut if_request: ccs::ifreq = ccs::ifreq {
+        ifr_name: [0; ccs::IFNAMSIZ],
+        ifr_ifru: [0; 16],
+    };
+    for (dst, src) in if_request.ifr_name.iter_mut().zip(name.bytes()) {
+        *dst = src as i8;
+    }
+
+    let socket = unsafe { ccs::socket(ccs::AF_INET as i32, ccs::SOCK_DGRAM, 0) };
+    if socket < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let err: i32 = unsafe { ccs::ioctl(socket, ccs::SIOCGIFMTU, &mut if_request) };
+    let error = io::Error::last_os_error();
+    unsafe { ccs::close(socket) };
+
+    if err == -1 {
+        return Err(match error.raw_os_error() {
+            Some(ccs::ENXIO) | Some(ccs::ENODEV) => interface_not_found(name),
+            _ => error,
+        });
+    }
+
+    // ifr_mtu is the first int of the union
+    let mtu: [u8; 4] = [
+        if_request.ifr_ifru[0],
+        if_request.ifr_ifru[1],
+        if_request.ifr_ifru[2],
+        if_request.ifr_ifru[3],
+    ];
+
+    Ok(i32::from_ne_bytes(mtu) as u32)
+}
+
+#[cfg(target_os = "macos")]
+fn sockaddr_ipv4(sockaddr: *const ccs::sockaddr) -> Option<net::Ipv4Addr> {
+    if sockaddr.is_null() || unsafe { (*sockaddr).sa_family } != ccs::AF_INET {
+        return None;
+    }
+
+    let sockaddr: &ccs::sockaddr_in = unsafe { &*(sockaddr as *const ccs::sockaddr_in) };
+
+    Some(net::Ipv4Addr::from(sockaddr.sin_addr.s_addr.to_ne_bytes()))
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "windows",
+    target_os = "macos"
+))]
+fn interface_not_found(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("interface {} not found", name),
+    )
+}
+
 #[cfg(target_os = "windows")]
-fn get_interface_info(
-    if_id: u32,
-) -> io::Result<(
-    Option<net::Ipv4Addr>,
-    Option<net::Ipv6Addr>,
-    Option<net::Ipv4Addr>,
-    Mac,
-    String,
-    String,
-)> {
-    use crate::ccs::AF_INET;
+fn get_interface_info(if_id: u32) -> io::Result<Adapter> {
+    let mut output = None;
 
+    for_each_adapter_addresses(|addresses| {
+        if addresses.if_index == if_id {
+            output = Some(adapter_from_addresses(addresses))
+        }
+    })?;
+
+    match output {
+        Some(output) => Ok(output),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("there isn\'t any adapter with id {}", if_id),
+        )),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn get_link_settings(guid: &str) -> io::Result<LinkSettings> {
+    let mut output = None;
+
+    for_each_adapter_addresses(|addresses| {
+        if str_from_cstr(addresses.adapter_name as *const i8) == guid {
+            output = Some(LinkSettings {
+                speed: match addresses.transmit_link_speed {
+                    0 | u64::MAX => None,
+                    speed => Some((speed / 1_000_000) as u32),
+                },
+                duplex: Duplex::Unknown,
+                autoneg: None,
+            })
+        }
+    })?;
+
+    output.ok_or_else(|| interface_not_found(guid))
+}
+
+#[cfg(target_os = "windows")]
+fn get_mtu(guid: &str) -> io::Result<u32> {
+    let mut output = None;
+
+    for_each_adapter_addresses(|addresses| {
+        if str_from_cstr(addresses.adapter_name as *const i8) == guid {
+            output = Some(addresses.mtu)
+        }
+    })?;
+
+    output.ok_or_else(|| interface_not_found(guid))
+}
+
+#[cfg(target_os = "windows")]
+fn for_each_adapter_addresses<F>(mut closure: F) -> io::Result<()>
+where
+    F: FnMut(&ccs::IP_ADAPTER_ADDRESSES),
+{
     let mut out_buf_len: u32 = 0;
     let flags = ccs::GAA_FLAG_INCLUDE_GATEWAYS;
 
@@ -302,108 +1108,130 @@ fn get_interface_info(
         ));
     }
 
-    let mut output = None;
-
     let mut cur_addr = addresses;
     while !cur_addr.is_null() {
-        let cur_addr_r = unsafe { &mut *cur_addr };
-
-        if cur_addr_r.if_index == if_id {
-            let mut ipv4 = None;
-            let mut ipv6 = None;
-            let mut gateway_ip = None;
-            let mut mac = [0; MAC_LEN];
-            memcpy(
-                mac.as_mut_ptr(),
-                cur_addr_r.physical_address.as_ptr(),
-                MAC_LEN,
-            );
-
-            let mac = Mac::from(mac);
-
-            let guid = str_from_cstr(cur_addr_r.adapter_name as *const i8);
-            let slice = unsafe {
-                std::slice::from_raw_parts(cur_addr_r.friendly_name, {
-                    let mut len = 0;
-                    while *cur_addr_r.friendly_name.add(len) != 0 {
-                        len += 1;
-                    }
-                    len
-                })
-            };
-            let name = slice
-                .iter()
-                .map(|u| std::char::from_u32(*u as u32).unwrap())
-                .collect::<String>();
-
-            let mut gateway = cur_addr_r.first_gateway_address;
-            while !gateway.is_null() {
-                let r_gateway = unsafe { &mut *gateway };
+        let cur_addr_r = unsafe { &*cur_addr };
 
-                let sockaddr =
-                    unsafe { &*(r_gateway.address.lp_sockaddr as *const ccs::sockaddr_in) };
+        closure(cur_addr_r);
 
-                if sockaddr.sin_family == AF_INET as i16 {
-                    gateway_ip = Some(net::Ipv4Addr::from(sockaddr.sin_addr.s_addr.to_ne_bytes()));
-                    break;
-                }
+        cur_addr = cur_addr_r.next
+    }
 
-                gateway = r_gateway.next;
-            }
+    Ok(())
+}
 
-            let mut unicast_addr = cur_addr_r.first_unicast_address;
-            while !unicast_addr.is_null() {
-                let unicast_addr_r = unsafe { &mut *unicast_addr };
+#[cfg(target_os = "windows")]
+fn adapter_from_addresses(addresses: &ccs::IP_ADAPTER_ADDRESSES) -> Adapter {
+    use crate::ccs::AF_INET;
 
-                let sockaddr =
-                    unsafe { &*(unicast_addr_r.address.lp_sockaddr as *const ccs::sockaddr) };
+    let mut ipv4 = None;
+    let mut netmask = None;
+    let mut broadcast = None;
+    let mut ipv6_addresses = Vec::new();
+    let mut gateway_ip = None;
+    let mut mac = [0; MAC_LEN];
+    memcpy(
+        mac.as_mut_ptr(),
+        addresses.physical_address.as_ptr(),
+        MAC_LEN,
+    );
 
-                match sockaddr.sa_family as usize {
-                    ccs::AF_INET => {
-                        if ipv4.is_some() {
-                            unicast_addr = unicast_addr_r.next;
-                            continue;
-                        }
+    let mac = Mac::from(mac);
 
-                        let sockaddr = unsafe {
-                            &*(unicast_addr_r.address.lp_sockaddr as *const ccs::sockaddr_in)
-                        };
+    let guid = str_from_cstr(addresses.adapter_name as *const i8);
+    let name = str_from_wstr(addresses.friendly_name);
 
-                        ipv4 = Some(net::Ipv4Addr::from(sockaddr.sin_addr.s_addr.to_ne_bytes()))
-                    }
-                    ccs::AF_INET6 => {
-                        if ipv6.is_some() {
-                            unicast_addr = unicast_addr_r.next;
-                            continue;
-                        }
+    let mut gateway = addresses.first_gateway_address;
+    while !gateway.is_null() {
+        let r_gateway = unsafe { &*gateway };
 
-                        let sockaddr = unsafe {
-                            &*(unicast_addr_r.address.lp_sockaddr as *const ccs::sockaddr_in6)
-                        };
+        let sockaddr = unsafe { &*(r_gateway.address.lp_sockaddr as *const ccs::sockaddr_in) };
 
-                        ipv6 = Some(net::Ipv6Addr::from(unsafe { sockaddr.sin6_addr.s6_addr }))
-                    }
-                    _ => {}
+        if sockaddr.sin_family == AF_INET as i16 {
+            gateway_ip = Some(net::Ipv4Addr::from(sockaddr.sin_addr.s_addr.to_ne_bytes()));
+            break;
+        }
+
+        gateway = r_gateway.next;
+    }
+
+    let mut unicast_addr = addresses.first_unicast_address;
+    while !unicast_addr.is_null() {
+        let unicast_addr_r = unsafe { &*unicast_addr };
+
+        let sockaddr = unsafe { &*(unicast_addr_r.address.lp_sockaddr as *const ccs::sockaddr) };
+
+        match sockaddr.sa_family as usize {
+            ccs::AF_INET => {
+                if ipv4.is_some() {
+                    unicast_addr = unicast_addr_r.next;
+                    continue;
                 }
 
-                unicast_addr = unicast_addr_r.next
+                let sockaddr =
+                    unsafe { &*(unicast_addr_r.address.lp_sockaddr as *const ccs::sockaddr_in) };
+
+                let addr = net::Ipv4Addr::from(sockaddr.sin_addr.s_addr.to_ne_bytes());
+                ipv4 = Some(addr);
+
+                if let Ok(cidr) = Ipv4Cidr::new(addr, unicast_addr_r.on_link_prefix_length, true) {
+                    netmask = Some(cidr.netmask());
+                    // there are no broadcast addresses in /31 and /32 networks
+                    if *cidr.get_prefix() < 31 {
+                        broadcast = Some(cidr.broadcast());
+                    }
+                }
             }
+            ccs::AF_INET6 => {
+                let sockaddr =
+                    unsafe { &*(unicast_addr_r.address.lp_sockaddr as *const ccs::sockaddr_in6) };
 
-            output = Some((ipv4, ipv6, gateway_ip, mac, guid, name))
+                ipv6_addresses.push(net::Ipv6Addr::from(unsafe { sockaddr.sin6_addr.s6_addr }))
+            }
+            _ => {}
         }
 
-        cur_addr = cur_addr_r.next
+        unicast_addr = unicast_addr_r.next
     }
 
-    let output = match output {
-        Some(output) => output,
-        None => {
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("there isn\'t any adapter with id {}", if_id),
-            ))
-        }
+    let description = match addresses.description.is_null() {
+        true => None,
+        false => Some(str_from_wstr(addresses.description)),
+    };
+
+    Adapter {
+        name,
+        ipv4,
+        netmask,
+        broadcast,
+        ipv6: preferred_ipv6(&ipv6_addresses),
+        ipv6_addresses,
+        gateway: gateway_ip,
+        mac,
+        description,
+        is_up: addresses.oper_status == ccs::IF_OPER_STATUS_UP,
+        is_loopback: addresses.if_type == ccs::IF_TYPE_SOFTWARE_LOOPBACK,
+        guid,
+        index: addresses.if_index,
+    }
+}
+
+/// creates string from null terminated utf-16 string
+#[cfg(target_os = "windows")]
+fn str_from_wstr(wstr: *const u16) -> String {
+    if wstr.is_null() {
+        return String::new();
+    }
+
+    let slice = unsafe {
+        std::slice::from_raw_parts(wstr, {
+            let mut len = 0;
+            while *wstr.add(len) != 0 {
+                len += 1;
+            }
+            len
+        })
     };
 
-    Ok(output)
+    String::from_utf16_lossy(slice)
 }
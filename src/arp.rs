@@ -1,20 +1,79 @@
 use std::collections::HashMap;
 use std::io;
 use std::net;
+use std::sync::Mutex;
+use std::time;
 
 use crate::*;
 
-#[derive(Clone)]
 pub struct Arp {
     socket: Socket,
-    cache: HashMap<net::Ipv4Addr, Mac>,
+    cache: Mutex<HashMap<net::Ipv4Addr, Mac>>,
+}
+
+/// state of a neighbor table entry, see [`arp_table`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArpState {
+    /// address is being resolved or resolution failed, mac address is zero
+    Incomplete,
+    /// entry was learned from arp and expires
+    Dynamic,
+    /// entry was added by the user and doesn't expire
+    Static,
+}
+
+/// entry of the os neighbor table, see [`arp_table`]
+#[derive(Clone)]
+pub struct ArpEntry {
+    ip: net::Ipv4Addr,
+    mac: Mac,
+    interface: String,
+    state: ArpState,
+}
+
+/// arp packet of a captured frame, see [`parse_arp`]
+#[derive(Clone)]
+pub struct ArpPacket {
+    htype: u16,
+    ptype: u16,
+    opcode: u16,
+    sender_mac: Mac,
+    sender_ip: net::Ipv4Addr,
+    target_mac: Mac,
+    target_ip: net::Ipv4Addr,
+}
+
+impl ArpEntry {
+    getters!(
+        pub get_ip(ip) -> net::Ipv4Addr;
+        pub get_mac(mac) -> Mac;
+        pub get_interface(interface) -> String;
+        pub get_state(state) -> ArpState;
+    );
+}
+
+impl ArpPacket {
+    /// Returns true for replies, which tell the mac address of the sender
+    pub fn is_reply(&self) -> bool {
+        self.opcode == ARP_REPLY
+    }
+
+    getters!(
+        pub get_htype(htype) -> u16;
+        pub get_ptype(ptype) -> u16;
+        pub get_opcode(opcode) -> u16;
+        pub get_sender_mac(sender_mac) -> Mac;
+        pub get_sender_ip(sender_ip) -> net::Ipv4Addr;
+        pub get_target_mac(target_mac) -> Mac;
+        pub get_target_ip(target_ip) -> net::Ipv4Addr;
+    );
 }
 
 impl Arp {
     /// Initializes arp structure
     ///
     /// # Examples
-    /// ```
+    /// ```no_run
     /// use cursock::*;
     /// use cursock::utils::*;
     /// #[cfg(target_os = "linux")]
@@ -27,72 +86,78 @@ impl Arp {
 
         Ok(Self {
             socket,
-            cache: HashMap::new(),
+            cache: Mutex::new(HashMap::new()),
         })
     }
-    /// Does an arp request
+    /// Resolves mac address of the ipv4 address with an arp request, waiting for the reply at most timeout
+    ///
+    /// Returns TimedOut error if nobody answered, replies from other addresses are skipped.
+    /// Resolved addresses are cached, so the next calls for them don't send requests
+    ///
     /// # Examples
-    /// ```
+    /// ```no_run
     /// use cursock::*;
+    /// use std::io;
     /// use std::net::Ipv4Addr;
+    /// use std::time::Duration;
     ///
     /// let arp = Arp::new("wlan0").expect("initialize error");
     /// let ip_addr: Ipv4Addr = Ipv4Addr::from([192, 168, 0, 1]);
-    /// let response = arp.who_has(&ip_addr).expect("send error");
+    ///
+    /// match arp.who_has(&ip_addr, Duration::from_secs(1)) {
+    ///     Ok(mac) => println!("{} is at {}", ip_addr, mac),
+    ///     Err(err) if err.kind() == io::ErrorKind::TimedOut => println!("{} is down", ip_addr),
+    ///     Err(err) => panic!("{}", err),
+    /// }
     /// ```
-    pub fn who_has(&mut self, dst_ip: &net::Ipv4Addr) -> io::Result<ArpResponse> {
-        let adapter = self.socket.get_adapter();
-        let src_ip = adapter.get_ipv4().ok_or(io::Error::new(
-            io::ErrorKind::NotFound,
-            format!(
-                "To send ARP request you need to have ipv4 source address ({})",
-                adapter.to_string()
-            ),
-        ))?;
+    pub fn who_has(&self, dst_ip: &net::Ipv4Addr, timeout: time::Duration) -> io::Result<Mac> {
+        self.resolve(dst_ip, Some(time::Instant::now() + timeout))
+    }
 
-        if let Some(mac) = self.cache.get(dst_ip) {
-            return Ok(ArpResponse::new(
-                *dst_ip,
-                mac.clone(),
-                src_ip,
-                adapter.get_mac().clone(),
-            ));
+    /// resolves the address without timeout, if deadline isn't set
+    pub(crate) fn resolve(
+        &self,
+        dst_ip: &net::Ipv4Addr,
+        deadline: Option<time::Instant>,
+    ) -> io::Result<Mac> {
+        if let Some(mac) = self.lock_cache().get(dst_ip) {
+            return Ok(*mac);
         }
 
-        const BUFFER_SIZE: usize = ETH_HEADER_SIZE + ARP_HEADER_SIZE;
-        let mut buffer: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
-
-        let eth_header: &mut EthHeader = unsafe { &mut *(buffer.as_mut_ptr() as *mut EthHeader) };
-        let arp_header: &mut ArpHeader =
-            unsafe { &mut *((buffer.as_mut_ptr() as usize + ETH_HEADER_SIZE) as *mut ArpHeader) };
-
-        eth_header.dest = [0xff; MAC_LEN];
-
-        eth_header.source = self.socket.get_src_mac().clone().into();
-        arp_header.target_mac = [0; MAC_LEN];
-        arp_header.sender_mac = self.socket.get_src_mac().clone().into();
-
-        eth_header.proto = u16::from_be(ARP_PROTO);
-
-        arp_header.hardware_type = u16::from_be(HW_TYPE);
-        arp_header.protocol_type = u16::from_be(IPV4_PROTO);
-        arp_header.hardware_len = MAC_LEN as u8;
-        arp_header.protocol_len = IPV4_LEN as u8;
-        arp_header.opcode = u16::from_be(ARP_REQUEST);
-
-        arp_header.sender_ip = src_ip.octets();
-        arp_header.target_ip = dst_ip.octets();
-
-        self.socket.send_raw_packet(&buffer)?;
-
-        self.read_arp(|header| {
-            u16::from_be(header.opcode) == ARP_REPLY
-                && &net::Ipv4Addr::from(header.sender_ip) == dst_ip
+        // every reply, which is seen while waiting, is cached
+        self.socket.resolve_mac(*dst_ip, deadline, |ip, mac| {
+            self.lock_cache().insert(ip, mac);
         })
     }
-    /// Does an arp reply
+    /// Sends arp reply to the target, claiming that claimed_ip is at claimed_mac
+    ///
+    /// Ethernet frame is sent from the mac address of the socket interface
+    ///
     /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    /// use std::net::Ipv4Addr;
+    ///
+    /// let arp = Arp::new("wlan0").expect("initialize error");
+    /// let target_ip: Ipv4Addr = Ipv4Addr::from([192, 168, 1, 2]);
+    /// let target_mac: Mac = Mac::parse("02:00:5e:0a:0b:ff").expect("parse error");
+    /// let claimed_ip: Ipv4Addr = Ipv4Addr::from([192, 168, 1, 1]);
+    ///
+    /// arp.send_reply(&target_ip, &target_mac, &claimed_ip, arp.get_socket().get_src_mac())
+    ///     .expect("send error")
     /// ```
+    pub fn send_reply(
+        &self,
+        target_ip: &net::Ipv4Addr,
+        target_mac: &Mac,
+        claimed_ip: &net::Ipv4Addr,
+        claimed_mac: &Mac,
+    ) -> io::Result<()> {
+        self.is_at(claimed_mac, claimed_ip, target_mac, target_ip)
+    }
+    /// Does an arp reply
+    /// # Examples
+    /// ```no_run
     /// use cursock::*;
     /// use std::net::Ipv4Addr;
     ///
@@ -100,7 +165,7 @@ impl Arp {
     /// let ip_addr1: Ipv4Addr = Ipv4Addr::from([192, 168, 1, 1]);
     /// let mac_addr1: Mac = Mac::from([0xff; 6]);
     /// let ip_addr2: Ipv4Addr = Ipv4Addr::from([192, 168, 1, 2]);
-    /// arp.is_at(arp.get_src_mac(), &ip_addr1, &mac_addr1, &ip_addr2).expect("send error")
+    /// arp.is_at(arp.get_socket().get_src_mac(), &ip_addr1, &mac_addr1, &ip_addr2).expect("send error")
     /// ```
     pub fn is_at(
         &self,
@@ -116,11 +181,11 @@ impl Arp {
         let arp_header: &mut ArpHeader =
             unsafe { &mut *((buffer.as_mut_ptr() as usize + ETH_HEADER_SIZE) as *mut ArpHeader) };
 
-        eth_header.dest = dst_mac.clone().into();
+        eth_header.dest = (*dst_mac).into();
 
-        eth_header.source = self.socket.get_src_mac().clone().into();
-        arp_header.target_mac = dst_mac.clone().into();
-        arp_header.sender_mac = src_mac.clone().into();
+        eth_header.source = (*self.socket.get_src_mac()).into();
+        arp_header.target_mac = (*dst_mac).into();
+        arp_header.sender_mac = (*src_mac).into();
 
         eth_header.proto = u16::from_be(ARP_PROTO);
 
@@ -133,64 +198,580 @@ impl Arp {
         arp_header.sender_ip = src_ip.octets();
         arp_header.target_ip = dst_ip.octets();
 
-        self.socket.send_raw_packet(&buffer)
+        self.socket.send_raw_packet(&buffer)?;
+
+        Ok(())
+    }
+
+    /// cache only holds resolved addresses, so it's still usable after a panic
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, HashMap<net::Ipv4Addr, Mac>> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Destroys arp structure
+    ///
+    /// Socket is also closed when structure is dropped, so calling destroy is optional
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// let arp = cursock::Arp::new("wlan0").expect("initialize error");
+    /// arp.destroy()
+    /// ```
+    pub fn destroy(self) {
+        self.socket.destroy()
+    }
+
+    getters!(
+        pub get_socket(socket) -> Socket;
+    );
+}
+
+impl Socket {
+    /// Resolves mac address of the ipv4 address with an arp request
+    ///
+    /// Returns TimedOut error if nobody answered until timeout, replies from other addresses are skipped.
+    /// Unlike [`Arp`] it doesn't cache resolved addresses
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    /// use std::net::Ipv4Addr;
+    /// use std::time::Duration;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    ///
+    /// let mac: Mac = socket
+    ///     .arp_request(Ipv4Addr::new(192, 168, 0, 1), Duration::from_secs(1))
+    ///     .expect("arp error");
+    ///
+    /// println!("gateway is at {}", mac)
+    /// ```
+    pub fn arp_request(&self, target: net::Ipv4Addr, timeout: time::Duration) -> io::Result<Mac> {
+        self.resolve_mac(target, Some(time::Instant::now() + timeout), |_, _| {})
     }
+    /// Announces that the socket interface owns the ipv4 address with gratuitous arp request
+    ///
+    /// Request is broadcast with the address as both sender and target ip,
+    /// so hosts, which already know the address, update their caches without replying
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    /// use std::net::Ipv4Addr;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let options = SocketOptions::new().ignore_outgoing(true);
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new_with("lo", options), Socket::new("lo")) {
+    ///         reader.set_filter("arp").expect("filter error");
+    ///
+    ///         let ip = Ipv4Addr::new(198, 51, 100, 77);
+    ///         writer.send_gratuitous_arp(ip).expect("send error");
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         let length: usize = loop {
+    ///             let length: usize = reader.read_raw_packet(&mut buffer).expect("read error");
+    ///             if buffer[28..32] == ip.octets() {
+    ///                 break length;
+    ///             }
+    ///         };
+    ///
+    ///         let (eth_header, _) = EthernetHeader::parse(&buffer[..length]).expect("parse error");
+    ///
+    ///         assert_eq!(&buffer[..MAC_LEN], &[0xff; MAC_LEN]);
+    ///         assert_eq!(eth_header.get_ethertype(), &ARP_PROTO);
+    ///         // request, which targets the sender address
+    ///         assert_eq!(&buffer[20..22], &[0, 1]);
+    ///         assert_eq!(&buffer[28..32], &buffer[38..42])
+    ///     }
+    /// }
+    /// ```
+    pub fn send_gratuitous_arp(&self, ip: net::Ipv4Addr) -> io::Result<()> {
+        self.send_raw_packet(&who_has_frame(self, &ip, &ip))?;
 
-    fn read_arp<F>(&mut self, mut closure: F) -> io::Result<ArpResponse>
+        Ok(())
+    }
+    /// sends arp request and waits for the reply from the target, waiting isn't limited without deadline,
+    /// on_reply is called with sender addresses of every arp reply, which was read
+    pub(crate) fn resolve_mac<F>(
+        &self,
+        target: net::Ipv4Addr,
+        deadline: Option<time::Instant>,
+        mut on_reply: F,
+    ) -> io::Result<Mac>
     where
-        F: FnMut(&ArpHeader) -> bool,
+        F: FnMut(net::Ipv4Addr, Mac),
     {
+        let src_ip: net::Ipv4Addr = *self.get_src_ip().ok_or(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "To send ARP request you need to have ipv4 source address ({})",
+                self.get_adapter()
+            ),
+        ))?;
+
+        self.send_raw_packet(&who_has_frame(self, &src_ip, &target))?;
+
         const BUFFER_SIZE: usize = 60;
         let mut buffer: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
 
-        let eth_header: &EthHeader = unsafe { &*(buffer.as_ptr() as *mut EthHeader) };
-        let arp_header: &ArpHeader =
-            unsafe { &*((buffer.as_ptr() as usize + ETH_HEADER_SIZE) as *mut ArpHeader) };
-
         loop {
-            self.socket.read_raw_packet(&mut buffer)?;
+            let read: io::Result<usize> = match deadline {
+                Some(deadline) => {
+                    let timeout: time::Duration =
+                        deadline.saturating_duration_since(time::Instant::now());
+                    self.read_raw_packet_timeout(&mut buffer, timeout)
+                }
+                None => self.read_raw_packet(&mut buffer),
+            };
+
+            let length: usize = match read {
+                Ok(length) => length,
+                Err(err) if err.kind() == io::ErrorKind::TimedOut => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!("{} didn't reply to arp request", target),
+                    ))
+                }
+                Err(err) => return Err(err),
+            };
 
-            if u16::from_be(eth_header.proto) != ARP_PROTO {
+            if length < ETH_HEADER_SIZE + ARP_HEADER_SIZE {
                 continue;
             }
 
-            if u16::from_be(arp_header.opcode) == ARP_REPLY {
-                // storing mac addresses into cache
-                self.cache.insert(
-                    net::Ipv4Addr::from(arp_header.sender_ip),
-                    Mac::from(arp_header.sender_mac),
-                );
+            let eth_header: &EthHeader = unsafe { &*(buffer.as_ptr() as *const EthHeader) };
+            let arp_header: &ArpHeader =
+                unsafe { &*((buffer.as_ptr() as usize + ETH_HEADER_SIZE) as *const ArpHeader) };
+
+            if u16::from_be(eth_header.proto) != ARP_PROTO
+                || u16::from_be(arp_header.opcode) != ARP_REPLY
+            {
+                continue;
             }
 
-            if closure(arp_header) {
-                break;
+            let sender_ip: net::Ipv4Addr = net::Ipv4Addr::from(arp_header.sender_ip);
+            let sender_mac: Mac = Mac::from(arp_header.sender_mac);
+            on_reply(sender_ip, sender_mac);
+
+            if sender_ip == target {
+                return Ok(sender_mac);
             }
         }
+    }
+}
+
+/// builds broadcast arp request from the socket's addresses
+fn who_has_frame(
+    socket: &Socket,
+    src_ip: &net::Ipv4Addr,
+    dst_ip: &net::Ipv4Addr,
+) -> [u8; ETH_HEADER_SIZE + ARP_HEADER_SIZE] {
+    const BUFFER_SIZE: usize = ETH_HEADER_SIZE + ARP_HEADER_SIZE;
+    let mut buffer: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
+
+    let eth_header: &mut EthHeader = unsafe { &mut *(buffer.as_mut_ptr() as *mut EthHeader) };
+    let arp_header: &mut ArpHeader =
+        unsafe { &mut *((buffer.as_mut_ptr() as usize + ETH_HEADER_SIZE) as *mut ArpHeader) };
+
+    eth_header.dest = [0xff; MAC_LEN];
+
+    eth_header.source = (*socket.get_src_mac()).into();
+    arp_header.target_mac = [0; MAC_LEN];
+    arp_header.sender_mac = (*socket.get_src_mac()).into();
+
+    eth_header.proto = u16::from_be(ARP_PROTO);
 
-        let arp_response: ArpResponse = ArpResponse::new(
-            net::Ipv4Addr::from(arp_header.sender_ip),
-            Mac::from(arp_header.sender_mac),
-            net::Ipv4Addr::from(arp_header.target_ip),
-            Mac::from(arp_header.target_mac),
-        );
+    arp_header.hardware_type = u16::from_be(HW_TYPE);
+    arp_header.protocol_type = u16::from_be(IPV4_PROTO);
+    arp_header.hardware_len = MAC_LEN as u8;
+    arp_header.protocol_len = IPV4_LEN as u8;
+    arp_header.opcode = u16::from_be(ARP_REQUEST);
 
-        Ok(arp_response)
+    arp_header.sender_ip = src_ip.octets();
+    arp_header.target_ip = dst_ip.octets();
+
+    buffer
+}
+
+/// Parses arp packet of an ethernet frame, vlan tags are skipped
+///
+/// Returns InvalidData error if the frame isn't arp, is truncated
+/// or its addresses aren't 6 byte mac and ipv4 ones
+///
+/// # Examples
+/// ```
+/// use std::net::Ipv4Addr;
+/// use cursock::*;
+///
+/// let mut frame = vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02, 0, 0, 0, 0, 0x01, 0x08, 0x06];
+/// frame.extend_from_slice(&[0, 1, 0x08, 0, 6, 4, 0, 2]);
+/// frame.extend_from_slice(&[0x02, 0, 0, 0, 0, 0x01, 192, 168, 0, 1]);
+/// frame.extend_from_slice(&[0x02, 0, 0, 0, 0, 0x02, 192, 168, 0, 2]);
+///
+/// let packet = parse_arp(&frame).expect("parse error");
+///
+/// assert!(packet.is_reply());
+/// assert_eq!(packet.get_htype(), &HW_TYPE);
+/// assert_eq!(packet.get_ptype(), &IPV4_PROTO);
+/// assert_eq!(packet.get_sender_ip(), &Ipv4Addr::new(192, 168, 0, 1));
+/// assert!(*packet.get_sender_mac() == Mac::from([0x02, 0, 0, 0, 0, 0x01]));
+/// assert_eq!(packet.get_target_ip(), &Ipv4Addr::new(192, 168, 0, 2));
+///
+/// assert!(parse_arp(&frame[..frame.len() - 1]).is_err());
+///
+/// frame[12..14].copy_from_slice(&IPV4_PROTO.to_be_bytes());
+/// assert!(parse_arp(&frame).is_err())
+/// ```
+pub fn parse_arp(frame: &[u8]) -> io::Result<ArpPacket> {
+    let invalid = |reason: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid arp packet: {}", reason),
+        )
+    };
+
+    let (eth_header, _, packet) =
+        EthernetHeader::parse_tagged(frame).map_err(|err| invalid(err.to_string()))?;
+    if *eth_header.get_ethertype() != ARP_PROTO {
+        return Err(invalid(format!(
+            "ethertype is {:#06x}",
+            eth_header.get_ethertype()
+        )));
     }
 
-    /// Destroys arp structure
-    ///
-    /// # Examples
-    /// ```
-    /// use cursock::*;
-    ///
-    /// let arp = cursock::Arp::new("wlan0").expect("initialize error");
-    /// arp.destroy()
-    /// ```
-    pub fn destroy(&self) {
-        self.socket.destroy()
+    if packet.len() < ARP_HEADER_SIZE {
+        return Err(invalid(format!("{} bytes are too short", packet.len())));
+    }
+    if packet[4] as usize != MAC_LEN || packet[5] as usize != IPV4_LEN {
+        return Err(invalid(format!(
+            "address lengths are {} and {}",
+            packet[4], packet[5]
+        )));
     }
 
-    getters!(
-        pub get_socket(socket) -> Socket;
-    );
+    let mac = |offset: usize| {
+        let mut mac: [u8; MAC_LEN] = [0; MAC_LEN];
+        mac.copy_from_slice(&packet[offset..offset + MAC_LEN]);
+        Mac::from(mac)
+    };
+    let ip = |offset: usize| {
+        net::Ipv4Addr::new(
+            packet[offset],
+            packet[offset + 1],
+            packet[offset + 2],
+            packet[offset + 3],
+        )
+    };
+
+    Ok(ArpPacket {
+        htype: u16::from_be_bytes([packet[0], packet[1]]),
+        ptype: u16::from_be_bytes([packet[2], packet[3]]),
+        opcode: u16::from_be_bytes([packet[6], packet[7]]),
+        sender_mac: mac(8),
+        sender_ip: ip(14),
+        target_mac: mac(18),
+        target_ip: ip(24),
+    })
+}
+
+/// Reads ipv4 neighbor table of the os, without sending any packets
+///
+/// It's /proc/net/arp on linux, GetIpNetTable on windows and the routing table with RTF_LLINFO on macos.
+/// Entries, which aren't resolved yet, are kept with zero mac address and [`ArpState::Incomplete`]
+///
+/// # Examples
+/// ```
+/// use cursock::*;
+///
+/// #[cfg(target_os = "linux")]
+/// {
+///     for entry in arp_table().expect("read error") {
+///         if *entry.get_state() == ArpState::Incomplete {
+///             assert!(*entry.get_mac() == Mac::ZERO)
+///         }
+///     }
+///
+///     // loopback doesn't use arp
+///     assert!(arp_table_for_interface("lo").expect("read error").is_empty())
+/// }
+/// ```
+pub fn arp_table() -> io::Result<Vec<ArpEntry>> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let contents: String = std::fs::read_to_string("/proc/net/arp")?;
+
+        Ok(parse_proc_arp(&contents))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        arp_table_windows()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        arp_table_macos()
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "windows",
+        target_os = "macos"
+    )))]
+    {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not supported yet!", std::env::consts::OS),
+        ))
+    }
+}
+
+/// Reads entries of the neighbor table, which belong to the interface, see [`arp_table`]
+pub fn arp_table_for_interface(interface: &str) -> io::Result<Vec<ArpEntry>> {
+    let mut entries: Vec<ArpEntry> = arp_table()?;
+    entries.retain(|entry| entry.interface == interface);
+
+    Ok(entries)
+}
+
+/// parses lines like "192.168.0.1 0x1 0x2 aa:bb:cc:dd:ee:ff * eth0" after the header line
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn parse_proc_arp(contents: &str) -> Vec<ArpEntry> {
+    const ATF_COM: u32 = 0x02;
+    const ATF_PERM: u32 = 0x04;
+
+    let mut entries: Vec<ArpEntry> = Vec::new();
+
+    for line in contents.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 {
+            continue;
+        }
+
+        let ip: net::Ipv4Addr = match fields[0].parse() {
+            Ok(ip) => ip,
+            Err(_) => continue,
+        };
+        let flags: u32 = u32::from_str_radix(fields[2].trim_start_matches("0x"), 16).unwrap_or(0);
+        let mac: Mac = Mac::parse(fields[3]).unwrap_or(Mac::ZERO);
+
+        let state: ArpState = if flags & ATF_COM == 0 || mac == Mac::ZERO {
+            ArpState::Incomplete
+        } else if flags & ATF_PERM != 0 {
+            ArpState::Static
+        } else {
+            ArpState::Dynamic
+        };
+
+        entries.push(ArpEntry {
+            ip,
+            mac,
+            interface: fields[5].to_string(),
+            state,
+        })
+    }
+
+    entries
+}
+
+#[cfg(target_os = "windows")]
+fn arp_table_windows() -> io::Result<Vec<ArpEntry>> {
+    let mut size: u32 = 0;
+    let mut buffer: Vec<u32> = Vec::new();
+
+    // table can grow between the calls
+    loop {
+        let result: u32 = unsafe {
+            ccs::GetIpNetTable(
+                buffer.as_mut_ptr() as *mut ccs::MIB_IPNETTABLE,
+                &mut size,
+                0,
+            )
+        };
+
+        match result {
+            0 => break,
+            ccs::ERROR_NO_DATA => return Ok(Vec::new()),
+            // u32 elements keep the table aligned
+            ccs::ERROR_INSUFFICIENT_BUFFER => buffer = vec![0; (size as usize).div_ceil(4)],
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::ConnectionReset,
+                    format!("GetIpNetTable call failed with {} error code", result),
+                ))
+            }
+        }
+    }
+
+    let names: HashMap<u32, String> = list_interfaces()?
+        .into_iter()
+        .map(|adapter| (*adapter.get_index(), adapter.get_name().to_string()))
+        .collect();
+
+    let table: &ccs::MIB_IPNETTABLE = unsafe { &*(buffer.as_ptr() as *const ccs::MIB_IPNETTABLE) };
+    let rows: &[ccs::MIB_IPNETROW] =
+        unsafe { std::slice::from_raw_parts(table.table.as_ptr(), table.num_entries as usize) };
+
+    let entries: Vec<ArpEntry> = rows
+        .iter()
+        .map(|row| {
+            let mut mac: [u8; MAC_LEN] = [0; MAC_LEN];
+            if row.phys_addr_len as usize == MAC_LEN {
+                mac.copy_from_slice(&row.phys_addr[..MAC_LEN]);
+            }
+            let mac: Mac = Mac::from(mac);
+
+            let state: ArpState = match row.type_ {
+                _ if mac == Mac::ZERO => ArpState::Incomplete,
+                ccs::MIB_IPNET_TYPE_INVALID => ArpState::Incomplete,
+                ccs::MIB_IPNET_TYPE_STATIC => ArpState::Static,
+                _ => ArpState::Dynamic,
+            };
+
+            ArpEntry {
+                // dwAddr is in network byte order
+                ip: net::Ipv4Addr::from(u32::from_be(row.addr)),
+                mac,
+                interface: names
+                    .get(&row.index)
+                    .cloned()
+                    .unwrap_or_else(|| row.index.to_string()),
+                state,
+            }
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+/// dumps routes with link-layer information, every message is rt_msghdr,
+/// followed by the destination sockaddr_in and the gateway sockaddr_dl
+#[cfg(target_os = "macos")]
+fn arp_table_macos() -> io::Result<Vec<ArpEntry>> {
+    let mut mib: [i32; 6] = [
+        ccs::CTL_NET,
+        ccs::PF_ROUTE,
+        0,
+        ccs::AF_INET as i32,
+        ccs::NET_RT_FLAGS,
+        ccs::RTF_LLINFO,
+    ];
+
+    let mut buffer: Vec<u8> = Vec::new();
+    // table can grow between the calls
+    loop {
+        let mut size: usize = 0;
+        let result: i32 = unsafe {
+            ccs::sysctl(
+                mib.as_mut_ptr(),
+                mib.len() as u32,
+                std::ptr::null_mut(),
+                &mut size,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        buffer.resize(size + size / 2, 0);
+        let mut size: usize = buffer.len();
+
+        let result: i32 = unsafe {
+            ccs::sysctl(
+                mib.as_mut_ptr(),
+                mib.len() as u32,
+                buffer.as_mut_ptr() as *mut std::os::raw::c_void,
+                &mut size,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        if result == 0 {
+            buffer.truncate(size);
+            break;
+        }
+
+        let err: io::Error = io::Error::last_os_error();
+        if err.raw_os_error() != Some(ccs::ENOMEM) {
+            return Err(err);
+        }
+    }
+
+    let header_size: usize = std::mem::size_of::<ccs::rt_msghdr>();
+    let mut entries: Vec<ArpEntry> = Vec::new();
+    let mut offset: usize = 0;
+
+    while offset + header_size <= buffer.len() {
+        let header: ccs::rt_msghdr =
+            unsafe { std::ptr::read_unaligned(buffer[offset..].as_ptr() as *const ccs::rt_msghdr) };
+
+        let length: usize = header.rtm_msglen as usize;
+        if length < header_size || offset + length > buffer.len() {
+            break;
+        }
+
+        let message: &[u8] = &buffer[offset + header_size..offset + length];
+        offset += length;
+
+        // socket addresses are padded to 4 bytes
+        let addr_len: usize = match message.first() {
+            Some(0) | None => continue,
+            Some(length) => (*length as usize).next_multiple_of(4),
+        };
+        // sockaddr_dl can be shorter than its struct, so only its fixed part is required
+        if message.len() < std::mem::size_of::<ccs::sockaddr_in>() || message.len() < addr_len + 8 {
+            continue;
+        }
+
+        let dst: ccs::sockaddr_in =
+            unsafe { std::ptr::read_unaligned(message.as_ptr() as *const ccs::sockaddr_in) };
+        let link: &[u8] = &message[addr_len..];
+
+        // sdl_family, sdl_index, sdl_nlen and sdl_alen
+        let (family, index) = (link[1], u16::from_ne_bytes([link[2], link[3]]));
+        let (name_len, mac_len) = (link[5] as usize, link[6] as usize);
+
+        if dst.sin_family != ccs::AF_INET || family != ccs::AF_LINK {
+            continue;
+        }
+
+        // address follows the name in sdl_data
+        let mut mac: [u8; MAC_LEN] = [0; MAC_LEN];
+        if mac_len == MAC_LEN && 8 + name_len + MAC_LEN <= link.len() {
+            mac.copy_from_slice(&link[8 + name_len..8 + name_len + MAC_LEN]);
+        }
+        let mac: Mac = Mac::from(mac);
+
+        let state: ArpState = if mac == Mac::ZERO {
+            ArpState::Incomplete
+        } else if header.rtm_flags & ccs::RTF_STATIC != 0 {
+            ArpState::Static
+        } else {
+            ArpState::Dynamic
+        };
+
+        let mut name: [i8; ccs::IFNAMSIZ] = [0; ccs::IFNAMSIZ];
+        let interface: String =
+            match unsafe { ccs::if_indextoname(index as u32, name.as_mut_ptr()) }.is_null() {
+                true => index.to_string(),
+                false => str_from_cstr(name.as_ptr()),
+            };
+
+        entries.push(ArpEntry {
+            ip: net::Ipv4Addr::from(u32::from_be(dst.sin_addr.s_addr)),
+            mac,
+            interface,
+            state,
+        })
+    }
+
+    Ok(entries)
 }
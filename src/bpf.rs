@@ -0,0 +1,562 @@
+use std::io;
+
+#[cfg(target_os = "windows")]
+use std::ffi::CString;
+
+use crate::*;
+
+/// classic bpf instruction, fields are the same as in linux sock_filter and pcap bpf_insn
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BpfInstruction {
+    code: u16,
+    jt: u8,
+    jf: u8,
+    k: u32,
+}
+
+/// classic bpf program, which is run by the kernel or driver for every frame,
+/// frames for which it returns zero are dropped
+///
+/// # Example
+/// ```
+/// use cursock::*;
+///
+/// // tcpdump -dd arp
+/// let program = BpfProgram::from_dd(
+///     "{ 0x28, 0, 0, 0x0000000c },
+///      { 0x15, 0, 1, 0x00000806 },
+///      { 0x6, 0, 0, 0x00040000 },
+///      { 0x6, 0, 0, 0x00000000 },",
+/// )
+/// .expect("parse error");
+///
+/// assert_eq!(program.len(), 4);
+/// assert_eq!(program.get_instructions()[1], BpfInstruction::new(0x15, 0, 1, 0x806));
+///
+/// assert!(BpfProgram::from_dd("{ 0x28, 0, 0 }").is_err())
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BpfProgram {
+    instructions: Vec<BpfInstruction>,
+}
+
+impl BpfInstruction {
+    pub fn new(code: u16, jt: u8, jf: u8, k: u32) -> Self {
+        Self { code, jt, jf, k }
+    }
+
+    getters!(
+        pub get_code(code) -> u16;
+        pub get_jt(jt) -> u8;
+        pub get_jf(jf) -> u8;
+        pub get_k(k) -> u32;
+    );
+}
+
+impl BpfProgram {
+    pub fn new(instructions: Vec<BpfInstruction>) -> Self {
+        Self { instructions }
+    }
+
+    /// Parses program printed by `tcpdump -dd <filter>`
+    pub fn from_dd(text: &str) -> io::Result<Self> {
+        let mut instructions: Vec<BpfInstruction> = Vec::new();
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let body: &str = line
+                .trim_end_matches(',')
+                .trim()
+                .strip_prefix('{')
+                .and_then(|line| line.strip_suffix('}'))
+                .ok_or_else(|| invalid_instruction(line))?;
+
+            let fields: Vec<u32> = body
+                .split(',')
+                .map(|field| parse_number(field.trim()))
+                .collect::<Option<Vec<u32>>>()
+                .ok_or_else(|| invalid_instruction(line))?;
+
+            let (code, jt, jf, k) = match fields[..] {
+                [code, jt, jf, k] => (code, jt, jf, k),
+                _ => return Err(invalid_instruction(line)),
+            };
+
+            if code > u16::MAX as u32 || jt > u8::MAX as u32 || jf > u8::MAX as u32 {
+                return Err(invalid_instruction(line));
+            }
+
+            instructions.push(BpfInstruction::new(code as u16, jt as u8, jf as u8, k))
+        }
+
+        Ok(Self { instructions })
+    }
+
+    /// Returns count of instructions
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    getters!(
+        pub get_instructions(instructions) -> [BpfInstruction];
+    );
+
+    /// program, which drops every frame
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn drop_all() -> Self {
+        const BPF_RET_K: u16 = 0x06;
+
+        Self::new(vec![BpfInstruction::new(BPF_RET_K, 0, 0, 0)])
+    }
+}
+
+impl Socket {
+    /// Attaches bpf program to the socket, only frames accepted by it are read afterwards
+    ///
+    /// Frames queued before the filter was attached are discarded too
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     fn frame(proto: u16) -> Vec<u8> {
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x99]);
+    ///         frame[12..14].copy_from_slice(&proto.to_be_bytes());
+    ///         frame
+    ///     }
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new("lo"), Socket::new("lo")) {
+    ///         let arp = BpfProgram::from_dd(
+    ///             "{ 0x28, 0, 0, 0x0000000c },
+    ///              { 0x15, 0, 1, 0x00000806 },
+    ///              { 0x6, 0, 0, 0x00040000 },
+    ///              { 0x6, 0, 0, 0x00000000 },",
+    ///         )
+    ///         .expect("parse error");
+    ///
+    ///         reader.attach_filter(&arp).expect("filter error");
+    ///
+    ///         writer.send_raw_packet(&frame(IPV4_PROTO)).expect("send error");
+    ///         writer.send_raw_packet(&frame(ARP_PROTO)).expect("send error");
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         let mut received_arp = false;
+    ///
+    ///         let timeout = Duration::from_millis(200);
+    ///
+    ///         while reader.read_raw_packet_timeout(&mut buffer, timeout).is_ok() {
+    ///             assert_eq!(&buffer[12..14], &ARP_PROTO.to_be_bytes());
+    ///             received_arp |= buffer[11] == 0x99;
+    ///         }
+    ///
+    ///         assert!(received_arp)
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Previous filter is kept, if the program is rejected
+    /// ```
+    /// use std::time::Duration;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new("lo"), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:8f").expect("filter error");
+    ///
+    ///         // jump goes past the end of the program
+    ///         let invalid = BpfProgram::new(vec![
+    ///             BpfInstruction::new(0x15, 5, 0, 0x806),
+    ///             BpfInstruction::new(0x6, 0, 0, 0),
+    ///         ]);
+    ///         assert!(reader.attach_filter(&invalid).is_err());
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x8e]);
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x8f]);
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         reader.read_raw_packet_timeout(&mut buffer, Duration::from_secs(1)).expect("read error");
+    ///         assert_eq!(buffer[11], 0x8f)
+    ///     }
+    /// }
+    /// ```
+    pub fn attach_filter(&self, program: &BpfProgram) -> io::Result<()> {
+        if program.is_empty() || program.len() > u16::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("bpf program can't have {} instructions", program.len()),
+            ));
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let previous: Option<BpfProgram> = get_filter_linux(self.raw_socket())?;
+
+            // frames, which were received before attaching, are still in the queue
+            set_filter_linux(self.raw_socket(), &BpfProgram::drop_all())?;
+
+            let result: io::Result<()> = self
+                .discard_queued_packets()
+                .and_then(|_| set_filter_linux(self.raw_socket(), program));
+
+            if result.is_err() {
+                // drop all program mustn't stay attached, so previous filter is restored
+                let _ = match previous {
+                    Some(previous) => set_filter_linux(self.raw_socket(), &previous),
+                    None => self.detach_filter(),
+                };
+            }
+
+            result
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let mut instructions: Vec<ccs::bpf_insn> = program
+                .instructions
+                .iter()
+                .map(|instruction| ccs::bpf_insn {
+                    code: instruction.code,
+                    jt: instruction.jt,
+                    jf: instruction.jf,
+                    k: instruction.k,
+                })
+                .collect();
+
+            let mut bpf_program: ccs::bpf_program = ccs::bpf_program {
+                bf_len: instructions.len() as u32,
+                bf_insns: instructions.as_mut_ptr(),
+            };
+
+            set_filter_windows(self.raw_adapter(), &mut bpf_program)
+        }
+        #[cfg(target_os = "macos")]
+        {
+            set_filter_macos(self.raw_socket(), program)?;
+
+            // kernel flushes its buffer, but packets, which were already read from it, are still kept
+            self.discard_read_packets();
+
+            Ok(())
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+    /// Removes filter from the socket, so every frame is read again
+    pub fn detach_filter(&self) -> io::Result<()> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let result: i32 = unsafe {
+                ccs::setsockopt(
+                    self.raw_socket(),
+                    ccs::SOL_SOCKET,
+                    ccs::SO_DETACH_FILTER,
+                    std::ptr::null(),
+                    0,
+                )
+            };
+
+            if result < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(())
+        }
+        #[cfg(target_os = "windows")]
+        {
+            // empty expression accepts every frame
+            self.set_filter("")
+        }
+        #[cfg(target_os = "macos")]
+        {
+            // empty expression accepts every frame
+            self.attach_filter(&BpfProgram::compile("")?)
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+    /// Compiles tcpdump-style filter expression and attaches it to the socket
+    ///
+    /// On windows expression is compiled by npcap, elsewhere by [`BpfProgram::compile`],
+    /// which supports the common part of pcap-filter syntax, so the same expression works on both.
+    /// Invalid expression returns InvalidInput error with the compiler message
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     fn frame(src: u8, proto: u16) -> Vec<u8> {
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, src]);
+    ///         frame[12..14].copy_from_slice(&proto.to_be_bytes());
+    ///         frame
+    ///     }
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new("lo"), Socket::new("lo")) {
+    ///         reader
+    ///             .set_filter("ether src 02:00:00:00:00:98 and (arp or ip6)")
+    ///             .expect("filter error");
+    ///
+    ///         writer.send_raw_packet(&frame(0x98, IPV4_PROTO)).expect("send error");
+    ///         writer.send_raw_packet(&frame(0x99, ARP_PROTO)).expect("send error");
+    ///         writer.send_raw_packet(&frame(0x98, ARP_PROTO)).expect("send error");
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         let mut received = false;
+    ///
+    ///         let timeout = Duration::from_millis(200);
+    ///
+    ///         while reader.read_raw_packet_timeout(&mut buffer, timeout).is_ok() {
+    ///             assert_eq!(&buffer[11..14], &[0x98, 0x08, 0x06]);
+    ///             received = true;
+    ///         }
+    ///
+    ///         assert!(received);
+    ///         assert!(reader.set_filter("ether src").is_err())
+    ///     }
+    /// }
+    /// ```
+    pub fn set_filter(&self, filter: &str) -> io::Result<()> {
+        #[cfg(target_os = "windows")]
+        {
+            let adapter: *mut ccs::pcap = self.raw_adapter();
+            let filter: CString = CString::new(filter)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+
+            let mut bpf_program: ccs::bpf_program = ccs::bpf_program {
+                bf_len: 0,
+                bf_insns: std::ptr::null_mut(),
+            };
+
+            let result: i32 = unsafe {
+                ccs::pcap_compile(
+                    adapter,
+                    &mut bpf_program,
+                    filter.as_ptr(),
+                    1,
+                    ccs::PCAP_NETMASK_UNKNOWN,
+                )
+            };
+
+            if result < 0 {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, unsafe {
+                    str_from_cstr(ccs::pcap_geterr(adapter))
+                }));
+            }
+
+            let result: io::Result<()> = set_filter_windows(adapter, &mut bpf_program);
+
+            unsafe { ccs::pcap_freecode(&mut bpf_program) };
+
+            result
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            self.attach_filter(&BpfProgram::compile(filter)?)
+        }
+    }
+}
+
+impl Socket {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn discard_queued_packets(&self) -> io::Result<()> {
+        let mut buffer: [u8; 1] = [0];
+        loop {
+            match self.try_read_raw_packet(&mut buffer) {
+                Ok(_) => continue,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// reads classic program attached to the socket, None is returned if there is no one
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_filter_linux(socket: i32) -> io::Result<Option<BpfProgram>> {
+    // SO_GET_FILTER is the same option as SO_ATTACH_FILTER, its length is counted in instructions
+    let mut len: ccs::SocklenT = 0;
+    let result: i32 = unsafe {
+        ccs::getsockopt(
+            socket,
+            ccs::SOL_SOCKET,
+            ccs::SO_ATTACH_FILTER,
+            std::ptr::null_mut(),
+            &mut len,
+        )
+    };
+
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if len == 0 {
+        return Ok(None);
+    }
+
+    let mut filter: Vec<ccs::sock_filter> = vec![
+        ccs::sock_filter {
+            code: 0,
+            jt: 0,
+            jf: 0,
+            k: 0,
+        };
+        len as usize
+    ];
+
+    let result: i32 = unsafe {
+        ccs::getsockopt(
+            socket,
+            ccs::SOL_SOCKET,
+            ccs::SO_ATTACH_FILTER,
+            filter.as_mut_ptr() as *mut std::os::raw::c_void,
+            &mut len,
+        )
+    };
+
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    filter.truncate(len as usize);
+
+    Ok(Some(BpfProgram::new(
+        filter
+            .iter()
+            .map(|instruction| {
+                BpfInstruction::new(
+                    instruction.code,
+                    instruction.jt,
+                    instruction.jf,
+                    instruction.k,
+                )
+            })
+            .collect(),
+    )))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_filter_linux(socket: i32, program: &BpfProgram) -> io::Result<()> {
+    let mut filter: Vec<ccs::sock_filter> = program
+        .instructions
+        .iter()
+        .map(|instruction| ccs::sock_filter {
+            code: instruction.code,
+            jt: instruction.jt,
+            jf: instruction.jf,
+            k: instruction.k,
+        })
+        .collect();
+
+    let fprog: ccs::sock_fprog = ccs::sock_fprog {
+        len: filter.len() as u16,
+        filter: filter.as_mut_ptr(),
+    };
+
+    let result: i32 = unsafe {
+        ccs::setsockopt(
+            socket,
+            ccs::SOL_SOCKET,
+            ccs::SO_ATTACH_FILTER,
+            &fprog as *const ccs::sock_fprog as *const std::os::raw::c_void,
+            std::mem::size_of::<ccs::sock_fprog>() as ccs::SocklenT,
+        )
+    };
+
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn set_filter_windows(adapter: *mut ccs::pcap, program: &mut ccs::bpf_program) -> io::Result<()> {
+    if unsafe { ccs::pcap_setfilter(adapter, program) } < 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            format!("can\'t set filter due to {}", unsafe {
+                str_from_cstr(ccs::pcap_geterr(adapter))
+            }),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn set_filter_macos(socket: i32, program: &BpfProgram) -> io::Result<()> {
+    let mut instructions: Vec<ccs::bpf_insn> = program
+        .instructions
+        .iter()
+        .map(|instruction| ccs::bpf_insn {
+            code: instruction.code,
+            jt: instruction.jt,
+            jf: instruction.jf,
+            k: instruction.k,
+        })
+        .collect();
+
+    let mut bpf_program: ccs::bpf_program = ccs::bpf_program {
+        bf_len: instructions.len() as u32,
+        bf_insns: instructions.as_mut_ptr(),
+    };
+
+    if unsafe { ccs::ioctl(socket, ccs::BIOCSETF, &mut bpf_program) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// parses decimal or 0x prefixed hex number, it's shared with filter expressions
+pub(crate) fn parse_number(number: &str) -> Option<u32> {
+    match number.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => number.parse::<u32>().ok(),
+    }
+}
+
+fn invalid_instruction(line: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("invalid bpf instruction \"{}\"", line),
+    )
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+
+use crate::*;
+
+/// features, which are available in the current environment
+///
+/// # Example
+/// ```no_run
+/// use cursock::*;
+///
+/// let capabilities = capabilities();
+///
+/// if !*capabilities.get_raw_socket() {
+///     eprintln!("raw sockets aren't available, try running as root");
+/// }
+///
+/// println!("{}", capabilities)
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Capabilities {
+    // raw sockets can be opened, on linux requires root or CAP_NET_RAW
+    raw_socket: bool,
+    // kernel supports PACKET_MMAP ring buffers (linux only)
+    packet_mmap: bool,
+    // kernel or driver timestamps received packets
+    timestamping: bool,
+    // packet sockets can be joined into fanout groups (linux only)
+    fanout: bool,
+    // npcap is installed (windows only)
+    npcap: bool,
+    // version string of the loaded pcap library (windows only)
+    pcap_version: Option<String>,
+}
+
+impl Capabilities {
+    getters!(
+        pub get_raw_socket(raw_socket) -> bool;
+        pub get_packet_mmap(packet_mmap) -> bool;
+        pub get_timestamping(timestamping) -> bool;
+        pub get_fanout(fanout) -> bool;
+        pub get_npcap(npcap) -> bool;
+        pub get_pcap_version(pcap_version) -> Option<String>;
+    );
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let yes_no = |value: bool| match value {
+            true => "yes",
+            false => "no",
+        };
+
+        writeln!(f, "raw socket: {}", yes_no(self.raw_socket))?;
+        writeln!(f, "packet mmap: {}", yes_no(self.packet_mmap))?;
+        writeln!(f, "timestamping: {}", yes_no(self.timestamping))?;
+        writeln!(f, "fanout: {}", yes_no(self.fanout))?;
+        write!(f, "npcap: ")?;
+        match &self.pcap_version {
+            Some(version) => write!(f, "{} ({})", yes_no(self.npcap), version),
+            None => write!(f, "{}", yes_no(self.npcap)),
+        }
+    }
+}
+
+/// Probes the environment for features, which depend on os, privileges, kernel version and installed drivers
+///
+/// Probing never fails, unavailable features are just reported as false.
+/// On linux features of packet sockets can only be probed by opening one,
+/// so packet_mmap and fanout are false when raw sockets are not permitted
+///
+/// # Examples
+/// ```no_run
+/// use cursock::*;
+///
+/// let capabilities = capabilities();
+///
+/// let socket = Socket::new("eth0").expect("initialize error");
+/// let mut buffer = [0; 1514];
+///
+/// if *capabilities.get_timestamping() {
+///     // request packet timestamps
+/// }
+///
+/// socket.read_raw_packet(&mut buffer).expect("read error");
+/// ```
+pub fn capabilities() -> Capabilities {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        capabilities_linux()
+    }
+    #[cfg(target_os = "windows")]
+    {
+        capabilities_windows()
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "windows")))]
+    {
+        Capabilities {
+            raw_socket: false,
+            packet_mmap: false,
+            timestamping: false,
+            fanout: false,
+            npcap: false,
+            pcap_version: None,
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn capabilities_linux() -> Capabilities {
+    // protocol 0 keeps the socket from receiving any packets while probing
+    let socket: i32 = match crate::socket::open_cloexec_socket(ccs::AF_PACKET, ccs::SOCK_RAW, 0) {
+        Ok(socket) => socket,
+        Err(_) => {
+            // timestamping doesn't depend on packet sockets and can still be checked
+            let timestamping: bool =
+                match crate::socket::open_cloexec_socket(ccs::AF_INET, ccs::SOCK_DGRAM, 0) {
+                    Ok(socket) => {
+                        let supported: bool = probe_timestamping(socket);
+                        unsafe { ccs::close(socket) };
+                        supported
+                    }
+                    Err(_) => false,
+                };
+
+            return Capabilities {
+                raw_socket: false,
+                packet_mmap: false,
+                timestamping,
+                fanout: false,
+                npcap: false,
+                pcap_version: None,
+            };
+        }
+    };
+
+    let version: i32 = ccs::TPACKET_V2;
+    let packet_mmap: bool = unsafe {
+        ccs::setsockopt(
+            socket,
+            ccs::SOL_PACKET,
+            ccs::PACKET_VERSION,
+            &version as *const i32 as *const std::os::raw::c_void,
+            std::mem::size_of::<i32>() as ccs::SocklenT,
+        )
+    } == 0;
+
+    let mut group: u32 = 0;
+    let mut group_len: ccs::SocklenT = std::mem::size_of::<u32>() as ccs::SocklenT;
+    let fanout: bool = unsafe {
+        ccs::getsockopt(
+            socket,
+            ccs::SOL_PACKET,
+            ccs::PACKET_FANOUT,
+            &mut group as *mut u32 as *mut std::os::raw::c_void,
+            &mut group_len,
+        )
+    } == 0;
+
+    let timestamping: bool = probe_timestamping(socket);
+
+    unsafe { ccs::close(socket) };
+
+    Capabilities {
+        raw_socket: true,
+        packet_mmap,
+        timestamping,
+        fanout,
+        npcap: false,
+        pcap_version: None,
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn probe_timestamping(socket: i32) -> bool {
+    let enable: i32 = 1;
+
+    let result: i32 = unsafe {
+        ccs::setsockopt(
+            socket,
+            ccs::SOL_SOCKET,
+            ccs::SO_TIMESTAMPNS,
+            &enable as *const i32 as *const std::os::raw::c_void,
+            std::mem::size_of::<i32>() as ccs::SocklenT,
+        )
+    };
+
+    result == 0
+}
+
+#[cfg(target_os = "windows")]
+fn capabilities_windows() -> Capabilities {
+    let system_root: String =
+        std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
+    let npcap: bool = std::path::Path::new(&format!("{}\\System32\\Npcap", system_root)).is_dir();
+
+    let version: *const i8 = unsafe { ccs::pcap_lib_version() };
+    let pcap_version: Option<String> = match version.is_null() {
+        true => None,
+        false => Some(str_from_cstr(version)),
+    };
+
+    // devices are only listed when the driver is loaded
+    let raw_socket: bool = match list_pcap_devices() {
+        Ok(devices) => !devices.is_empty(),
+        Err(_) => false,
+    };
+
+    Capabilities {
+        raw_socket,
+        packet_mmap: false,
+        timestamping: raw_socket,
+        fanout: false,
+        npcap,
+        pcap_version,
+    }
+}
@@ -1,32 +1,187 @@
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use super::IoctlRequest;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
 pub const AF_PACKET: i32 = 17;
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
 pub const AF_INET: i32 = 2;
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
 pub const AF_INET6: i32 = 10;
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
 pub const SOCK_RAW: i32 = 3;
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
 pub const SOCK_DGRAM: i32 = 2;
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SOCK_CLOEXEC: i32 = 0o2000000;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const F_GETFD: i32 = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const F_SETFD: i32 = 2;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const FD_CLOEXEC: i32 = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const F_DUPFD_CLOEXEC: i32 = 1030;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const F_GETFL: i32 = 3;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const F_SETFL: i32 = 4;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const O_NONBLOCK: i32 = 0o4000;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const MSG_DONTWAIT: i32 = 0x40;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const UIO_MAXIOV: usize = 1024;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const POLLIN: i16 = 0x001;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const POLLOUT: i16 = 0x004;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const EINTR: i32 = 4;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const EINVAL: i32 = 22;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IPPROTO_IP: i32 = 0;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IP_HDRINCL: i32 = 3;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SOL_SOCKET: i32 = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SOL_PACKET: i32 = 263;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_TIMESTAMPNS: i32 = 35;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_RCVBUF: i32 = 8;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_ATTACH_FILTER: i32 = 26;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_DETACH_FILTER: i32 = 27;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_VERSION: i32 = 10;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_IGNORE_OUTGOING: i32 = 23;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_AUXDATA: i32 = 8;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_ADD_MEMBERSHIP: i32 = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_DROP_MEMBERSHIP: i32 = 2;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_MR_PROMISC: u16 = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_FANOUT: i32 = 18;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const TPACKET_V2: i32 = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_RX_RING: i32 = 5;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_STATISTICS: i32 = 6;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const TP_STATUS_KERNEL: u32 = 0;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const TP_STATUS_USER: u32 = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const TP_STATUS_VLAN_VALID: u32 = 0x10;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const TP_STATUS_VLAN_TPID_VALID: u32 = 0x40;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PROT_READ: i32 = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PROT_WRITE: i32 = 2;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const MAP_SHARED: i32 = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
 pub const ETH_P_ARP: i32 = 0x0806;
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
 pub const ETH_P_ALL: i32 = 0x0003;
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
 pub const ETH_P_IP: i32 = 0x0800;
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
 pub const ARPHRD_ETHER: u16 = 1;
-#[cfg(target_os = "linux")]
-pub const SIOCGIFHWADDR: u64 = 0x8927;
-#[cfg(target_os = "linux")]
-pub const SIOCGIFINDEX: u64 = 0x8933;
-#[cfg(target_os = "linux")]
-pub const SIOCGIFADDR: u64 = 0x8915;
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const ARPHRD_IEEE1394: u16 = 24;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const ARPHRD_INFINIBAND: u16 = 32;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const ARPHRD_PPP: u16 = 512;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const ARPHRD_RAWIP: u16 = 519;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const ARPHRD_TUNNEL: u16 = 768;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const ARPHRD_TUNNEL6: u16 = 769;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const ARPHRD_SIT: u16 = 776;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const ARPHRD_IPGRE: u16 = 778;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const ARPHRD_IEEE802154: u16 = 804;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const ARPHRD_NONE: u16 = 0xfffe;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SIOCGIFHWADDR: IoctlRequest = 0x8927;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SIOCGIFINDEX: IoctlRequest = 0x8933;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SIOCGIFADDR: IoctlRequest = 0x8915;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SIOCGIFFLAGS: IoctlRequest = 0x8913;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SIOCGIFMTU: IoctlRequest = 0x8921;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SIOCGIFNETMASK: IoctlRequest = 0x891b;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SIOCGIFBRDADDR: IoctlRequest = 0x8919;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IFF_UP: i16 = 0x1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IFF_BROADCAST: i16 = 0x2;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IFF_LOOPBACK: i16 = 0x8;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const ENODEV: i32 = 19;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const EMSGSIZE: i32 = 90;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SIOCETHTOOL: IoctlRequest = 0x8946;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_HOST: u8 = 0;
+#[cfg(any(target_os = "linux", target_os = "android"))]
 pub const PACKET_BROADCAST: u8 = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_MULTICAST: u8 = 2;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_OTHERHOST: u8 = 3;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const PACKET_OUTGOING: u8 = 4;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IFNAMSIZ: usize = 16;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const ETHTOOL_GLINKSETTINGS: u32 = 0x0000004c;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SPEED_UNKNOWN: u32 = u32::MAX;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const DUPLEX_HALF: u8 = 0x00;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const DUPLEX_FULL: u8 = 0x01;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const AUTONEG_ENABLE: u8 = 0x01;
 #[cfg(target_os = "windows")]
 pub const PCAP_OPENFLAG_PROMISCUOUS: i32 = 1;
 #[cfg(target_os = "windows")]
+pub const PCAP_TSTAMP_PRECISION_MICRO: i32 = 0;
+#[cfg(target_os = "windows")]
+pub const PCAP_ERROR_PERM_DENIED: i32 = -8;
+#[cfg(target_os = "windows")]
+pub const PCAP_D_IN: i32 = 1;
+#[cfg(target_os = "windows")]
+pub const PCAP_TSTAMP_PRECISION_NANO: i32 = 1;
+#[cfg(target_os = "windows")]
+pub const PCAP_NETMASK_UNKNOWN: u32 = 0xffffffff;
+#[cfg(target_os = "windows")]
+pub const IF_OPER_STATUS_UP: u32 = 1;
+#[cfg(target_os = "windows")]
+pub const IF_TYPE_SOFTWARE_LOOPBACK: u32 = 24;
+#[cfg(target_os = "windows")]
 pub const AF_INET: usize = 2;
 #[cfg(target_os = "windows")]
 pub const AF_INET6: usize = 23;
@@ -37,4 +192,93 @@ pub const GAA_FLAG_INCLUDE_PREFIX: u8 = 0x0010;
 #[cfg(target_os = "windows")]
 pub const GAA_FLAG_INCLUDE_GATEWAYS: u8 = 0x0080;
 #[cfg(target_os = "windows")]
+pub const ERROR_INSUFFICIENT_BUFFER: u32 = 122;
+#[cfg(target_os = "windows")]
+pub const ERROR_NO_DATA: u32 = 232;
+#[cfg(target_os = "windows")]
+pub const INFINITE: u32 = 0xffffffff;
+#[cfg(target_os = "windows")]
+pub const WAIT_FAILED: u32 = 0xffffffff;
+#[cfg(target_os = "windows")]
+pub const MIB_IPNET_TYPE_INVALID: u32 = 2;
+#[cfg(target_os = "windows")]
+pub const MIB_IPNET_TYPE_STATIC: u32 = 4;
+#[cfg(target_os = "windows")]
 pub type TimeT = i64; // Should be changed after 292 billion years, due to overflow
+
+#[cfg(target_os = "macos")]
+pub const AF_INET: u8 = 2;
+#[cfg(target_os = "macos")]
+pub const AF_INET6: u8 = 30;
+#[cfg(target_os = "macos")]
+pub const AF_LINK: u8 = 18;
+#[cfg(target_os = "macos")]
+pub const O_RDWR: i32 = 0x0002;
+#[cfg(target_os = "macos")]
+pub const O_NONBLOCK: i32 = 0x0004;
+#[cfg(target_os = "macos")]
+pub const F_DUPFD_CLOEXEC: i32 = 67;
+#[cfg(target_os = "macos")]
+pub const F_GETFL: i32 = 3;
+#[cfg(target_os = "macos")]
+pub const F_SETFL: i32 = 4;
+#[cfg(target_os = "macos")]
+pub const F_SETFD: i32 = 2;
+#[cfg(target_os = "macos")]
+pub const FD_CLOEXEC: i32 = 1;
+#[cfg(target_os = "macos")]
+pub const POLLIN: i16 = 0x001;
+#[cfg(target_os = "macos")]
+pub const POLLOUT: i16 = 0x004;
+#[cfg(target_os = "macos")]
+pub const EINTR: i32 = 4;
+#[cfg(target_os = "macos")]
+pub const EBUSY: i32 = 16;
+#[cfg(target_os = "macos")]
+pub const ENODEV: i32 = 19;
+#[cfg(target_os = "macos")]
+pub const ENXIO: i32 = 6;
+#[cfg(target_os = "macos")]
+pub const ENOMEM: i32 = 12;
+#[cfg(target_os = "macos")]
+pub const IFF_UP: u32 = 0x1;
+#[cfg(target_os = "macos")]
+pub const IFF_BROADCAST: u32 = 0x2;
+#[cfg(target_os = "macos")]
+pub const IFF_LOOPBACK: u32 = 0x8;
+#[cfg(target_os = "macos")]
+pub const IFNAMSIZ: usize = 16;
+#[cfg(target_os = "macos")]
+pub const SOCK_DGRAM: i32 = 2;
+#[cfg(target_os = "macos")]
+pub const SIOCGIFMTU: u64 = 0xc0206933;
+#[cfg(target_os = "macos")]
+pub const BIOCGBLEN: u64 = 0x40044266;
+#[cfg(target_os = "macos")]
+pub const BIOCSBLEN: u64 = 0xc0044266;
+#[cfg(target_os = "macos")]
+pub const BIOCSETIF: u64 = 0x8020426c;
+#[cfg(target_os = "macos")]
+pub const BIOCSETF: u64 = 0x80104267;
+#[cfg(target_os = "macos")]
+pub const BIOCSSEESENT: u64 = 0x80044277;
+#[cfg(target_os = "macos")]
+pub const BIOCPROMISC: u64 = 0x20004269;
+#[cfg(target_os = "macos")]
+pub const BIOCGSTATS: u64 = 0x4008426f;
+#[cfg(target_os = "macos")]
+pub const BIOCIMMEDIATE: u64 = 0x80044270;
+#[cfg(target_os = "macos")]
+pub const BIOCSHDRCMPLT: u64 = 0x80044275;
+#[cfg(any(target_os = "macos", test))]
+pub const BPF_ALIGNMENT: usize = 4;
+#[cfg(target_os = "macos")]
+pub const CTL_NET: i32 = 4;
+#[cfg(target_os = "macos")]
+pub const PF_ROUTE: i32 = 17;
+#[cfg(target_os = "macos")]
+pub const NET_RT_FLAGS: i32 = 2;
+#[cfg(target_os = "macos")]
+pub const RTF_LLINFO: i32 = 0x400;
+#[cfg(target_os = "macos")]
+pub const RTF_STATIC: i32 = 0x800;
@@ -104,3 +104,19 @@ pub struct IP_ADAPTER_ANYCAST_ADDRESS {
     pub next: *mut IP_ADAPTER_ANYCAST_ADDRESS,
     pub address: SOCKET_ADDRESS,
 }
+
+#[repr(C)]
+pub struct MIB_IPNETROW {
+    pub index: u32,
+    pub phys_addr_len: u32,
+    pub phys_addr: [u8; MAX_ADAPTER_ADDRESS_LENGTH],
+    pub addr: u32,
+    pub type_: u32,
+}
+
+/// num_entries rows follow the header, table is declared with one row
+#[repr(C)]
+pub struct MIB_IPNETTABLE {
+    pub num_entries: u32,
+    pub table: [MIB_IPNETROW; 1],
+}
@@ -10,11 +10,21 @@ pub use consts::*;
 pub use iphlpapi::*;
 pub use structs::*;
 
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
 pub type SocklenT = u32;
-
-#[link(name = "cursock")]
 #[cfg(target_os = "linux")]
+pub type IoctlRequest = u64;
+/// bionic declares ioctl request as int
+#[cfg(target_os = "android")]
+pub type IoctlRequest = i32;
+#[cfg(target_os = "linux")]
+pub type NfdsT = u64;
+#[cfg(target_os = "android")]
+pub type NfdsT = u32;
+
+// libcursock is only built for linux, bionic provides everything on android
+#[cfg_attr(target_os = "linux", link(name = "cursock"))]
+#[cfg(any(target_os = "linux", target_os = "android"))]
 extern "C" {
     pub fn recvfrom(
         socket: i32,
@@ -32,13 +42,67 @@ extern "C" {
         dest_addr: *const sockaddr,
         addrlen: SocklenT,
     ) -> isize;
+    pub fn recvmsg(sockfd: i32, msg: *mut msghdr, flags: i32) -> isize;
+    pub fn sendmmsg(sockfd: i32, msgvec: *mut mmsghdr, vlen: u32, flags: i32) -> i32;
     pub fn bind(sockfd: i32, addr: *const sockaddr, addrlen: SocklenT) -> i32;
     pub fn socket(domain: i32, type_: i32, protocol: i32) -> i32;
-    pub fn ioctl(fd: i32, request: u64, ...) -> i32;
+    pub fn ioctl(fd: i32, request: IoctlRequest, ...) -> i32;
+    pub fn fcntl(fd: i32, cmd: i32, ...) -> i32;
+    pub fn poll(fds: *mut pollfd, nfds: NfdsT, timeout: i32) -> i32;
+    pub fn if_indextoname(ifindex: u32, ifname: *mut i8) -> *mut i8;
+    pub fn getifaddrs(ifap: *mut *mut ifaddrs) -> i32;
+    pub fn freeifaddrs(ifa: *mut ifaddrs);
+    pub fn setsockopt(
+        sockfd: i32,
+        level: i32,
+        optname: i32,
+        optval: *const c_void,
+        optlen: SocklenT,
+    ) -> i32;
+    pub fn getsockopt(
+        sockfd: i32,
+        level: i32,
+        optname: i32,
+        optval: *mut c_void,
+        optlen: *mut SocklenT,
+    ) -> i32;
+    pub fn mmap(
+        addr: *mut c_void,
+        length: usize,
+        prot: i32,
+        flags: i32,
+        fd: i32,
+        offset: i64,
+    ) -> *mut c_void;
+    pub fn munmap(addr: *mut c_void, length: usize) -> i32;
     pub fn perror(str: *const i8);
     pub fn close(fd: i32) -> i32;
 }
 
+#[cfg(target_os = "macos")]
+extern "C" {
+    pub fn open(path: *const i8, oflag: i32, ...) -> i32;
+    pub fn socket(domain: i32, type_: i32, protocol: i32) -> i32;
+    pub fn read(fd: i32, buf: *mut c_void, nbyte: usize) -> isize;
+    pub fn write(fd: i32, buf: *const c_void, nbyte: usize) -> isize;
+    pub fn ioctl(fd: i32, request: u64, ...) -> i32;
+    pub fn fcntl(fd: i32, cmd: i32, ...) -> i32;
+    pub fn poll(fds: *mut pollfd, nfds: u32, timeout: i32) -> i32;
+    pub fn if_nametoindex(ifname: *const i8) -> u32;
+    pub fn if_indextoname(ifindex: u32, ifname: *mut i8) -> *mut i8;
+    pub fn getifaddrs(ifap: *mut *mut ifaddrs) -> i32;
+    pub fn freeifaddrs(ifa: *mut ifaddrs);
+    pub fn sysctl(
+        name: *mut i32,
+        namelen: u32,
+        oldp: *mut c_void,
+        oldlenp: *mut usize,
+        newp: *mut c_void,
+        newlen: usize,
+    ) -> i32;
+    pub fn close(fd: i32) -> i32;
+}
+
 #[link(name = "iphlpapi")]
 #[cfg(target_os = "windows")]
 extern "C" {
@@ -49,6 +113,8 @@ extern "C" {
         AdapterAddresses: *mut IP_ADAPTER_ADDRESSES,
         SizePointer: *mut u32,
     ) -> u32;
+    pub fn GetIpNetTable(IpNetTable: *mut MIB_IPNETTABLE, SizePointer: *mut u32, Order: i32)
+        -> u32;
 }
 
 #[link(name = "wpcap", kind = "static")]
@@ -62,14 +128,54 @@ extern "C" {
         auth: *mut pcap_rmtauth,
         errbuf: *mut i8,
     ) -> *mut pcap;
+    pub fn pcap_create(source: *const i8, errbuf: *mut i8) -> *mut pcap;
+    pub fn pcap_set_snaplen(_: *mut pcap, snaplen: i32) -> i32;
+    pub fn pcap_set_promisc(_: *mut pcap, promisc: i32) -> i32;
+    pub fn pcap_set_timeout(_: *mut pcap, to_ms: i32) -> i32;
+    pub fn pcap_set_tstamp_precision(_: *mut pcap, tstamp_precision: i32) -> i32;
+    pub fn pcap_set_buffer_size(_: *mut pcap, buffer_size: i32) -> i32;
+    pub fn pcap_setbuff(_: *mut pcap, dim: i32) -> i32;
+    pub fn pcap_activate(_: *mut pcap) -> i32;
+    pub fn pcap_close(_: *mut pcap);
     pub fn pcap_findalldevs_ex(
         source: *const i8,
         auth: *mut pcap_rmtauth,
         alldevs: *mut *mut pcap_if,
         errbuf: *mut i8,
     ) -> i32;
+    pub fn pcap_findalldevs(alldevs: *mut *mut pcap_if, errbuf: *mut i8) -> i32;
+    pub fn pcap_freealldevs(alldevs: *mut pcap_if);
     pub fn pcap_next_ex(_: *mut pcap, _: *mut *mut pcap_pkthdr, _: *mut *const u8) -> i32;
     pub fn pcap_inject(_: *mut pcap, _: *const c_void, _: usize) -> i32;
     pub fn pcap_sendpacket(_: *mut pcap, _: *const u8, _: i32) -> i32;
+    pub fn pcap_sendqueue_alloc(memsize: u32) -> *mut pcap_send_queue;
+    pub fn pcap_sendqueue_destroy(queue: *mut pcap_send_queue);
+    pub fn pcap_sendqueue_queue(
+        queue: *mut pcap_send_queue,
+        pkt_header: *const pcap_pkthdr,
+        pkt_data: *const u8,
+    ) -> i32;
+    pub fn pcap_sendqueue_transmit(_: *mut pcap, queue: *mut pcap_send_queue, sync: i32) -> u32;
     pub fn pcap_geterr(_: *mut pcap) -> *mut i8;
+    pub fn pcap_lib_version() -> *const i8;
+    pub fn pcap_compile(
+        _: *mut pcap,
+        fp: *mut bpf_program,
+        str: *const i8,
+        optimize: i32,
+        netmask: u32,
+    ) -> i32;
+    pub fn pcap_setfilter(_: *mut pcap, fp: *mut bpf_program) -> i32;
+    pub fn pcap_freecode(fp: *mut bpf_program);
+    pub fn pcap_stats(_: *mut pcap, ps: *mut pcap_stat) -> i32;
+    pub fn pcap_setnonblock(_: *mut pcap, nonblock: i32, errbuf: *mut i8) -> i32;
+    pub fn pcap_getnonblock(_: *mut pcap, errbuf: *mut i8) -> i32;
+    pub fn pcap_setdirection(_: *mut pcap, d: i32) -> i32;
+    pub fn pcap_getevent(_: *mut pcap) -> *mut c_void;
+}
+
+#[link(name = "kernel32")]
+#[cfg(target_os = "windows")]
+extern "system" {
+    pub fn WaitForSingleObject(hHandle: *mut c_void, dwMilliseconds: u32) -> u32;
 }
@@ -1,5 +1,5 @@
 #[derive(Clone, Copy)]
-#[cfg(any(target_os = "linux", target_os = "windows"))]
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "windows"))]
 #[repr(C)]
 pub struct sockaddr {
     pub sa_family: u16,
@@ -7,7 +7,7 @@ pub struct sockaddr {
 }
 
 #[derive(Clone)]
-#[cfg(any(target_os = "linux", target_os = "windows"))]
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "windows"))]
 #[repr(C)]
 pub struct sockaddr_in {
     pub sin_family: i16,
@@ -17,14 +17,14 @@ pub struct sockaddr_in {
 }
 
 #[derive(Clone)]
-#[cfg(any(target_os = "linux", target_os = "windows"))]
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "windows"))]
 #[repr(C)]
 pub struct in_addr {
     pub s_addr: u32,
 }
 
 #[derive(Clone)]
-#[cfg(any(target_os = "linux", target_os = "windows"))]
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "windows"))]
 #[repr(C)]
 pub struct sockaddr_in6 {
     pub sin6_family: u16,
@@ -40,10 +40,18 @@ pub union in6_addr {
     pub s6_addr: [u8; 16],
     pub s6_addr16: [u16; 8],
     pub s6_addr32: [u32; 4],
-    pub s6_addr128: u128,
 }
 
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct packet_mreq {
+    pub mr_ifindex: i32,
+    pub mr_type: u16,
+    pub mr_alen: u16,
+    pub mr_address: [u8; 8],
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
 #[repr(C)]
 pub struct sockaddr_ll {
     pub sll_family: u16,
@@ -55,14 +63,135 @@ pub struct sockaddr_ll {
     pub sll_addr: [u8; 8],
 }
 
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct iovec {
+    pub iov_base: *mut std::os::raw::c_void,
+    pub iov_len: usize,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct msghdr {
+    pub msg_name: *mut std::os::raw::c_void,
+    pub msg_namelen: u32,
+    pub msg_iov: *mut iovec,
+    pub msg_iovlen: usize,
+    pub msg_control: *mut std::os::raw::c_void,
+    pub msg_controllen: usize,
+    pub msg_flags: i32,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct mmsghdr {
+    pub msg_hdr: msghdr,
+    pub msg_len: u32,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct cmsghdr {
+    pub cmsg_len: usize,
+    pub cmsg_level: i32,
+    pub cmsg_type: i32,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct tpacket_auxdata {
+    pub tp_status: u32,
+    pub tp_len: u32,
+    pub tp_snaplen: u32,
+    pub tp_mac: u16,
+    pub tp_net: u16,
+    pub tp_vlan_tci: u16,
+    pub tp_vlan_tpid: u16,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct timespec {
+    pub tv_sec: i64,
+    pub tv_nsec: i64,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct tpacket_stats {
+    pub tp_packets: u32,
+    pub tp_drops: u32,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct tpacket_req {
+    pub tp_block_size: u32,
+    pub tp_block_nr: u32,
+    pub tp_frame_size: u32,
+    pub tp_frame_nr: u32,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct tpacket2_hdr {
+    pub tp_status: u32,
+    pub tp_len: u32,
+    pub tp_snaplen: u32,
+    pub tp_mac: u16,
+    pub tp_net: u16,
+    pub tp_sec: u32,
+    pub tp_nsec: u32,
+    pub tp_vlan_tci: u16,
+    pub tp_vlan_tpid: u16,
+    pub tp_padding: [u8; 4],
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct ifaddrs {
+    pub ifa_next: *mut ifaddrs,
+    pub ifa_name: *mut i8,
+    pub ifa_flags: u32,
+    pub ifa_addr: *mut sockaddr,
+    pub ifa_netmask: *mut sockaddr,
+    pub ifa_ifu: *mut sockaddr,
+    pub ifa_data: *mut std::os::raw::c_void,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct sock_filter {
+    pub code: u16,
+    pub jt: u8,
+    pub jf: u8,
+    pub k: u32,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct sock_fprog {
+    pub len: u16,
+    pub filter: *mut sock_filter,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct pollfd {
+    pub fd: i32,
+    pub events: i16,
+    pub revents: i16,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
 #[repr(C)]
 pub struct ifreq {
-    pub ifr_name: [i8; 16],
+    pub ifr_name: [i8; super::IFNAMSIZ],
     pub ifr_ifru: ifreq_data,
 }
 
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
 #[repr(C)]
 pub union ifreq_data {
     pub ifru_addr: sockaddr,
@@ -80,8 +209,28 @@ pub union ifreq_data {
     pub ifru_data: *mut i8,
 }
 
-#[cfg(target_os = "linux")]
-#[derive(Copy)]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct ethtool_link_settings {
+    pub cmd: u32,
+    pub speed: u32,
+    pub duplex: u8,
+    pub port: u8,
+    pub phy_address: u8,
+    pub autoneg: u8,
+    pub mdio_support: u8,
+    pub eth_tp_mdix: u8,
+    pub eth_tp_mdix_ctrl: u8,
+    pub link_mode_masks_nwords: i8,
+    pub transceiver: u8,
+    pub master_slave_cfg: u8,
+    pub master_slave_state: u8,
+    pub rate_matching: u8,
+    pub reserved: [u32; 7],
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Clone, Copy)]
 #[repr(C)]
 pub struct ifmap {
     pub mem_start: u64,
@@ -92,20 +241,6 @@ pub struct ifmap {
     pub port: u8,
 }
 
-#[cfg(target_os = "linux")]
-impl Clone for ifmap {
-    fn clone(&self) -> Self {
-        Self {
-            mem_start: self.mem_start,
-            mem_end: self.mem_end,
-            base_addr: self.base_addr,
-            irq: self.irq,
-            dma: self.dma,
-            port: self.port,
-        }
-    }
-}
-
 #[cfg(target_os = "windows")]
 #[repr(C)]
 pub struct FILE {
@@ -172,20 +307,21 @@ pub struct pcap_stat {
     pub ps_ifdrop: u32,
 }
 
-#[cfg(target_os = "windows")]
+#[cfg(any(target_os = "windows", target_os = "macos"))]
 #[repr(C)]
 pub struct bpf_program {
-    pub bf_insns: *mut bpf_insn,
     pub bf_len: u32,
+    pub bf_insns: *mut bpf_insn,
 }
 
-#[cfg(target_os = "windows")]
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+#[derive(Clone, Copy)]
 #[repr(C)]
 pub struct bpf_insn {
     pub code: u16,
-    pub jf: u8,
     pub jt: u8,
-    pub k: i32,
+    pub jf: u8,
+    pub k: u32,
 }
 
 #[cfg(target_os = "windows")]
@@ -226,6 +362,135 @@ pub struct pcap_pkthdr {
 #[cfg(target_os = "windows")]
 #[repr(C)]
 pub struct timeval {
-    tv_sec: i32,
-    tv_usec: i32,
+    pub tv_sec: i32,
+    pub tv_usec: i32,
+}
+
+#[cfg(target_os = "windows")]
+#[repr(C)]
+pub struct pcap_send_queue {
+    pub maxlen: u32,
+    pub len: u32,
+    pub buffer: *mut i8,
+}
+
+#[cfg(target_os = "macos")]
+#[repr(C)]
+pub struct sockaddr {
+    pub sa_len: u8,
+    pub sa_family: u8,
+    pub sa_data: [i8; 14],
+}
+
+#[cfg(target_os = "macos")]
+#[repr(C)]
+pub struct sockaddr_in {
+    pub sin_len: u8,
+    pub sin_family: u8,
+    pub sin_port: u16,
+    pub sin_addr: in_addr,
+    pub sin_zero: [i8; 8],
+}
+
+#[cfg(target_os = "macos")]
+#[repr(C)]
+pub struct in_addr {
+    pub s_addr: u32,
+}
+
+#[cfg(target_os = "macos")]
+#[repr(C)]
+pub struct sockaddr_in6 {
+    pub sin6_len: u8,
+    pub sin6_family: u8,
+    pub sin6_port: u16,
+    pub sin6_flowinfo: u32,
+    pub sin6_addr: in6_addr,
+    pub sin6_scope_id: u32,
+}
+
+/// link-level address, sdl_data holds interface name followed by sdl_alen bytes of the address
+#[cfg(target_os = "macos")]
+#[repr(C)]
+pub struct sockaddr_dl {
+    pub sdl_len: u8,
+    pub sdl_family: u8,
+    pub sdl_index: u16,
+    pub sdl_type: u8,
+    pub sdl_nlen: u8,
+    pub sdl_alen: u8,
+    pub sdl_slen: u8,
+    pub sdl_data: [i8; 12],
+}
+
+/// header of a routing message, it's followed by socket addresses, which are set in rtm_addrs
+#[cfg(target_os = "macos")]
+#[repr(C)]
+pub struct rt_msghdr {
+    pub rtm_msglen: u16,
+    pub rtm_version: u8,
+    pub rtm_type: u8,
+    pub rtm_index: u16,
+    pub rtm_flags: i32,
+    pub rtm_addrs: i32,
+    pub rtm_pid: i32,
+    pub rtm_seq: i32,
+    pub rtm_errno: i32,
+    pub rtm_use: i32,
+    pub rtm_inits: u32,
+    // struct rt_metrics
+    pub rtm_rmx: [u32; 14],
+}
+
+#[cfg(target_os = "macos")]
+#[repr(C)]
+pub struct ifaddrs {
+    pub ifa_next: *mut ifaddrs,
+    pub ifa_name: *mut i8,
+    pub ifa_flags: u32,
+    pub ifa_addr: *mut sockaddr,
+    pub ifa_netmask: *mut sockaddr,
+    pub ifa_dstaddr: *mut sockaddr,
+    pub ifa_data: *mut std::os::raw::c_void,
+}
+
+#[cfg(target_os = "macos")]
+#[repr(C)]
+pub struct pollfd {
+    pub fd: i32,
+    pub events: i16,
+    pub revents: i16,
+}
+
+/// only the name is used by BIOCSETIF, union is kept as bytes for the size
+#[cfg(target_os = "macos")]
+#[repr(C)]
+pub struct ifreq {
+    pub ifr_name: [i8; super::IFNAMSIZ],
+    pub ifr_ifru: [u8; 16],
+}
+
+#[cfg(any(target_os = "macos", test))]
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct timeval32 {
+    pub tv_sec: i32,
+    pub tv_usec: i32,
+}
+
+#[cfg(target_os = "macos")]
+#[repr(C)]
+pub struct bpf_stat {
+    pub bs_recv: u32,
+    pub bs_drop: u32,
+}
+
+#[cfg(any(target_os = "macos", test))]
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct bpf_hdr {
+    pub bh_tstamp: timeval32,
+    pub bh_caplen: u32,
+    pub bh_datalen: u32,
+    pub bh_hdrlen: u16,
 }
@@ -0,0 +1,129 @@
+use std::net;
+
+use crate::*;
+
+/// Computes checksum of ipv4 header, checksum field has to be zeroed
+///
+/// Returned value is written in network byte order, checksum of a valid header is zero
+///
+/// # Examples
+/// ```
+/// use cursock::*;
+///
+/// let mut header: [u8; 20] = [
+///     0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11,
+///     0x00, 0x00, 0xc0, 0xa8, 0x00, 0x01, 0xc0, 0xa8, 0x00, 0xc7,
+/// ];
+///
+/// let checksum: u16 = ipv4_checksum(&header);
+/// assert_eq!(checksum, 0xb861);
+///
+/// header[10..12].copy_from_slice(&checksum.to_be_bytes());
+/// assert_eq!(ipv4_checksum(&header), 0)
+/// ```
+pub fn ipv4_checksum(header: &[u8]) -> u16 {
+    !fold(add(0, header))
+}
+
+/// Computes checksum of tcp segment with ipv4 pseudo header, checksum field has to be zeroed
+///
+/// # Examples
+/// ```
+/// use cursock::*;
+/// use std::net::Ipv4Addr;
+///
+/// let src = Ipv4Addr::new(10, 0, 0, 1);
+/// let dst = Ipv4Addr::new(10, 0, 0, 2);
+///
+/// let segment = TcpHeader::new(40000, 80, 1, TcpHeader::SYN).to_bytes(&src, &dst, b"odd");
+///
+/// let mut zeroed = segment.clone();
+/// zeroed[16..18].copy_from_slice(&[0, 0]);
+///
+/// assert_eq!(tcp_checksum(&src, &dst, &zeroed).to_be_bytes(), segment[16..18]);
+/// assert_eq!(tcp_checksum(&src, &dst, &segment), 0)
+/// ```
+pub fn tcp_checksum(src: &net::Ipv4Addr, dst: &net::Ipv4Addr, segment: &[u8]) -> u16 {
+    pseudo_header_checksum(src, dst, TCP_PROTO as u8, segment)
+}
+
+/// Computes checksum of udp datagram with ipv4 pseudo header, checksum field has to be zeroed
+///
+/// Zero checksum means that it wasn't computed, so it's returned as 0xffff
+///
+/// # Examples
+/// ```
+/// use cursock::*;
+/// use std::net::Ipv4Addr;
+///
+/// let src = Ipv4Addr::new(10, 0, 0, 1);
+/// let dst = Ipv4Addr::new(10, 0, 0, 2);
+///
+/// // 40001 -> 53, length 13, "hello"
+/// let datagram = [0x9c, 0x41, 0x00, 0x35, 0x00, 0x0d, 0x00, 0x00, b'h', b'e', b'l', b'l', b'o'];
+///
+/// assert_eq!(udp_checksum(&src, &dst, &datagram), 0x0b89)
+/// ```
+pub fn udp_checksum(src: &net::Ipv4Addr, dst: &net::Ipv4Addr, datagram: &[u8]) -> u16 {
+    match pseudo_header_checksum(src, dst, UDP_PROTO as u8, datagram) {
+        0 => 0xffff,
+        checksum => checksum,
+    }
+}
+
+/// Computes checksum of icmp message, checksum field has to be zeroed
+///
+/// Unlike tcp and udp, there is no pseudo header
+///
+/// # Examples
+/// ```
+/// use cursock::*;
+///
+/// // echo request with id 1 and sequence 1
+/// let message = [0x08, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01];
+///
+/// assert_eq!(icmp_checksum(&message), 0xf7fd)
+/// ```
+pub fn icmp_checksum(message: &[u8]) -> u16 {
+    !fold(add(0, message))
+}
+
+/// one's complement sum of ipv4 pseudo header and tcp or udp segment
+pub(crate) fn pseudo_header_checksum(
+    src: &net::Ipv4Addr,
+    dst: &net::Ipv4Addr,
+    protocol: u8,
+    segment: &[u8],
+) -> u16 {
+    let mut sum: u32 = add(0, &src.octets());
+    sum = add(sum, &dst.octets());
+    sum = add(sum, &[0, protocol]);
+    sum = add(sum, &(segment.len() as u16).to_be_bytes());
+    sum = add(sum, segment);
+
+    !fold(sum)
+}
+
+/// adds big endian words to the sum, odd byte is padded with zero
+fn add(mut sum: u32, bytes: &[u8]) -> u32 {
+    for word in bytes.chunks(2) {
+        let high: u8 = word[0];
+        let low: u8 = word.get(1).copied().unwrap_or(0);
+        sum += u16::from_be_bytes([high, low]) as u32;
+
+        // carries are folded as they come, so the sum can't overflow
+        if sum > 0xffff {
+            sum = (sum >> 16) + (sum & 0xffff);
+        }
+    }
+
+    sum
+}
+
+fn fold(mut sum: u32) -> u16 {
+    while sum > 0xffff {
+        sum = (sum >> 16) + (sum & 0xffff);
+    }
+
+    sum as u16
+}
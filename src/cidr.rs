@@ -0,0 +1,195 @@
+use std::fmt;
+use std::io;
+use std::net;
+use std::str;
+
+use crate::*;
+
+/// ipv4 network with a prefix length, like "10.0.0.0/24"
+///
+/// # Example
+/// ```
+/// use std::net::Ipv4Addr;
+/// use cursock::*;
+///
+/// let cidr: Ipv4Cidr = "10.0.0.0/24".parse().expect("parse error");
+///
+/// assert_eq!(cidr.network(), Ipv4Addr::new(10, 0, 0, 0));
+/// assert_eq!(cidr.broadcast(), Ipv4Addr::new(10, 0, 0, 255));
+/// assert_eq!(cidr.netmask(), Ipv4Addr::new(255, 255, 255, 0));
+/// assert!(cidr.contains(&Ipv4Addr::new(10, 0, 0, 77)));
+/// assert!(!cidr.contains(&Ipv4Addr::new(10, 0, 1, 0)));
+/// assert_eq!(cidr.to_string(), "10.0.0.0/24");
+///
+/// let hosts: Vec<Ipv4Addr> = cidr.hosts().collect();
+/// assert_eq!(hosts.len(), 254);
+/// assert_eq!(hosts[0], Ipv4Addr::new(10, 0, 0, 1));
+/// assert_eq!(hosts[253], Ipv4Addr::new(10, 0, 0, 254));
+///
+/// // point-to-point links have no network and broadcast addresses
+/// let link = Ipv4Cidr::parse("10.0.0.2/31", false).expect("parse error");
+/// assert_eq!(link.hosts().collect::<Vec<_>>(), [Ipv4Addr::new(10, 0, 0, 2), Ipv4Addr::new(10, 0, 0, 3)]);
+/// let host = Ipv4Cidr::parse("10.0.0.2/32", false).expect("parse error");
+/// assert_eq!(host.hosts().collect::<Vec<_>>(), [Ipv4Addr::new(10, 0, 0, 2)]);
+///
+/// assert_eq!(cidr.host_count(), 254);
+/// assert_eq!(Ipv4Cidr::parse("0.0.0.0/0", false).expect("parse error").host_count(), (1 << 32) - 2);
+///
+/// assert!(Ipv4Cidr::parse("10.0.0.0/33", false).is_err());
+/// assert!(Ipv4Cidr::parse("10.0.0/24", false).is_err());
+/// assert!(Ipv4Cidr::parse("10.0.0.0", false).is_err());
+/// // host bits are cleared only if it's asked
+/// assert!(Ipv4Cidr::parse("10.0.0.1/24", false).is_err());
+/// assert_eq!(
+///     Ipv4Cidr::parse("10.0.0.1/24", true).expect("parse error"),
+///     Ipv4Cidr::new(Ipv4Addr::new(10, 0, 0, 0), 24, false).expect("cidr error")
+/// )
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Ipv4Cidr {
+    network: net::Ipv4Addr,
+    prefix: u8,
+}
+
+/// iterator over host addresses of a network, see [`Ipv4Cidr::hosts`]
+#[derive(Clone, Debug)]
+pub struct Ipv4CidrHosts {
+    // next address and the last one, u64 so the range can be empty
+    next: u64,
+    last: u64,
+}
+
+impl Ipv4Cidr {
+    /// Returns InvalidInput error, if prefix is longer than 32 bits or,
+    /// unless normalize is set, if the address has host bits set
+    pub fn new(addr: net::Ipv4Addr, prefix: u8, normalize: bool) -> io::Result<Self> {
+        if prefix > 32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("prefix /{} is longer than 32 bits", prefix),
+            ));
+        }
+
+        let network: net::Ipv4Addr = net::Ipv4Addr::from(u32::from(addr) & mask(prefix));
+        if network != addr && !normalize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{}/{} has host bits set", addr, prefix),
+            ));
+        }
+
+        Ok(Self { network, prefix })
+    }
+
+    /// Parses network from "address/prefix", see [`Ipv4Cidr::new`]
+    pub fn parse(cidr: &str, normalize: bool) -> io::Result<Self> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid cidr \"{}\"", cidr),
+            )
+        };
+
+        let (addr, prefix) = cidr.split_once('/').ok_or_else(invalid)?;
+        let addr: net::Ipv4Addr = addr.parse().map_err(|_| invalid())?;
+        // u8 parser accepts a sign
+        if !prefix.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let prefix: u8 = prefix.parse().map_err(|_| invalid())?;
+
+        Self::new(addr, prefix, normalize)
+    }
+
+    pub fn contains(&self, addr: &net::Ipv4Addr) -> bool {
+        u32::from(*addr) & mask(self.prefix) == u32::from(self.network)
+    }
+
+    pub fn network(&self) -> net::Ipv4Addr {
+        self.network
+    }
+
+    pub fn broadcast(&self) -> net::Ipv4Addr {
+        net::Ipv4Addr::from(u32::from(self.network) | !mask(self.prefix))
+    }
+
+    pub fn netmask(&self) -> net::Ipv4Addr {
+        net::Ipv4Addr::from(mask(self.prefix))
+    }
+
+    /// Returns iterator over addresses, which can be assigned to hosts
+    ///
+    /// Network and broadcast addresses are skipped, except for /31 and /32 networks
+    pub fn hosts(&self) -> Ipv4CidrHosts {
+        let network: u64 = u32::from(self.network()) as u64;
+        let broadcast: u64 = u32::from(self.broadcast()) as u64;
+
+        match self.prefix {
+            31 | 32 => Ipv4CidrHosts {
+                next: network,
+                last: broadcast,
+            },
+            _ => Ipv4CidrHosts {
+                next: network + 1,
+                last: broadcast - 1,
+            },
+        }
+    }
+
+    /// Returns number of addresses yielded by [`Ipv4Cidr::hosts`],
+    /// it's u64 since /0 hosts don't fit in usize on 32 bit targets
+    pub fn host_count(&self) -> u64 {
+        let hosts: Ipv4CidrHosts = self.hosts();
+
+        hosts.last + 1 - hosts.next
+    }
+
+    getters!(
+        pub get_prefix(prefix) -> u8;
+    );
+}
+
+impl str::FromStr for Ipv4Cidr {
+    type Err = io::Error;
+
+    fn from_str(cidr: &str) -> Result<Self, Self::Err> {
+        Self::parse(cidr, false)
+    }
+}
+
+impl fmt::Display for Ipv4Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+impl Iterator for Ipv4CidrHosts {
+    type Item = net::Ipv4Addr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next > self.last {
+            return None;
+        }
+
+        let addr: net::Ipv4Addr = net::Ipv4Addr::from(self.next as u32);
+        self.next += 1;
+
+        Some(addr)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count: u64 = (self.last + 1).saturating_sub(self.next);
+
+        match usize::try_from(count) {
+            Ok(count) => (count, Some(count)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+fn mask(prefix: u8) -> u32 {
+    match prefix {
+        0 => 0,
+        _ => u32::MAX << (32 - prefix as u32),
+    }
+}
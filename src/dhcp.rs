@@ -0,0 +1,311 @@
+//! Building dhcp discover messages and parsing answers of dhcp servers
+//!
+//! # Examples
+//! ```no_run
+//! use std::collections::HashSet;
+//! use std::time::Duration;
+//! use cursock::*;
+//!
+//! let socket = Socket::new("eth0").expect("initialize error");
+//! let offers = socket.dhcp_probe(Duration::from_secs(3)).expect("probe error");
+//!
+//! let servers: HashSet<_> = offers.iter().filter_map(|offer| *offer.get_server_id()).collect();
+//! if servers.len() > 1 {
+//!     println!("several dhcp servers answer: {:?}", servers);
+//! }
+//! ```
+
+use std::io;
+use std::net;
+use std::time;
+
+use rand::Rng;
+
+use crate::udp::parse_udp;
+use crate::*;
+
+pub const DHCP_SERVER_PORT: u16 = 67;
+pub const DHCP_CLIENT_PORT: u16 = 68;
+
+// values of option 53
+pub const DHCP_DISCOVER: u8 = 1;
+pub const DHCP_OFFER: u8 = 2;
+pub const DHCP_REQUEST: u8 = 3;
+pub const DHCP_DECLINE: u8 = 4;
+pub const DHCP_ACK: u8 = 5;
+pub const DHCP_NAK: u8 = 6;
+pub const DHCP_RELEASE: u8 = 7;
+
+const BOOTREQUEST: u8 = 1;
+const BOOTREPLY: u8 = 2;
+const BOOTP_FLAG_BROADCAST: u16 = 0x8000;
+/// fixed part of bootp message, options follow the magic cookie
+const BOOTP_HEADER_SIZE: usize = 236;
+/// some servers drop messages, which are shorter than bootp ones
+const BOOTP_MIN_SIZE: usize = 300;
+const MAGIC_COOKIE: [u8; 4] = [99, 130, 83, 99];
+
+// option codes
+const OPTION_PAD: u8 = 0;
+const OPTION_SUBNET_MASK: u8 = 1;
+const OPTION_ROUTER: u8 = 3;
+const OPTION_DNS: u8 = 6;
+const OPTION_LEASE_TIME: u8 = 51;
+const OPTION_MESSAGE_TYPE: u8 = 53;
+const OPTION_SERVER_ID: u8 = 54;
+const OPTION_PARAMETER_LIST: u8 = 55;
+const OPTION_END: u8 = 255;
+
+/// dhcp discover, which asks servers for offers
+///
+/// # Example
+/// ```
+/// use std::net::Ipv4Addr;
+/// use cursock::*;
+/// use cursock::dhcp::{self, DhcpDiscover, DhcpOffer};
+///
+/// let mac = Mac::from([0x02, 0, 0, 0, 0, 0x01]);
+/// let mut discover = DhcpDiscover::new(mac);
+/// discover.set_xid(0x12345678);
+///
+/// let bytes = discover.to_bytes();
+/// assert_eq!(bytes.len(), 300);
+/// assert_eq!(&bytes[..12], &[1, 1, 6, 0, 0x12, 0x34, 0x56, 0x78, 0, 0, 0x80, 0]);
+/// assert_eq!(&bytes[28..34], &[0x02, 0, 0, 0, 0, 0x01]);
+/// assert_eq!(&bytes[236..243], &[99, 130, 83, 99, 53, 1, dhcp::DHCP_DISCOVER]);
+///
+/// // offer of a server, which answers with the bootp fields of the request
+/// let mut offer = bytes.clone();
+/// offer[0] = 2;
+/// offer[16..20].copy_from_slice(&[10, 0, 0, 50]);
+/// offer.truncate(240);
+/// offer.extend_from_slice(&[53, 1, 2, 54, 4, 10, 0, 0, 1, 51, 4, 0, 0, 0x0e, 0x10, 0, 1, 4, 255, 255, 255, 0]);
+/// offer.extend_from_slice(&[3, 8, 10, 0, 0, 1, 10, 0, 0, 2, 6, 8, 1, 1, 1, 1, 8, 8, 8, 8, 255]);
+///
+/// let parsed = DhcpOffer::from_bytes(&offer).expect("parse error");
+/// assert_eq!(parsed.get_message_type(), &dhcp::DHCP_OFFER);
+/// assert_eq!(parsed.get_xid(), &0x12345678);
+/// assert_eq!(parsed.get_your_ip(), &Ipv4Addr::new(10, 0, 0, 50));
+/// assert_eq!(parsed.get_server_id(), &Some(Ipv4Addr::new(10, 0, 0, 1)));
+/// assert_eq!(parsed.get_lease_time(), &Some(3600));
+/// assert_eq!(parsed.get_subnet_mask(), &Some(Ipv4Addr::new(255, 255, 255, 0)));
+/// // first router is the default one
+/// assert_eq!(parsed.get_router(), &Some(Ipv4Addr::new(10, 0, 0, 1)));
+/// assert_eq!(parsed.get_dns(), &[Ipv4Addr::new(1, 1, 1, 1), Ipv4Addr::new(8, 8, 8, 8)]);
+///
+/// // requests and truncated options aren't answers
+/// assert!(DhcpOffer::from_bytes(&bytes).is_err());
+/// assert!(DhcpOffer::from_bytes(&offer[..offer.len() - 4]).is_err())
+/// ```
+#[derive(Clone)]
+pub struct DhcpDiscover {
+    xid: u32,
+    client_mac: Mac,
+}
+
+/// answer of a dhcp server, usually an offer or an ack
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DhcpOffer {
+    message_type: u8,
+    xid: u32,
+    your_ip: net::Ipv4Addr,
+    server_id: Option<net::Ipv4Addr>,
+    lease_time: Option<u32>,
+    subnet_mask: Option<net::Ipv4Addr>,
+    router: Option<net::Ipv4Addr>,
+    dns: Vec<net::Ipv4Addr>,
+}
+
+impl DhcpDiscover {
+    /// Creates discover with random transaction id, servers are asked to broadcast answers
+    pub fn new(client_mac: Mac) -> Self {
+        Self {
+            xid: rand::thread_rng().gen(),
+            client_mac,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = vec![0; BOOTP_HEADER_SIZE];
+
+        bytes[0] = BOOTREQUEST;
+        bytes[1] = HW_TYPE as u8;
+        bytes[2] = MAC_LEN as u8;
+        bytes[4..8].copy_from_slice(&self.xid.to_be_bytes());
+        bytes[10..12].copy_from_slice(&BOOTP_FLAG_BROADCAST.to_be_bytes());
+        bytes[28..34].copy_from_slice(&<[u8; MAC_LEN]>::from(self.client_mac));
+
+        bytes.extend_from_slice(&MAGIC_COOKIE);
+        bytes.extend_from_slice(&[OPTION_MESSAGE_TYPE, 1, DHCP_DISCOVER]);
+        bytes.extend_from_slice(&[
+            OPTION_PARAMETER_LIST,
+            5,
+            OPTION_SUBNET_MASK,
+            OPTION_ROUTER,
+            OPTION_DNS,
+            OPTION_LEASE_TIME,
+            OPTION_SERVER_ID,
+        ]);
+        bytes.push(OPTION_END);
+        bytes.resize(BOOTP_MIN_SIZE, OPTION_PAD);
+
+        bytes
+    }
+
+    getters!(
+        pub get_xid(xid) -> u32;
+        pub get_client_mac(client_mac) -> Mac;
+    );
+    setters!(
+        pub set_xid(u32) -> xid;
+        pub set_client_mac(Mac) -> client_mac;
+    );
+}
+
+impl DhcpOffer {
+    /// Parses bootp reply with dhcp options, returns InvalidData error if it's not a dhcp answer
+    /// or its options are truncated
+    pub fn from_bytes(message: &[u8]) -> io::Result<Self> {
+        let invalid = |reason: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid dhcp answer: {}", reason),
+            )
+        };
+
+        if message.len() < BOOTP_HEADER_SIZE + MAGIC_COOKIE.len() {
+            return Err(invalid("message is truncated"));
+        }
+        if message[0] != BOOTREPLY {
+            return Err(invalid("message is not a bootp reply"));
+        }
+        if message[BOOTP_HEADER_SIZE..BOOTP_HEADER_SIZE + 4] != MAGIC_COOKIE {
+            return Err(invalid("no magic cookie"));
+        }
+
+        let addr = |bytes: &[u8]| net::Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]);
+
+        let mut offer: Self = Self {
+            message_type: 0,
+            xid: u32::from_be_bytes([message[4], message[5], message[6], message[7]]),
+            your_ip: addr(&message[16..20]),
+            server_id: None,
+            lease_time: None,
+            subnet_mask: None,
+            router: None,
+            dns: Vec::new(),
+        };
+
+        let mut options: &[u8] = &message[BOOTP_HEADER_SIZE + 4..];
+        loop {
+            let (code, rest) = options
+                .split_first()
+                .ok_or_else(|| invalid("no end option"))?;
+
+            match *code {
+                OPTION_END => break,
+                OPTION_PAD => {
+                    options = rest;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let (len, rest) = rest
+                .split_first()
+                .ok_or_else(|| invalid("option is truncated"))?;
+            if rest.len() < *len as usize {
+                return Err(invalid("option is truncated"));
+            }
+            let (data, rest) = rest.split_at(*len as usize);
+
+            // options of unexpected lengths are ignored
+            match (*code, data.len()) {
+                (OPTION_MESSAGE_TYPE, 1) => offer.message_type = data[0],
+                (OPTION_SERVER_ID, 4) => offer.server_id = Some(addr(data)),
+                (OPTION_LEASE_TIME, 4) => {
+                    offer.lease_time =
+                        Some(u32::from_be_bytes([data[0], data[1], data[2], data[3]]))
+                }
+                (OPTION_SUBNET_MASK, 4) => offer.subnet_mask = Some(addr(data)),
+                (OPTION_ROUTER, len) if len >= 4 => offer.router = Some(addr(data)),
+                (OPTION_DNS, _) => offer.dns = data.chunks_exact(IPV4_LEN).map(addr).collect(),
+                _ => {}
+            }
+
+            options = rest;
+        }
+
+        if offer.message_type == 0 {
+            return Err(invalid("no message type"));
+        }
+
+        Ok(offer)
+    }
+
+    getters!(
+        pub get_message_type(message_type) -> u8;
+        pub get_xid(xid) -> u32;
+        pub get_your_ip(your_ip) -> net::Ipv4Addr;
+        pub get_server_id(server_id) -> Option<net::Ipv4Addr>;
+        pub get_lease_time(lease_time) -> Option<u32>;
+        pub get_subnet_mask(subnet_mask) -> Option<net::Ipv4Addr>;
+        pub get_router(router) -> Option<net::Ipv4Addr>;
+        pub get_dns(dns) -> [net::Ipv4Addr];
+    );
+}
+
+impl Socket {
+    /// Broadcasts dhcp discover from the socket mac and collects offers, which are received until timeout
+    ///
+    /// Every answering server sends its own offer, so offers with different server ids
+    /// mean there are several dhcp servers in the network.
+    /// Broadcast discover doesn't need an ipv4 address on the interface
+    pub fn dhcp_probe(&self, timeout: time::Duration) -> io::Result<Vec<dhcp::DhcpOffer>> {
+        let discover: dhcp::DhcpDiscover = dhcp::DhcpDiscover::new(*self.get_src_mac());
+
+        self.send_udp(
+            (net::Ipv4Addr::UNSPECIFIED, dhcp::DHCP_CLIENT_PORT),
+            (net::Ipv4Addr::BROADCAST, dhcp::DHCP_SERVER_PORT),
+            &Mac::BROADCAST,
+            &discover.to_bytes(),
+        )?;
+
+        let deadline: time::Instant = time::Instant::now() + timeout;
+        let mut buffer: [u8; 1514] = [0; 1514];
+        let mut offers: Vec<dhcp::DhcpOffer> = Vec::new();
+
+        loop {
+            let timeout: time::Duration = deadline.saturating_duration_since(time::Instant::now());
+
+            let length: usize = match self.read_raw_packet_timeout(&mut buffer, timeout) {
+                Ok(length) => length,
+                Err(err) if err.kind() == io::ErrorKind::TimedOut => break,
+                Err(err) => return Err(err),
+            };
+
+            let packet: UdpPacket = match parse_udp(&buffer[..length]) {
+                Some(packet) => packet,
+                None => continue,
+            };
+
+            if *packet.get_src_port() != dhcp::DHCP_SERVER_PORT
+                || *packet.get_dst_port() != dhcp::DHCP_CLIENT_PORT
+            {
+                continue;
+            }
+
+            match dhcp::DhcpOffer::from_bytes(packet.payload(&buffer)) {
+                Ok(offer)
+                    if offer.xid == discover.xid && offer.message_type == dhcp::DHCP_OFFER =>
+                {
+                    logger::debug(|| format!("dhcp offer from {:?}", offer.server_id));
+                    offers.push(offer)
+                }
+                _ => continue,
+            }
+        }
+
+        Ok(offers)
+    }
+}
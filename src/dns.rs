@@ -0,0 +1,423 @@
+//! Building dns queries and parsing responses, which are sent in udp datagrams
+//!
+//! # Examples
+//! ```no_run
+//! use std::net::Ipv4Addr;
+//! use std::time::Duration;
+//! use cursock::*;
+//! use cursock::dns::{self, DnsQuery, DnsResponse};
+//!
+//! let socket = Socket::new("eth0").expect("initialize error");
+//! let gateway = socket.get_adapter().get_gateway().expect("no gateway");
+//! let gateway_mac = socket.arp_request(gateway, Duration::from_secs(1)).expect("arp error");
+//!
+//! let src_ip = *socket.get_src_ip().expect("no ipv4 address");
+//! let query = DnsQuery::new("example.com", dns::TYPE_A).expect("name error");
+//!
+//! socket
+//!     .send_udp((src_ip, 40053), (Ipv4Addr::new(8, 8, 8, 8), 53), &gateway_mac, &query.to_bytes())
+//!     .expect("send error");
+//!
+//! let mut buffer = [0; 1514];
+//! loop {
+//!     let packet = socket.recv_udp(&mut buffer).expect("read error");
+//!     if *packet.get_src_port() != 53 || *packet.get_dst_port() != 40053 {
+//!         continue;
+//!     }
+//!
+//!     let response = DnsResponse::from_bytes(packet.payload(&buffer)).expect("parse error");
+//!     if response.get_id() == query.get_id() {
+//!         for answer in response.get_answers() {
+//!             println!("{} {:?}", answer.get_name(), answer.get_data());
+//!         }
+//!         break;
+//!     }
+//! }
+//! ```
+
+use std::io;
+use std::net;
+
+use rand::Rng;
+
+use crate::*;
+
+pub const TYPE_A: u16 = 1;
+pub const TYPE_NS: u16 = 2;
+pub const TYPE_CNAME: u16 = 5;
+pub const TYPE_PTR: u16 = 12;
+pub const TYPE_AAAA: u16 = 28;
+pub const CLASS_IN: u16 = 1;
+
+const DNS_HEADER_SIZE: usize = 12;
+const MAX_LABEL_LEN: usize = 63;
+/// length of an encoded name with its length bytes
+const MAX_NAME_LEN: usize = 255;
+/// compression pointers followed in one name, more of them are treated as a loop
+const MAX_POINTERS: usize = 32;
+
+// header flags
+const FLAG_RESPONSE: u16 = 0x8000;
+const FLAG_AUTHORITATIVE: u16 = 0x0400;
+const FLAG_TRUNCATED: u16 = 0x0200;
+const FLAG_RECURSION_DESIRED: u16 = 0x0100;
+const FLAG_RECURSION_AVAILABLE: u16 = 0x0080;
+
+/// dns query with one question
+///
+/// # Example
+/// ```
+/// use std::io;
+/// use std::net::Ipv4Addr;
+/// use cursock::dns::{self, DnsQuery, DnsRecordData, DnsResponse};
+///
+/// let mut query = DnsQuery::new("example.com.", dns::TYPE_A).expect("name error");
+/// query.set_id(0x1234);
+///
+/// let bytes = query.to_bytes();
+/// assert_eq!(&bytes[..12], &[0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+/// assert_eq!(&bytes[12..], b"\x07example\x03com\x00\x00\x01\x00\x01");
+///
+/// // answer of the server, names are compressed
+/// let mut response = bytes.clone();
+/// response[2..4].copy_from_slice(&[0x81, 0x80]);
+/// response[7] = 2;
+/// // example.com is cname of www.example.com
+/// response.extend_from_slice(&[0xc0, 0x0c, 0, 5, 0, 1, 0, 0, 0, 60, 0, 6, 3, b'w', b'w', b'w', 0xc0, 0x0c]);
+/// response.extend_from_slice(&[0xc0, 0x29, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 93, 184, 216, 34]);
+///
+/// let parsed = DnsResponse::from_bytes(&response).expect("parse error");
+/// assert_eq!(parsed.get_id(), &0x1234);
+/// assert!(parsed.is_response());
+/// assert_eq!(parsed.get_rcode(), 0);
+/// assert_eq!(parsed.get_questions()[0].get_name(), "example.com");
+///
+/// let answers = parsed.get_answers();
+/// assert_eq!(answers[0].get_data(), &DnsRecordData::Cname("www.example.com".to_string()));
+/// assert_eq!(answers[1].get_name(), "www.example.com");
+/// assert_eq!(answers[1].get_ttl(), &60);
+/// assert_eq!(answers[1].get_data(), &DnsRecordData::A(Ipv4Addr::new(93, 184, 216, 34)));
+///
+/// // name of the first answer points to itself
+/// let mut looped = response.clone();
+/// looped[29..31].copy_from_slice(&[0xc0, 0x1d]);
+/// assert_eq!(DnsResponse::from_bytes(&looped).unwrap_err().kind(), io::ErrorKind::InvalidData);
+///
+/// assert!(DnsResponse::from_bytes(&response[..40]).is_err());
+/// assert!(DnsQuery::new(&"a".repeat(64), dns::TYPE_A).is_err());
+/// assert!(DnsQuery::new("example..com", dns::TYPE_A).is_err())
+/// ```
+#[derive(Clone, Debug)]
+pub struct DnsQuery {
+    id: u16,
+    name: String,
+    qtype: u16,
+    recursion_desired: bool,
+}
+
+/// question of a dns message
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DnsQuestion {
+    name: String,
+    qtype: u16,
+    qclass: u16,
+}
+
+/// data of a resource record, types which aren't parsed are kept as is
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DnsRecordData {
+    A(net::Ipv4Addr),
+    Aaaa(net::Ipv6Addr),
+    Cname(String),
+    Ptr(String),
+    Other(Vec<u8>),
+}
+
+/// resource record of a dns message
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DnsRecord {
+    name: String,
+    rtype: u16,
+    class: u16,
+    ttl: u32,
+    data: DnsRecordData,
+}
+
+/// dns response, authority and additional records are skipped
+#[derive(Clone, Debug)]
+pub struct DnsResponse {
+    id: u16,
+    flags: u16,
+    questions: Vec<DnsQuestion>,
+    answers: Vec<DnsRecord>,
+}
+
+impl DnsQuery {
+    /// Creates recursive query with random transaction id
+    ///
+    /// Returns InvalidInput error, if the name has empty labels,
+    /// labels longer than 63 bytes or is longer than 255 bytes
+    pub fn new(name: &str, qtype: u16) -> io::Result<Self> {
+        let name: &str = name.strip_suffix('.').unwrap_or(name);
+        encode_name(name)?;
+
+        Ok(Self {
+            id: rand::thread_rng().gen(),
+            name: name.to_string(),
+            qtype,
+            recursion_desired: true,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let flags: u16 = match self.recursion_desired {
+            true => FLAG_RECURSION_DESIRED,
+            false => 0,
+        };
+        // name was checked, when query was created
+        let name: Vec<u8> = encode_name(&self.name).unwrap_or_default();
+
+        let mut bytes: Vec<u8> = Vec::with_capacity(DNS_HEADER_SIZE + name.len() + 4);
+        bytes.extend_from_slice(&self.id.to_be_bytes());
+        bytes.extend_from_slice(&flags.to_be_bytes());
+        bytes.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+        bytes.extend_from_slice(&name);
+        bytes.extend_from_slice(&self.qtype.to_be_bytes());
+        bytes.extend_from_slice(&CLASS_IN.to_be_bytes());
+
+        bytes
+    }
+
+    getters!(
+        pub get_id(id) -> u16;
+        pub get_name(name) -> String;
+        pub get_qtype(qtype) -> u16;
+        pub get_recursion_desired(recursion_desired) -> bool;
+    );
+    setters!(
+        pub set_id(u16) -> id;
+        pub set_qtype(u16) -> qtype;
+        pub set_recursion_desired(bool) -> recursion_desired;
+    );
+}
+
+impl DnsQuestion {
+    getters!(
+        pub get_name(name) -> String;
+        pub get_qtype(qtype) -> u16;
+        pub get_qclass(qclass) -> u16;
+    );
+}
+
+impl DnsRecord {
+    getters!(
+        pub get_name(name) -> String;
+        pub get_rtype(rtype) -> u16;
+        pub get_class(class) -> u16;
+        pub get_ttl(ttl) -> u32;
+        pub get_data(data) -> DnsRecordData;
+    );
+}
+
+impl DnsResponse {
+    /// Parses dns message, returns InvalidData error if it's truncated or malformed
+    ///
+    /// Compression pointers are followed at most 32 times in a name, so loops are rejected
+    pub fn from_bytes(message: &[u8]) -> io::Result<Self> {
+        if message.len() < DNS_HEADER_SIZE {
+            return Err(truncated());
+        }
+
+        let field = |offset: usize| u16::from_be_bytes([message[offset], message[offset + 1]]);
+        let mut offset: usize = DNS_HEADER_SIZE;
+
+        let mut questions: Vec<DnsQuestion> = Vec::new();
+        for _ in 0..field(4) {
+            let (name, end) = read_name(message, offset)?;
+            let fixed: &[u8] = message.get(end..end + 4).ok_or_else(truncated)?;
+
+            questions.push(DnsQuestion {
+                name,
+                qtype: u16::from_be_bytes([fixed[0], fixed[1]]),
+                qclass: u16::from_be_bytes([fixed[2], fixed[3]]),
+            });
+            offset = end + 4;
+        }
+
+        let mut answers: Vec<DnsRecord> = Vec::new();
+        for _ in 0..field(6) {
+            let (record, end) = read_record(message, offset)?;
+
+            answers.push(record);
+            offset = end;
+        }
+
+        Ok(Self {
+            id: field(0),
+            flags: field(2),
+            questions,
+            answers,
+        })
+    }
+
+    pub fn is_response(&self) -> bool {
+        self.flags & FLAG_RESPONSE != 0
+    }
+
+    pub fn is_authoritative(&self) -> bool {
+        self.flags & FLAG_AUTHORITATIVE != 0
+    }
+
+    /// Returns true if the message didn't fit into the datagram, answers can be missing then
+    pub fn is_truncated(&self) -> bool {
+        self.flags & FLAG_TRUNCATED != 0
+    }
+
+    pub fn is_recursion_available(&self) -> bool {
+        self.flags & FLAG_RECURSION_AVAILABLE != 0
+    }
+
+    /// Returns response code, 0 means no error and 3 that the name doesn't exist
+    pub fn get_rcode(&self) -> u8 {
+        (self.flags & 0x000f) as u8
+    }
+
+    getters!(
+        pub get_id(id) -> u16;
+        pub get_flags(flags) -> u16;
+        pub get_questions(questions) -> Vec<DnsQuestion>;
+        pub get_answers(answers) -> Vec<DnsRecord>;
+    );
+}
+
+fn encode_name(name: &str) -> io::Result<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(name.len() + 2);
+
+    if !name.is_empty() {
+        for label in name.split('.') {
+            if label.is_empty() || label.len() > MAX_LABEL_LEN {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid label \"{}\" in {}", label, name),
+                ));
+            }
+
+            bytes.push(label.len() as u8);
+            bytes.extend_from_slice(label.as_bytes());
+        }
+    }
+    bytes.push(0);
+
+    if bytes.len() > MAX_NAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is longer than {} bytes", name, MAX_NAME_LEN),
+        ));
+    }
+
+    Ok(bytes)
+}
+
+/// reads name at the offset, returns it and offset after the name
+fn read_name(message: &[u8], start: usize) -> io::Result<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut offset: usize = start;
+    // end of the name, where the first pointer was
+    let mut end: Option<usize> = None;
+    let mut pointers: usize = 0;
+    let mut length: usize = 1;
+
+    loop {
+        let len: usize = *message.get(offset).ok_or_else(truncated)? as usize;
+
+        match len & 0xc0 {
+            0xc0 => {
+                let low: usize = *message.get(offset + 1).ok_or_else(truncated)? as usize;
+
+                pointers += 1;
+                if pointers > MAX_POINTERS {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "too many compression pointers in a name",
+                    ));
+                }
+
+                end.get_or_insert(offset + 2);
+                offset = (len & 0x3f) << 8 | low;
+            }
+            0x00 if len == 0 => {
+                return Ok((labels.join("."), end.unwrap_or(offset + 1)));
+            }
+            0x00 => {
+                let label: &[u8] = message
+                    .get(offset + 1..offset + 1 + len)
+                    .ok_or_else(truncated)?;
+
+                length += len + 1;
+                if length > MAX_NAME_LEN {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("name is longer than {} bytes", MAX_NAME_LEN),
+                    ));
+                }
+
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                offset += 1 + len;
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown label type {:#04x}", len),
+                ))
+            }
+        }
+    }
+}
+
+/// reads resource record at the offset, returns it and offset after the record
+fn read_record(message: &[u8], offset: usize) -> io::Result<(DnsRecord, usize)> {
+    let (name, offset) = read_name(message, offset)?;
+    let fixed: &[u8] = message.get(offset..offset + 10).ok_or_else(truncated)?;
+
+    let rtype: u16 = u16::from_be_bytes([fixed[0], fixed[1]]);
+    let rdlength: usize = u16::from_be_bytes([fixed[8], fixed[9]]) as usize;
+
+    let start: usize = offset + 10;
+    let rdata: &[u8] = message.get(start..start + rdlength).ok_or_else(truncated)?;
+
+    let invalid_length = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("record of type {} has {} bytes of data", rtype, rdlength),
+        )
+    };
+
+    let data: DnsRecordData = match rtype {
+        TYPE_A => {
+            let addr: [u8; 4] = rdata.try_into().map_err(|_| invalid_length())?;
+            DnsRecordData::A(net::Ipv4Addr::from(addr))
+        }
+        TYPE_AAAA => {
+            let addr: [u8; 16] = rdata.try_into().map_err(|_| invalid_length())?;
+            DnsRecordData::Aaaa(net::Ipv6Addr::from(addr))
+        }
+        // names in the data can point anywhere in the message
+        TYPE_CNAME => DnsRecordData::Cname(read_name(message, start)?.0),
+        TYPE_PTR => DnsRecordData::Ptr(read_name(message, start)?.0),
+        _ => DnsRecordData::Other(rdata.to_vec()),
+    };
+
+    let record: DnsRecord = DnsRecord {
+        name,
+        rtype,
+        class: u16::from_be_bytes([fixed[2], fixed[3]]),
+        ttl: u32::from_be_bytes([fixed[4], fixed[5], fixed[6], fixed[7]]),
+        data,
+    };
+
+    Ok((record, start + rdlength))
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "dns message is truncated")
+}
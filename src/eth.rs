@@ -0,0 +1,432 @@
+use std::io;
+
+use crate::*;
+
+/// ethernet header of a frame
+///
+/// # Example
+/// ```
+/// use cursock::*;
+///
+/// let header = EthernetHeader::new(
+///     Mac::from([0xff; MAC_LEN]),
+///     Mac::from([0x02, 0, 0, 0, 0, 0x01]),
+///     ARP_PROTO,
+/// );
+///
+/// let mut frame = header.to_bytes().to_vec();
+/// frame.extend_from_slice(&[1, 2, 3]);
+///
+/// let (parsed, payload) = EthernetHeader::parse(&frame).expect("parse error");
+///
+/// assert_eq!(parsed.get_src().to_string(), "2:0:0:0:0:1");
+/// assert_eq!(parsed.get_ethertype(), &ARP_PROTO);
+/// assert_eq!(payload, &[1, 2, 3]);
+///
+/// assert!(EthernetHeader::parse(&frame[..ETH_HEADER_SIZE - 1]).is_err())
+/// ```
+#[derive(Clone)]
+pub struct EthernetHeader {
+    dst: Mac,
+    src: Mac,
+    ethertype: u16,
+    // 802.1Q tag, which is written before the ethertype
+    vlan: Option<VlanTag>,
+}
+
+/// 802.1Q or 802.1ad tag, which follows mac addresses of a tagged frame
+///
+/// # Example
+/// ```
+/// use cursock::*;
+///
+/// // QinQ frame with service vlan 10 and customer vlan 100
+/// let mut frame = vec![0; MAC_LEN * 2];
+/// frame.extend_from_slice(&VlanTag::new(10, 0).expect("vlan error").with_tpid(QINQ_PROTO).to_bytes());
+/// frame.extend_from_slice(&VlanTag::new(100, 5).expect("vlan error").to_bytes());
+/// frame.extend_from_slice(&IPV4_PROTO.to_be_bytes());
+/// frame.extend_from_slice(&[1, 2, 3]);
+///
+/// let (header, tags, payload) = EthernetHeader::parse_tagged(&frame).expect("parse error");
+///
+/// assert_eq!(tags.len(), 2);
+/// assert_eq!(tags[0].get_tpid(), &QINQ_PROTO);
+/// assert_eq!(tags[0].get_vlan_id(), &10);
+/// assert_eq!(tags[1].get_tpid(), &VLAN_PROTO);
+/// assert_eq!(tags[1].get_vlan_id(), &100);
+/// assert_eq!(tags[1].get_priority(), &5);
+/// // inner ethertype
+/// assert_eq!(header.get_ethertype(), &IPV4_PROTO);
+/// assert_eq!(payload, &[1, 2, 3]);
+///
+/// assert_eq!(VlanTag::from_tci(VLAN_PROTO, 0xb064).get_tci(), 0xb064);
+/// assert!(VlanTag::new(4096, 0).is_err());
+/// assert!(EthernetHeader::parse_tagged(&frame[..MAC_LEN * 2 + VLAN_HEADER_SIZE + 1]).is_err())
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VlanTag {
+    tpid: u16,
+    priority: u8,
+    // drop eligible indicator
+    dei: bool,
+    vlan_id: u16,
+}
+
+/// well-known ethertypes, other values are kept as Other
+///
+/// # Example
+/// ```
+/// use cursock::*;
+///
+/// assert_eq!(EtherType::from(0x0800), EtherType::Ipv4);
+/// assert_eq!(u16::from(EtherType::Vlan), VLAN_PROTO);
+/// assert_eq!(u16::from(EtherType::from(0x88cc)), 0x88cc);
+///
+/// let header = EthernetHeader::new(Mac::from([0xff; MAC_LEN]), Mac::from([0; MAC_LEN]), EtherType::Ipv6.into());
+/// let (parsed, _) = EthernetHeader::parse(&header.to_bytes()).expect("parse error");
+///
+/// assert_eq!(parsed.ether_type(), EtherType::Ipv6)
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EtherType {
+    Ipv4,
+    Ipv6,
+    Arp,
+    Vlan,
+    QinQ,
+    Other(u16),
+}
+
+impl From<u16> for EtherType {
+    fn from(value: u16) -> Self {
+        match value {
+            IPV4_PROTO => Self::Ipv4,
+            IPV6_PROTO => Self::Ipv6,
+            ARP_PROTO => Self::Arp,
+            VLAN_PROTO => Self::Vlan,
+            QINQ_PROTO => Self::QinQ,
+            value => Self::Other(value),
+        }
+    }
+}
+
+impl From<EtherType> for u16 {
+    fn from(value: EtherType) -> Self {
+        match value {
+            EtherType::Ipv4 => IPV4_PROTO,
+            EtherType::Ipv6 => IPV6_PROTO,
+            EtherType::Arp => ARP_PROTO,
+            EtherType::Vlan => VLAN_PROTO,
+            EtherType::QinQ => QINQ_PROTO,
+            EtherType::Other(value) => value,
+        }
+    }
+}
+
+impl EthernetHeader {
+    pub fn new(dst: Mac, src: Mac, ethertype: u16) -> Self {
+        Self {
+            dst,
+            src,
+            ethertype,
+            vlan: None,
+        }
+    }
+
+    /// Adds 802.1Q tag with vlan id and priority code point, which is serialized by [`EthernetHeader::to_bytes`]
+    ///
+    /// Returns InvalidInput error, if vlan id doesn't fit into 12 bits or priority into 3 bits
+    ///
+    /// # Example
+    /// ```
+    /// use cursock::*;
+    ///
+    /// let header = EthernetHeader::new(Mac::BROADCAST, Mac::ZERO, IPV4_PROTO)
+    ///     .with_vlan(100, 5)
+    ///     .expect("vlan error");
+    ///
+    /// let bytes = header.to_bytes();
+    /// assert_eq!(bytes.len(), ETH_HEADER_SIZE + VLAN_HEADER_SIZE);
+    /// assert_eq!(&bytes[12..], &[0x81, 0x00, 0xa0, 0x64, 0x08, 0x00]);
+    ///
+    /// let (parsed, tags, _) = EthernetHeader::parse_tagged(&bytes).expect("parse error");
+    /// assert_eq!(parsed.get_ethertype(), &IPV4_PROTO);
+    /// assert_eq!(tags, [*header.get_vlan().as_ref().expect("no vlan tag")])
+    /// ```
+    pub fn with_vlan(mut self, vlan_id: u16, priority: u8) -> io::Result<Self> {
+        self.vlan = Some(VlanTag::new(vlan_id, priority)?);
+
+        Ok(self)
+    }
+
+    /// Parses header from the start of buffer, returns it with the rest of the frame
+    pub fn parse(buffer: &[u8]) -> io::Result<(Self, &[u8])> {
+        if buffer.len() < ETH_HEADER_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "ethernet header is {} bytes long, but buffer has only {}",
+                    ETH_HEADER_SIZE,
+                    buffer.len()
+                ),
+            ));
+        }
+
+        let mut dst: [u8; MAC_LEN] = [0; MAC_LEN];
+        let mut src: [u8; MAC_LEN] = [0; MAC_LEN];
+        dst.copy_from_slice(&buffer[..MAC_LEN]);
+        src.copy_from_slice(&buffer[MAC_LEN..MAC_LEN * 2]);
+
+        let header: Self = Self {
+            dst: Mac::from(dst),
+            src: Mac::from(src),
+            ethertype: u16::from_be_bytes([buffer[12], buffer[13]]),
+            vlan: None,
+        };
+
+        Ok((header, &buffer[ETH_HEADER_SIZE..]))
+    }
+
+    /// Parses header and every 802.1Q and 802.1ad tag after it, outer tag goes first
+    ///
+    /// Ethertype of the returned header is the inner one, which follows the tags
+    pub fn parse_tagged(buffer: &[u8]) -> io::Result<(Self, Vec<VlanTag>, &[u8])> {
+        let (mut header, mut rest) = Self::parse(buffer)?;
+        let mut tags: Vec<VlanTag> = Vec::new();
+
+        while matches!(header.ethertype, VLAN_PROTO | QINQ_PROTO) {
+            if rest.len() < VLAN_HEADER_SIZE {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "vlan tag is {} bytes long, but buffer has only {}",
+                        VLAN_HEADER_SIZE,
+                        rest.len()
+                    ),
+                ));
+            }
+
+            tags.push(VlanTag::from_tci(
+                header.ethertype,
+                u16::from_be_bytes([rest[0], rest[1]]),
+            ));
+            header.ethertype = u16::from_be_bytes([rest[2], rest[3]]);
+            rest = &rest[VLAN_HEADER_SIZE..];
+        }
+
+        Ok((header, tags, rest))
+    }
+
+    /// Serializes header in network byte order, it's 4 bytes longer with a vlan tag
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::with_capacity(ETH_HEADER_SIZE + VLAN_HEADER_SIZE);
+
+        let dst: [u8; MAC_LEN] = self.dst.into();
+        let src: [u8; MAC_LEN] = self.src.into();
+
+        bytes.extend_from_slice(&dst);
+        bytes.extend_from_slice(&src);
+        if let Some(vlan) = &self.vlan {
+            bytes.extend_from_slice(&vlan.to_bytes());
+        }
+        bytes.extend_from_slice(&self.ethertype.to_be_bytes());
+
+        bytes
+    }
+
+    /// Returns ethertype as enum
+    pub fn ether_type(&self) -> EtherType {
+        EtherType::from(self.ethertype)
+    }
+
+    getters!(
+        pub get_dst(dst) -> Mac;
+        pub get_src(src) -> Mac;
+        pub get_ethertype(ethertype) -> u16;
+        pub get_vlan(vlan) -> Option<VlanTag>;
+    );
+    setters!(
+        pub set_dst(Mac) -> dst;
+        pub set_src(Mac) -> src;
+        pub set_ethertype(u16) -> ethertype;
+        pub set_vlan(Option<VlanTag>) -> vlan;
+    );
+}
+
+impl VlanTag {
+    /// Creates 802.1Q tag, returns InvalidInput error
+    /// if vlan id doesn't fit into 12 bits or priority into 3 bits
+    pub fn new(vlan_id: u16, priority: u8) -> io::Result<Self> {
+        if vlan_id > 0x0fff || priority > 7 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid vlan id {} or priority {}, they must be in 0..=4095 and 0..=7",
+                    vlan_id, priority
+                ),
+            ));
+        }
+
+        Ok(Self {
+            tpid: VLAN_PROTO,
+            priority,
+            dei: false,
+            vlan_id,
+        })
+    }
+
+    /// Splits tag control information into priority, dei and vlan id
+    pub fn from_tci(tpid: u16, tci: u16) -> Self {
+        Self {
+            tpid,
+            priority: (tci >> 13) as u8,
+            dei: tci & 0x1000 != 0,
+            vlan_id: tci & 0x0fff,
+        }
+    }
+
+    /// Sets tag protocol identifier, e.g. [`QINQ_PROTO`] for the outer tag
+    pub fn with_tpid(mut self, tpid: u16) -> Self {
+        self.tpid = tpid;
+        self
+    }
+
+    /// Returns tag control information
+    pub fn get_tci(&self) -> u16 {
+        (self.priority as u16) << 13 | (self.dei as u16) << 12 | self.vlan_id
+    }
+
+    /// Serializes tag protocol identifier and tag control information in network byte order
+    pub fn to_bytes(&self) -> [u8; VLAN_HEADER_SIZE] {
+        let mut bytes: [u8; VLAN_HEADER_SIZE] = [0; VLAN_HEADER_SIZE];
+
+        bytes[..2].copy_from_slice(&self.tpid.to_be_bytes());
+        bytes[2..].copy_from_slice(&self.get_tci().to_be_bytes());
+
+        bytes
+    }
+
+    getters!(
+        pub get_tpid(tpid) -> u16;
+        pub get_priority(priority) -> u8;
+        pub get_dei(dei) -> bool;
+        pub get_vlan_id(vlan_id) -> u16;
+    );
+}
+
+/// Inserts 802.1Q tag after mac addresses, vlan id is truncated to 12 bits and priority to 3 bits
+///
+/// Frames shorter than ethernet header are returned unchanged
+///
+/// # Example
+/// ```
+/// use cursock::*;
+///
+/// let mut frame = vec![0; 60];
+/// frame[12..14].copy_from_slice(&IPV4_PROTO.to_be_bytes());
+///
+/// let tagged = insert_vlan_tag(&frame, 100, 3);
+/// assert_eq!(tagged.len(), frame.len() + VLAN_HEADER_SIZE);
+/// assert_eq!(&tagged[12..18], &[0x81, 0x00, 0x60, 0x64, 0x08, 0x00]);
+///
+/// assert_eq!(strip_vlan_tag(&tagged), (Some(100), frame.clone()));
+/// // untagged frames are kept
+/// assert_eq!(strip_vlan_tag(&frame), (None, frame))
+/// ```
+pub fn insert_vlan_tag(frame: &[u8], vlan_id: u16, priority: u8) -> Vec<u8> {
+    if frame.len() < ETH_HEADER_SIZE {
+        return frame.to_vec();
+    }
+
+    let tag: VlanTag =
+        VlanTag::from_tci(VLAN_PROTO, (priority as u16 & 0x7) << 13 | vlan_id & 0x0fff);
+
+    let mut tagged: Vec<u8> = Vec::with_capacity(frame.len() + VLAN_HEADER_SIZE);
+    tagged.extend_from_slice(&frame[..MAC_LEN * 2]);
+    tagged.extend_from_slice(&tag.to_bytes());
+    tagged.extend_from_slice(&frame[MAC_LEN * 2..]);
+
+    tagged
+}
+
+/// Removes the outer 802.1Q tag, returns its vlan id with the untagged frame
+///
+/// Frames without 802.1Q tag are returned unchanged, including ones with 802.1ad outer tag,
+/// see [`EthernetHeader::parse_tagged`] for reading them
+pub fn strip_vlan_tag(frame: &[u8]) -> (Option<u16>, Vec<u8>) {
+    if frame.len() < ETH_HEADER_SIZE + VLAN_HEADER_SIZE
+        || u16::from_be_bytes([frame[12], frame[13]]) != VLAN_PROTO
+    {
+        return (None, frame.to_vec());
+    }
+
+    let tag: VlanTag = VlanTag::from_tci(VLAN_PROTO, u16::from_be_bytes([frame[14], frame[15]]));
+
+    let mut untagged: Vec<u8> = Vec::with_capacity(frame.len() - VLAN_HEADER_SIZE);
+    untagged.extend_from_slice(&frame[..MAC_LEN * 2]);
+    untagged.extend_from_slice(&frame[MAC_LEN * 2 + VLAN_HEADER_SIZE..]);
+
+    (Some(*tag.get_vlan_id()), untagged)
+}
+
+impl Socket {
+    /// Sends payload in an ethernet frame from the mac address of the socket interface,
+    /// returns count of sent bytes including the header
+    ///
+    /// Returns InvalidInput error, if the payload is longer than mtu of the interface
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let options = SocketOptions::new().ignore_outgoing(true);
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new_with("lo", options), Socket::new("lo")) {
+    ///         reader.set_filter("ether proto 0x88b5").expect("filter error");
+    ///
+    ///         let sent = writer.send_frame(&Mac::BROADCAST, 0x88b5, &[7; 46]).expect("send error");
+    ///         assert_eq!(sent, ETH_HEADER_SIZE + 46);
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         let length = reader.read_raw_packet(&mut buffer).expect("read error");
+    ///         let (header, payload) = EthernetHeader::parse(&buffer[..length]).expect("parse error");
+    ///
+    ///         assert!(header.get_dst().is_broadcast());
+    ///         assert!(header.get_src() == writer.get_src_mac());
+    ///         assert_eq!(payload, &[7; 46]);
+    ///
+    ///         let mtu = writer.get_mtu().expect("mtu error") as usize;
+    ///         let err = writer.send_frame(&Mac::BROADCAST, 0x88b5, &vec![0; mtu + 1]).err().expect("sent");
+    ///         assert_eq!(err.kind(), io::ErrorKind::InvalidInput)
+    ///     }
+    /// }
+    /// ```
+    pub fn send_frame(&self, dst: &Mac, ethertype: u16, payload: &[u8]) -> io::Result<usize> {
+        // if mtu is unknown, it's left to the os to reject the frame
+        if let Ok(mtu) = self.get_mtu() {
+            if payload.len() > mtu as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "payload of {} bytes exceeds mtu {} of {}",
+                        payload.len(),
+                        mtu,
+                        self.get_adapter().get_name()
+                    ),
+                ));
+            }
+        }
+
+        let header: EthernetHeader = EthernetHeader::new(*dst, *self.get_src_mac(), ethertype);
+
+        let mut frame: Vec<u8> = Vec::with_capacity(ETH_HEADER_SIZE + payload.len());
+        frame.extend_from_slice(&header.to_bytes());
+        frame.extend_from_slice(payload);
+
+        self.send_raw_packet(&frame)
+    }
+}
@@ -0,0 +1,830 @@
+use std::io;
+use std::net;
+
+use crate::bpf::parse_number;
+use crate::*;
+
+const BPF_LD: u16 = 0x00;
+const BPF_LDX: u16 = 0x01;
+const BPF_ALU: u16 = 0x04;
+const BPF_JMP: u16 = 0x05;
+const BPF_RET: u16 = 0x06;
+
+const BPF_W: u16 = 0x00;
+const BPF_H: u16 = 0x08;
+const BPF_B: u16 = 0x10;
+
+const BPF_ABS: u16 = 0x20;
+const BPF_IND: u16 = 0x40;
+const BPF_LEN: u16 = 0x80;
+const BPF_MSH: u16 = 0xa0;
+
+const BPF_AND: u16 = 0x50;
+const BPF_JEQ: u16 = 0x10;
+const BPF_JGT: u16 = 0x20;
+const BPF_JGE: u16 = 0x30;
+const BPF_JSET: u16 = 0x40;
+
+/// value returned for accepted frames, same as tcpdump uses
+const SNAPLEN: u32 = 0x40000;
+
+const PROTO_ICMP: u32 = ICMP_PROTO as u32;
+const PROTO_TCP: u32 = TCP_PROTO as u32;
+const PROTO_UDP: u32 = UDP_PROTO as u32;
+const PROTO_ICMPV6: u32 = 58;
+const PROTO_SCTP: u32 = 132;
+
+impl BpfProgram {
+    /// Compiles tcpdump-style filter expression into bpf program for ethernet frames
+    ///
+    /// Only common part of pcap-filter syntax is supported:
+    /// - `ether [src|dst|host] <mac>`, `ether proto <type>`, `ether broadcast`, `ether multicast`
+    /// - `ip`, `ip6`, `arp`, `tcp`, `udp`, `sctp`, `icmp`, `icmp6`, `ip proto <n>`, `ip6 proto <n>`
+    /// - `[src|dst] host <ip>`, `[src|dst] net <ipv4>/<len>`, `[tcp|udp|sctp] [src|dst] port <n>`
+    /// - `vlan [id]`, `less <n>`, `greater <n>`
+    /// - `not`, `and`, `or` (and `!`, `&&`, `||`) with parentheses, `and` and `or` have same precedence
+    ///
+    /// Unsupported or malformed expressions return InvalidInput error
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// // tcpdump -dd arp
+    /// let tcpdump = BpfProgram::from_dd(
+    ///     "{ 0x28, 0, 0, 0x0000000c },
+    ///      { 0x15, 0, 1, 0x00000806 },
+    ///      { 0x6, 0, 0, 0x00040000 },
+    ///      { 0x6, 0, 0, 0x00000000 },",
+    /// )
+    /// .expect("parse error");
+    ///
+    /// assert_eq!(BpfProgram::compile("arp").expect("compile error"), tcpdump);
+    ///
+    /// assert!(BpfProgram::compile("tcp port 80 and not (src host 10.0.0.1 or net 192.168.0.0/16)").is_ok());
+    /// assert!(BpfProgram::compile("tcp port").is_err());
+    /// assert!(BpfProgram::compile("(arp").is_err())
+    /// ```
+    pub fn compile(filter: &str) -> io::Result<Self> {
+        let tokens: Vec<Token> = tokenize(filter)?;
+
+        // empty expression accepts every frame
+        if tokens.is_empty() {
+            return Ok(Self::new(vec![BpfInstruction::new(BPF_RET, 0, 0, SNAPLEN)]));
+        }
+
+        let mut parser: Parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let expr: Expr = parser.parse_expr()?;
+
+        if let Some(token) = parser.peek() {
+            return Err(invalid_filter(format!("unexpected {}", token)));
+        }
+
+        let mut codegen: Codegen = Codegen::default();
+
+        let accept: usize = codegen.new_label();
+        let reject: usize = codegen.new_label();
+
+        codegen.compile(&expr, accept, reject);
+
+        codegen.place(accept);
+        codegen.emit(BPF_RET, SNAPLEN);
+        codegen.place(reject);
+        codegen.emit(BPF_RET, 0);
+
+        codegen.finish()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Token {
+    Word(String),
+    LParen,
+    RParen,
+    Not,
+    And,
+    Or,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "\"{}\"", word),
+            Token::LParen => write!(f, "\"(\""),
+            Token::RParen => write!(f, "\")\""),
+            Token::Not => write!(f, "\"not\""),
+            Token::And => write!(f, "\"and\""),
+            Token::Or => write!(f, "\"or\""),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Size {
+    Byte,
+    Half,
+    Word,
+}
+
+/// offset of the loaded value
+#[derive(Clone, Copy)]
+enum Offset {
+    /// from the start of the frame
+    Frame(u32),
+    /// from the end of ipv4 header, which has variable length
+    Ipv4Payload(u32),
+}
+
+#[derive(Clone, Copy)]
+enum Load {
+    Packet(Size, Offset),
+    Len,
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Eq,
+    Gt,
+    Ge,
+    Set,
+}
+
+#[derive(Clone, Copy)]
+enum Dir {
+    Src,
+    Dst,
+    Any,
+}
+
+/// single comparison of loaded value, optionally masked
+#[derive(Clone, Copy)]
+struct Test {
+    load: Load,
+    mask: Option<u32>,
+    op: Op,
+    value: u32,
+}
+
+enum Expr {
+    Test(Test),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+/// instruction with jumps to labels, which are resolved in the end
+struct Pending {
+    code: u16,
+    k: u32,
+    jt: Option<usize>,
+    jf: Option<usize>,
+}
+
+#[derive(Default)]
+struct Codegen {
+    code: Vec<Pending>,
+    labels: Vec<Option<usize>>,
+}
+
+fn tokenize(filter: &str) -> io::Result<Vec<Token>> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut chars = filter.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            ' ' | '\t' | '\n' | '\r' => {}
+            '(' => tokens.push(Token::LParen),
+            ')' => tokens.push(Token::RParen),
+            '!' => tokens.push(Token::Not),
+            '&' | '|' => {
+                if chars.next() != Some(char) {
+                    return Err(invalid_filter(format!("expected \"{0}{0}\"", char)));
+                }
+
+                tokens.push(match char {
+                    '&' => Token::And,
+                    _ => Token::Or,
+                })
+            }
+            _ => {
+                let mut word: String = char.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || "()!&|".contains(next) {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+
+                tokens.push(match &word[..] {
+                    "not" => Token::Not,
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    _ => Token::Word(word),
+                })
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_word(&self) -> Option<&str> {
+        match self.peek() {
+            Some(Token::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    fn next_word(&mut self, expected: &str) -> io::Result<String> {
+        match self.tokens.get(self.position) {
+            Some(Token::Word(word)) => {
+                self.position += 1;
+                Ok(word.clone())
+            }
+            Some(token) => Err(invalid_filter(format!(
+                "expected {}, found {}",
+                expected, token
+            ))),
+            None => Err(invalid_filter(format!(
+                "expected {}, found end of expression",
+                expected
+            ))),
+        }
+    }
+
+    /// skips the word if it's next
+    fn accept_word(&mut self, word: &str) -> bool {
+        if self.peek_word() == Some(word) {
+            self.position += 1;
+            return true;
+        }
+
+        false
+    }
+
+    fn parse_expr(&mut self) -> io::Result<Expr> {
+        let mut expr: Expr = self.parse_unary()?;
+
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.position += 1;
+                    expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?))
+                }
+                Some(Token::Or) => {
+                    self.position += 1;
+                    expr = Expr::Or(Box::new(expr), Box::new(self.parse_unary()?))
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn parse_unary(&mut self) -> io::Result<Expr> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.position += 1;
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::LParen) => {
+                self.position += 1;
+                let expr: Expr = self.parse_expr()?;
+
+                match self.peek() {
+                    Some(Token::RParen) => {
+                        self.position += 1;
+                        Ok(expr)
+                    }
+                    _ => Err(invalid_filter("expected \")\"")),
+                }
+            }
+            _ => self.parse_primitive(),
+        }
+    }
+
+    fn parse_primitive(&mut self) -> io::Result<Expr> {
+        let word: String = self.next_word("primitive")?;
+
+        match &word[..] {
+            "ether" => self.parse_ether(),
+            "ip" => match self.accept_word("proto") {
+                true => Ok(ip_proto(self.parse_ip_proto()?)),
+                false => Ok(ether_type(IPV4_PROTO as u32)),
+            },
+            "ip6" => match self.accept_word("proto") {
+                true => Ok(ip6_proto(self.parse_ip_proto()?)),
+                false => Ok(ether_type(IPV6_PROTO as u32)),
+            },
+            "arp" => Ok(ether_type(ARP_PROTO as u32)),
+            "icmp" => Ok(ip_proto(PROTO_ICMP)),
+            "icmp6" => Ok(ip6_proto(PROTO_ICMPV6)),
+            "tcp" | "udp" | "sctp" => {
+                let proto: u32 = match &word[..] {
+                    "tcp" => PROTO_TCP,
+                    "udp" => PROTO_UDP,
+                    _ => PROTO_SCTP,
+                };
+
+                match self.peek_word() {
+                    Some("src" | "dst" | "port") => {
+                        let dir: Dir = self.parse_dir();
+                        self.expect_word("port")?;
+
+                        Ok(port(&[proto], dir, self.parse_port()?))
+                    }
+                    _ => Ok(Expr::Or(
+                        Box::new(ip_proto(proto)),
+                        Box::new(ip6_proto(proto)),
+                    )),
+                }
+            }
+            "vlan" => {
+                let vlan: Expr = ether_type(VLAN_PROTO as u32);
+
+                match self.peek_word().map(parse_number) {
+                    Some(Some(vlan_id)) if vlan_id < 4096 => {
+                        self.position += 1;
+
+                        Ok(and(vlan, masked(Size::Half, 14, 0x0fff, vlan_id)))
+                    }
+                    Some(Some(vlan_id)) => {
+                        Err(invalid_filter(format!("invalid vlan id {}", vlan_id)))
+                    }
+                    _ => Ok(vlan),
+                }
+            }
+            "less" => {
+                let length: u32 = self.parse_number("length")?;
+
+                Ok(Expr::Not(Box::new(Expr::Test(Test {
+                    load: Load::Len,
+                    mask: None,
+                    op: Op::Gt,
+                    value: length,
+                }))))
+            }
+            "greater" => {
+                let length: u32 = self.parse_number("length")?;
+
+                Ok(Expr::Test(Test {
+                    load: Load::Len,
+                    mask: None,
+                    op: Op::Ge,
+                    value: length,
+                }))
+            }
+            "src" | "dst" | "host" | "net" | "port" => {
+                self.position -= 1;
+                let dir: Dir = self.parse_dir();
+
+                match self.peek_word() {
+                    Some("port") => {
+                        self.position += 1;
+                        Ok(port(
+                            &[PROTO_TCP, PROTO_UDP, PROTO_SCTP],
+                            dir,
+                            self.parse_port()?,
+                        ))
+                    }
+                    Some("net") => {
+                        self.position += 1;
+                        self.parse_net(dir)
+                    }
+                    _ => {
+                        // host keyword can be omitted after direction
+                        self.accept_word("host");
+                        self.parse_host(dir)
+                    }
+                }
+            }
+            _ => Err(invalid_filter(format!("unknown primitive \"{}\"", word))),
+        }
+    }
+
+    fn parse_ether(&mut self) -> io::Result<Expr> {
+        let word: String = self.next_word("ether qualifier")?;
+
+        match &word[..] {
+            "src" | "dst" | "host" => {
+                let dir: Dir = match &word[..] {
+                    "src" => Dir::Src,
+                    "dst" => Dir::Dst,
+                    _ => Dir::Any,
+                };
+                // host keyword is allowed after direction
+                if !matches!(dir, Dir::Any) {
+                    self.accept_word("host");
+                }
+
+                let mac: String = self.next_word("mac address")?;
+                let mac: Mac = Mac::parse(&mac)
+                    .map_err(|_| invalid_filter(format!("invalid mac address \"{}\"", mac)))?;
+
+                Ok(dir_expr(dir, |offset| ether_addr(offset, &mac), 6, 0))
+            }
+            "proto" => {
+                let proto: String = self.next_word("ether type")?;
+                let proto: u32 = match &proto[..] {
+                    "ip" => IPV4_PROTO as u32,
+                    "ip6" => IPV6_PROTO as u32,
+                    "arp" => ARP_PROTO as u32,
+                    _ => parse_number(&proto)
+                        .filter(|proto| *proto <= u16::MAX as u32)
+                        .ok_or_else(|| {
+                            invalid_filter(format!("invalid ether type \"{}\"", proto))
+                        })?,
+                };
+
+                Ok(ether_type(proto))
+            }
+            "broadcast" => Ok(ether_addr(0, &Mac::from([0xff; MAC_LEN]))),
+            "multicast" => Ok(Expr::Test(Test {
+                load: Load::Packet(Size::Byte, Offset::Frame(0)),
+                mask: None,
+                op: Op::Set,
+                value: 1,
+            })),
+            _ => Err(invalid_filter(format!(
+                "unknown ether qualifier \"{}\"",
+                word
+            ))),
+        }
+    }
+
+    fn parse_dir(&mut self) -> Dir {
+        if self.accept_word("src") {
+            return Dir::Src;
+        }
+        if self.accept_word("dst") {
+            return Dir::Dst;
+        }
+
+        Dir::Any
+    }
+
+    fn parse_host(&mut self, dir: Dir) -> io::Result<Expr> {
+        let host: String = self.next_word("host address")?;
+
+        match host.parse::<net::IpAddr>() {
+            Ok(net::IpAddr::V4(ipv4)) => Ok(ipv4_net(dir, u32::from(ipv4), u32::MAX)),
+            Ok(net::IpAddr::V6(ipv6)) => {
+                let words: [u8; IPV6_LEN] = ipv6.octets();
+
+                Ok(and(
+                    ether_type(IPV6_PROTO as u32),
+                    dir_expr(
+                        dir,
+                        |offset| {
+                            (0..4)
+                                .map(|i| {
+                                    let word: u32 = u32::from_be_bytes([
+                                        words[i * 4],
+                                        words[i * 4 + 1],
+                                        words[i * 4 + 2],
+                                        words[i * 4 + 3],
+                                    ]);
+                                    cmp(Size::Word, Offset::Frame(offset + i as u32 * 4), word)
+                                })
+                                .reduce(and)
+                                .expect("ipv6 address has 4 words")
+                        },
+                        22,
+                        38,
+                    ),
+                ))
+            }
+            Err(_) => Err(invalid_filter(format!("invalid host \"{}\"", host))),
+        }
+    }
+
+    fn parse_net(&mut self, dir: Dir) -> io::Result<Expr> {
+        let network: String = self.next_word("network")?;
+        let invalid = || invalid_filter(format!("invalid network \"{}\"", network));
+
+        let (addr, prefix) = match network.split_once('/') {
+            Some((addr, prefix)) => (addr, prefix.parse::<u32>().map_err(|_| invalid())?),
+            None => (&network[..], 32),
+        };
+
+        let addr: net::Ipv4Addr = addr.parse::<net::Ipv4Addr>().map_err(|_| invalid())?;
+        if prefix > 32 {
+            return Err(invalid());
+        }
+
+        let mask: u32 = match prefix {
+            0 => 0,
+            prefix => u32::MAX << (32 - prefix),
+        };
+
+        if u32::from(addr) & !mask != 0 {
+            return Err(invalid_filter(format!(
+                "network \"{}\" has host bits set",
+                network
+            )));
+        }
+
+        Ok(ipv4_net(dir, u32::from(addr), mask))
+    }
+
+    fn parse_ip_proto(&mut self) -> io::Result<u32> {
+        let proto: String = self.next_word("protocol")?;
+
+        match &proto[..] {
+            "icmp" => Ok(PROTO_ICMP),
+            "tcp" => Ok(PROTO_TCP),
+            "udp" => Ok(PROTO_UDP),
+            "sctp" => Ok(PROTO_SCTP),
+            "icmp6" => Ok(PROTO_ICMPV6),
+            _ => parse_number(&proto)
+                .filter(|proto| *proto <= u8::MAX as u32)
+                .ok_or_else(|| invalid_filter(format!("invalid protocol \"{}\"", proto))),
+        }
+    }
+
+    fn parse_port(&mut self) -> io::Result<u32> {
+        let port: String = self.next_word("port")?;
+
+        parse_number(&port)
+            .filter(|port| *port <= u16::MAX as u32)
+            .ok_or_else(|| invalid_filter(format!("invalid port \"{}\"", port)))
+    }
+
+    fn parse_number(&mut self, expected: &str) -> io::Result<u32> {
+        let number: String = self.next_word(expected)?;
+
+        parse_number(&number)
+            .ok_or_else(|| invalid_filter(format!("invalid {} \"{}\"", expected, number)))
+    }
+
+    fn expect_word(&mut self, word: &str) -> io::Result<()> {
+        let found: String = self.next_word(&format!("\"{}\"", word))?;
+
+        match found == word {
+            true => Ok(()),
+            false => Err(invalid_filter(format!(
+                "expected \"{}\", found \"{}\"",
+                word, found
+            ))),
+        }
+    }
+}
+
+impl Codegen {
+    fn new_label(&mut self) -> usize {
+        self.labels.push(None);
+        self.labels.len() - 1
+    }
+
+    fn place(&mut self, label: usize) {
+        self.labels[label] = Some(self.code.len())
+    }
+
+    fn emit(&mut self, code: u16, k: u32) {
+        self.code.push(Pending {
+            code,
+            k,
+            jt: None,
+            jf: None,
+        })
+    }
+
+    /// emits code, which jumps to on_true label if expression matches and to on_false otherwise
+    fn compile(&mut self, expr: &Expr, on_true: usize, on_false: usize) {
+        match expr {
+            Expr::Test(test) => self.compile_test(test, on_true, on_false),
+            Expr::And(left, right) => {
+                let next: usize = self.new_label();
+                self.compile(left, next, on_false);
+                self.place(next);
+                self.compile(right, on_true, on_false)
+            }
+            Expr::Or(left, right) => {
+                let next: usize = self.new_label();
+                self.compile(left, on_true, next);
+                self.place(next);
+                self.compile(right, on_true, on_false)
+            }
+            Expr::Not(expr) => self.compile(expr, on_false, on_true),
+        }
+    }
+
+    fn compile_test(&mut self, test: &Test, on_true: usize, on_false: usize) {
+        match test.load {
+            Load::Len => self.emit(BPF_LD | BPF_W | BPF_LEN, 0),
+            Load::Packet(size, offset) => {
+                let size: u16 = match size {
+                    Size::Byte => BPF_B,
+                    Size::Half => BPF_H,
+                    Size::Word => BPF_W,
+                };
+
+                match offset {
+                    Offset::Frame(offset) => self.emit(BPF_LD | size | BPF_ABS, offset),
+                    Offset::Ipv4Payload(offset) => {
+                        // x = ipv4 header length
+                        self.emit(BPF_LDX | BPF_B | BPF_MSH, ETH_HEADER_SIZE as u32);
+                        self.emit(BPF_LD | size | BPF_IND, ETH_HEADER_SIZE as u32 + offset)
+                    }
+                }
+            }
+        }
+
+        if let Some(mask) = test.mask {
+            self.emit(BPF_ALU | BPF_AND, mask)
+        }
+
+        let op: u16 = match test.op {
+            Op::Eq => BPF_JEQ,
+            Op::Gt => BPF_JGT,
+            Op::Ge => BPF_JGE,
+            Op::Set => BPF_JSET,
+        };
+
+        self.code.push(Pending {
+            code: BPF_JMP | op,
+            k: test.value,
+            jt: Some(on_true),
+            jf: Some(on_false),
+        })
+    }
+
+    fn finish(self) -> io::Result<BpfProgram> {
+        let mut instructions: Vec<BpfInstruction> = Vec::with_capacity(self.code.len());
+
+        for (position, pending) in self.code.iter().enumerate() {
+            let resolve = |label: Option<usize>| -> io::Result<u8> {
+                let label: usize = match label {
+                    Some(label) => label,
+                    None => return Ok(0),
+                };
+                let target: usize = self.labels[label].expect("every label is placed");
+
+                u8::try_from(target - position - 1).map_err(|_| {
+                    invalid_filter("filter is too long, jump doesn't fit into bpf instruction")
+                })
+            };
+
+            instructions.push(BpfInstruction::new(
+                pending.code,
+                resolve(pending.jt)?,
+                resolve(pending.jf)?,
+                pending.k,
+            ))
+        }
+
+        Ok(BpfProgram::new(instructions))
+    }
+}
+
+fn and(left: Expr, right: Expr) -> Expr {
+    Expr::And(Box::new(left), Box::new(right))
+}
+
+fn cmp(size: Size, offset: Offset, value: u32) -> Expr {
+    Expr::Test(Test {
+        load: Load::Packet(size, offset),
+        mask: None,
+        op: Op::Eq,
+        value,
+    })
+}
+
+fn masked(size: Size, offset: u32, mask: u32, value: u32) -> Expr {
+    Expr::Test(Test {
+        load: Load::Packet(size, Offset::Frame(offset)),
+        mask: Some(mask),
+        op: Op::Eq,
+        value,
+    })
+}
+
+fn ether_type(proto: u32) -> Expr {
+    cmp(Size::Half, Offset::Frame(12), proto)
+}
+
+fn ether_addr(offset: u32, mac: &Mac) -> Expr {
+    let mac: [u8; MAC_LEN] = (*mac).into();
+
+    and(
+        cmp(
+            Size::Word,
+            Offset::Frame(offset + 2),
+            u32::from_be_bytes([mac[2], mac[3], mac[4], mac[5]]),
+        ),
+        cmp(
+            Size::Half,
+            Offset::Frame(offset),
+            u16::from_be_bytes([mac[0], mac[1]]) as u32,
+        ),
+    )
+}
+
+fn ip_proto(proto: u32) -> Expr {
+    and(
+        ether_type(IPV4_PROTO as u32),
+        cmp(Size::Byte, Offset::Frame(23), proto),
+    )
+}
+
+/// extension headers aren't followed
+fn ip6_proto(proto: u32) -> Expr {
+    and(
+        ether_type(IPV6_PROTO as u32),
+        cmp(Size::Byte, Offset::Frame(20), proto),
+    )
+}
+
+/// builds expression for source, destination or any of them
+fn dir_expr<F>(dir: Dir, test: F, src: u32, dst: u32) -> Expr
+where
+    F: Fn(u32) -> Expr,
+{
+    match dir {
+        Dir::Src => test(src),
+        Dir::Dst => test(dst),
+        Dir::Any => Expr::Or(Box::new(test(src)), Box::new(test(dst))),
+    }
+}
+
+/// matches ipv4 and arp packets, whose address is in the network
+fn ipv4_net(dir: Dir, addr: u32, mask: u32) -> Expr {
+    let test = move |offset: u32| match mask {
+        u32::MAX => cmp(Size::Word, Offset::Frame(offset), addr),
+        mask => masked(Size::Word, offset, mask, addr),
+    };
+
+    Expr::Or(
+        Box::new(and(
+            ether_type(IPV4_PROTO as u32),
+            dir_expr(dir, test, 26, 30),
+        )),
+        Box::new(and(
+            ether_type(ARP_PROTO as u32),
+            dir_expr(dir, test, 28, 38),
+        )),
+    )
+}
+
+/// matches tcp, udp or sctp port, ipv4 fragments except the first one don't have ports
+fn port(protos: &[u32], dir: Dir, port: u32) -> Expr {
+    let any_proto = |offset: u32| {
+        protos
+            .iter()
+            .map(|proto| cmp(Size::Byte, Offset::Frame(offset), *proto))
+            .reduce(|left, right| Expr::Or(Box::new(left), Box::new(right)))
+            .expect("at least one protocol")
+    };
+
+    let not_fragment: Expr = Expr::Not(Box::new(Expr::Test(Test {
+        load: Load::Packet(Size::Half, Offset::Frame(20)),
+        mask: None,
+        op: Op::Set,
+        value: 0x1fff,
+    })));
+
+    let ipv4: Expr = and(
+        and(
+            and(ether_type(IPV4_PROTO as u32), any_proto(23)),
+            not_fragment,
+        ),
+        dir_expr(
+            dir,
+            |offset| cmp(Size::Half, Offset::Ipv4Payload(offset), port),
+            0,
+            2,
+        ),
+    );
+
+    let ipv6: Expr = and(
+        and(ether_type(IPV6_PROTO as u32), any_proto(20)),
+        dir_expr(
+            dir,
+            |offset| cmp(Size::Half, Offset::Frame(offset), port),
+            54,
+            56,
+        ),
+    );
+
+    Expr::Or(Box::new(ipv4), Box::new(ipv6))
+}
+
+fn invalid_filter<S: Into<String>>(message: S) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("invalid filter: {}", message.into()),
+    )
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::net;
+use std::time;
+
+use crate::*;
+
+/// 5-tuple which identifies a flow, ports are zero for protocols without them
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct FlowKey {
+    src: net::IpAddr,
+    dst: net::IpAddr,
+    src_port: u16,
+    dst_port: u16,
+    protocol: u8,
+}
+
+/// counters collected for one flow
+#[derive(Clone, Debug)]
+pub struct FlowSummary {
+    key: FlowKey,
+    packets: u64,
+    bytes: u64,
+    first_seen: time::SystemTime,
+    last_seen: time::SystemTime,
+}
+
+/// Accumulates per-flow statistics from captured ethernet frames
+///
+/// # Example
+/// ```
+/// use cursock::*;
+/// use std::time::{Duration, SystemTime};
+///
+/// fn packet(src: u8, len: usize) -> Vec<u8> {
+///     let mut frame = vec![0; ETH_HEADER_SIZE + IPV4_HEADER_SIZE + 8 + len];
+///     frame[12..14].copy_from_slice(&IPV4_PROTO.to_be_bytes());
+///     frame[14] = 0x45;
+///     frame[23] = 17;
+///     frame[26..30].copy_from_slice(&[10, 0, 0, src]);
+///     frame[30..34].copy_from_slice(&[10, 0, 0, 254]);
+///     frame[34..36].copy_from_slice(&5000u16.to_be_bytes());
+///     frame[36..38].copy_from_slice(&53u16.to_be_bytes());
+///     frame
+/// }
+///
+/// let start = SystemTime::UNIX_EPOCH;
+/// let mut stats = FlowStats::new();
+///
+/// for i in 0..10 {
+///     stats.push(&packet(1, 1000), start + Duration::from_millis(i * 100));
+/// }
+/// stats.push(&packet(2, 10), start);
+///
+/// let top = stats.top_flows(1);
+///
+/// assert_eq!(top.len(), 1);
+/// assert_eq!(top[0].get_key().get_src_port(), &5000);
+/// assert_eq!(top[0].get_packets(), &10);
+/// assert_eq!(top[0].average_gap(), Some(Duration::from_millis(100)));
+/// assert_eq!(stats.len(), 2)
+/// ```
+pub struct FlowStats {
+    flows: HashMap<FlowKey, FlowSummary>,
+}
+
+impl FlowKey {
+    pub fn new(
+        src: net::IpAddr,
+        dst: net::IpAddr,
+        src_port: u16,
+        dst_port: u16,
+        protocol: u8,
+    ) -> Self {
+        Self {
+            src,
+            dst,
+            src_port,
+            dst_port,
+            protocol,
+        }
+    }
+
+    /// Parses flow key from ethernet frame, supports ipv4 and ipv6 without extension headers
+    pub fn from_frame(frame: &[u8]) -> Option<Self> {
+        if frame.len() < ETH_HEADER_SIZE {
+            return None;
+        }
+
+        let packet: &[u8] = &frame[ETH_HEADER_SIZE..];
+
+        let (src, dst, protocol, transport) = match u16::from_be_bytes([frame[12], frame[13]]) {
+            IPV4_PROTO => {
+                if packet.len() < IPV4_HEADER_SIZE {
+                    return None;
+                }
+
+                let header_len: usize = ((packet[0] & 0x0f) as usize) * 4;
+                let frag: u16 = u16::from_be_bytes([packet[6], packet[7]]) & 0x1fff;
+                let mut src: [u8; IPV4_LEN] = [0; IPV4_LEN];
+                let mut dst: [u8; IPV4_LEN] = [0; IPV4_LEN];
+                src.copy_from_slice(&packet[12..16]);
+                dst.copy_from_slice(&packet[16..20]);
+
+                // only the first fragment carries ports
+                let transport: &[u8] = match frag == 0 && packet.len() >= header_len {
+                    true => &packet[header_len..],
+                    false => &[],
+                };
+
+                (
+                    net::IpAddr::V4(net::Ipv4Addr::from(src)),
+                    net::IpAddr::V4(net::Ipv4Addr::from(dst)),
+                    packet[9],
+                    transport,
+                )
+            }
+            IPV6_PROTO => {
+                const IPV6_HEADER_SIZE: usize = 40;
+                if packet.len() < IPV6_HEADER_SIZE {
+                    return None;
+                }
+
+                let mut src: [u8; IPV6_LEN] = [0; IPV6_LEN];
+                let mut dst: [u8; IPV6_LEN] = [0; IPV6_LEN];
+                src.copy_from_slice(&packet[8..24]);
+                dst.copy_from_slice(&packet[24..40]);
+
+                (
+                    net::IpAddr::V6(net::Ipv6Addr::from(src)),
+                    net::IpAddr::V6(net::Ipv6Addr::from(dst)),
+                    packet[6],
+                    &packet[IPV6_HEADER_SIZE..],
+                )
+            }
+            _ => return None,
+        };
+
+        let is_tcp_or_udp: bool = protocol == TCP_PROTO as u8 || protocol == UDP_PROTO as u8;
+
+        let (src_port, dst_port) = match is_tcp_or_udp && transport.len() >= 4 {
+            true => (
+                u16::from_be_bytes([transport[0], transport[1]]),
+                u16::from_be_bytes([transport[2], transport[3]]),
+            ),
+            false => (0, 0),
+        };
+
+        Some(Self {
+            src,
+            dst,
+            src_port,
+            dst_port,
+            protocol,
+        })
+    }
+
+    getters!(
+        pub get_src(src) -> net::IpAddr;
+        pub get_dst(dst) -> net::IpAddr;
+        pub get_src_port(src_port) -> u16;
+        pub get_dst_port(dst_port) -> u16;
+        pub get_protocol(protocol) -> u8;
+    );
+}
+
+impl FlowSummary {
+    getters!(
+        pub get_key(key) -> FlowKey;
+        pub get_packets(packets) -> u64;
+        pub get_bytes(bytes) -> u64;
+        pub get_first_seen(first_seen) -> time::SystemTime;
+        pub get_last_seen(last_seen) -> time::SystemTime;
+    );
+
+    /// Returns time between first and last packet of the flow
+    pub fn duration(&self) -> time::Duration {
+        self.last_seen
+            .duration_since(self.first_seen)
+            .unwrap_or_default()
+    }
+
+    /// Returns average throughput in bytes per second
+    pub fn throughput(&self) -> f64 {
+        let duration: f64 = self.duration().as_secs_f64();
+
+        match duration > 0.0 {
+            true => self.bytes as f64 / duration,
+            false => 0.0,
+        }
+    }
+
+    /// Returns average gap between packets, None for flows with one packet
+    pub fn average_gap(&self) -> Option<time::Duration> {
+        match self.packets {
+            0 | 1 => None,
+            // divided in nanoseconds, since packet count doesn't fit into u32 divisor of Duration
+            packets => {
+                let gap: u128 = self.duration().as_nanos() / (packets - 1) as u128;
+
+                Some(time::Duration::new(
+                    (gap / 1_000_000_000) as u64,
+                    (gap % 1_000_000_000) as u32,
+                ))
+            }
+        }
+    }
+}
+
+impl FlowStats {
+    pub fn new() -> Self {
+        Self {
+            flows: HashMap::new(),
+        }
+    }
+
+    /// Accounts captured frame, returns false if frame isn't an ip packet
+    pub fn push(&mut self, frame: &[u8], timestamp: time::SystemTime) -> bool {
+        let key: FlowKey = match FlowKey::from_frame(frame) {
+            Some(key) => key,
+            None => return false,
+        };
+
+        let summary: &mut FlowSummary = self.flows.entry(key.clone()).or_insert(FlowSummary {
+            key,
+            packets: 0,
+            bytes: 0,
+            first_seen: timestamp,
+            last_seen: timestamp,
+        });
+
+        summary.packets += 1;
+        summary.bytes += frame.len() as u64;
+        summary.first_seen = summary.first_seen.min(timestamp);
+        summary.last_seen = summary.last_seen.max(timestamp);
+
+        true
+    }
+
+    /// Returns n flows with the most bytes
+    pub fn top_flows(&self, n: usize) -> Vec<FlowSummary> {
+        let mut flows: Vec<&FlowSummary> = self.flows.values().collect();
+        flows.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(b.packets.cmp(&a.packets)));
+
+        flows.into_iter().take(n).cloned().collect()
+    }
+
+    /// Returns summary of the flow
+    pub fn get(&self, key: &FlowKey) -> Option<&FlowSummary> {
+        self.flows.get(key)
+    }
+
+    /// Returns count of tracked flows
+    pub fn len(&self) -> usize {
+        self.flows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.flows.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.flows.clear()
+    }
+}
+
+impl Default for FlowStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
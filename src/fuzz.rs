@@ -0,0 +1,222 @@
+use std::io;
+use std::ops;
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+use crate::*;
+
+/// describes how frames are mutated by [`Fuzzer`]
+///
+/// # Example
+/// ```
+/// use cursock::*;
+///
+/// let config = FuzzConfig::new(1337)
+///     .range(ETH_HEADER_SIZE..ETH_HEADER_SIZE + IPV4_HEADER_SIZE)
+///     .bit_flips(3)
+///     .byte_corruptions(0)
+///     .resize(false);
+///
+/// assert_eq!(config.get_seed(), &1337)
+/// ```
+#[derive(Clone)]
+pub struct FuzzConfig {
+    seed: u64,
+    ranges: Vec<ops::Range<usize>>,
+    bit_flips: usize,
+    byte_corruptions: usize,
+    resize: bool,
+    max_len: usize,
+}
+
+/// seeded frame mutator, same seed and template always produce same variants
+///
+/// # Example
+/// ```
+/// use cursock::*;
+///
+/// let template = [0u8; 64];
+///
+/// let mut first = Fuzzer::new(FuzzConfig::new(42));
+/// let mut second = Fuzzer::new(FuzzConfig::new(42));
+///
+/// for _ in 0..100 {
+///     assert_eq!(first.mutate(&template), second.mutate(&template))
+/// }
+///
+/// let mut headers_only = Fuzzer::new(FuzzConfig::new(42).range(0..ETH_HEADER_SIZE).resize(false));
+///
+/// let variant = headers_only.mutate(&template);
+///
+/// assert_eq!(variant.len(), template.len());
+/// assert_eq!(&variant[ETH_HEADER_SIZE..], &template[ETH_HEADER_SIZE..])
+/// ```
+pub struct Fuzzer {
+    config: FuzzConfig,
+    rng: StdRng,
+}
+
+impl FuzzConfig {
+    /// Initializes config, which mutates the whole frame with up to 8 bit flips,
+    /// up to 2 random bytes and random length changes
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            ranges: Vec::new(),
+            bit_flips: 8,
+            byte_corruptions: 2,
+            resize: true,
+            max_len: 1514,
+        }
+    }
+
+    /// limits mutations to the byte range, can be called several times
+    pub fn range(mut self, range: ops::Range<usize>) -> Self {
+        self.ranges.push(range);
+        self
+    }
+    /// maximum count of flipped bits per frame
+    pub fn bit_flips(mut self, bit_flips: usize) -> Self {
+        self.bit_flips = bit_flips;
+        self
+    }
+    /// maximum count of bytes replaced with random values per frame
+    pub fn byte_corruptions(mut self, byte_corruptions: usize) -> Self {
+        self.byte_corruptions = byte_corruptions;
+        self
+    }
+    /// allows truncating frames or extending them with random bytes
+    pub fn resize(mut self, resize: bool) -> Self {
+        self.resize = resize;
+        self
+    }
+    /// maximum length of extended frames
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    getters!(
+        pub get_seed(seed) -> u64;
+        pub get_ranges(ranges) -> [ops::Range<usize>];
+        pub get_bit_flips(bit_flips) -> usize;
+        pub get_byte_corruptions(byte_corruptions) -> usize;
+        pub get_resize(resize) -> bool;
+        pub get_max_len(max_len) -> usize;
+    );
+}
+
+impl Fuzzer {
+    pub fn new(config: FuzzConfig) -> Self {
+        let rng: StdRng = StdRng::seed_from_u64(config.seed);
+
+        Self { config, rng }
+    }
+
+    /// Generates next variant of the template frame
+    pub fn mutate(&mut self, template: &[u8]) -> Vec<u8> {
+        let mut frame: Vec<u8> = template.to_vec();
+
+        let positions: Vec<usize> = self.mutable_positions(frame.len());
+
+        if !positions.is_empty() {
+            let flips: usize = self.rng.gen_range(0..=self.config.bit_flips);
+            for _ in 0..flips {
+                let position: usize = positions[self.rng.gen_range(0..positions.len())];
+                frame[position] ^= 1 << self.rng.gen_range(0..8);
+            }
+
+            let corruptions: usize = self.rng.gen_range(0..=self.config.byte_corruptions);
+            for _ in 0..corruptions {
+                let position: usize = positions[self.rng.gen_range(0..positions.len())];
+                frame[position] = self.rng.gen();
+            }
+        }
+
+        // every fourth frame changes its length
+        if self.config.resize && !frame.is_empty() && self.rng.gen_ratio(1, 4) {
+            match self.rng.gen_bool(0.5) || frame.len() >= self.config.max_len {
+                true => {
+                    let len: usize = self.rng.gen_range(1..=frame.len());
+                    frame.truncate(len)
+                }
+                false => {
+                    let len: usize = self.rng.gen_range(frame.len() + 1..=self.config.max_len);
+                    while frame.len() < len {
+                        frame.push(self.rng.gen())
+                    }
+                }
+            }
+        }
+
+        frame
+    }
+
+    fn mutable_positions(&self, len: usize) -> Vec<usize> {
+        if self.config.ranges.is_empty() {
+            return (0..len).collect();
+        }
+
+        let mut positions: Vec<usize> = Vec::new();
+        for range in self.config.ranges.iter() {
+            positions.extend(range.start.min(len)..range.end.min(len))
+        }
+        positions.sort_unstable();
+        positions.dedup();
+
+        positions
+    }
+}
+
+impl Socket {
+    /// Sends count mutated variants of the template frame, returns how many of them were sent
+    ///
+    /// Variants rejected by the os as malformed are skipped, other errors stop fuzzing
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    ///
+    /// let mut template = [0u8; 60];
+    /// template[..MAC_LEN].copy_from_slice(&[0xff; MAC_LEN]);
+    /// template[12..14].copy_from_slice(&ARP_PROTO.to_be_bytes());
+    ///
+    /// let config = FuzzConfig::new(1).range(ETH_HEADER_SIZE..ETH_HEADER_SIZE + ARP_HEADER_SIZE);
+    ///
+    /// let sent = socket.fuzz_send(&template, config, 1000).expect("fuzz error");
+    /// ```
+    pub fn fuzz_send(
+        &self,
+        template: &[u8],
+        mutations: FuzzConfig,
+        count: usize,
+    ) -> io::Result<u64> {
+        let mut fuzzer: Fuzzer = Fuzzer::new(mutations);
+        let mut sent: u64 = 0;
+
+        for _ in 0..count {
+            let frame: Vec<u8> = fuzzer.mutate(template);
+
+            match self.send_raw_packet(&frame) {
+                Ok(_) => sent += 1,
+                Err(err) if is_malformed_frame_error(&err) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(sent)
+    }
+}
+
+fn is_malformed_frame_error(err: &io::Error) -> bool {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if err.raw_os_error() == Some(ccs::EMSGSIZE) {
+        return true;
+    }
+
+    err.kind() == io::ErrorKind::InvalidInput
+}
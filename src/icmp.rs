@@ -7,7 +7,6 @@ use crate::ip::IpPacket;
 use crate::ip::V4;
 use crate::*;
 
-#[derive(Clone)]
 pub struct Icmp {
     arp: Arp,
 }
@@ -16,7 +15,7 @@ impl Icmp {
     /// Initializes icmp structure
     ///
     /// # Examples
-    /// ```
+    /// ```no_run
     /// use cursock::*;
     ///
     /// #[cfg(target_os = "linux")]
@@ -33,7 +32,7 @@ impl Icmp {
     /// Creates icmp connection, can be used for icmp echo requests
     ///
     /// # Examples
-    /// ```
+    /// ```no_run
     /// use cursock::*;
     ///
     /// let mut icmp = Icmp::new("wlan0").expect("initialize error");
@@ -58,11 +57,11 @@ impl Icmp {
     /// Sends icmp request
     ///
     /// # Examples
-    /// ```
+    /// ```no_run
     /// use cursock::*;
     /// use std::net;
     ///
-    /// let icmp = Icmp::new("wlan0").expect("initialize error");
+    /// let mut icmp = Icmp::new("wlan0").expect("initialize error");
     ///
     /// let ip = net::IpAddr::V4(net::Ipv4Addr::new(192, 168, 1, 1));
     ///
@@ -83,17 +82,18 @@ impl Icmp {
 
         buffer[ICMP_HEADER_SIZE..(message.len() + ICMP_HEADER_SIZE)].copy_from_slice(message);
 
-        let icmp_checksum: u16 =
-            checksum(icmp_header as *const IcmpHeader as *const u8, buffer_len);
+        let checksum: u16 = icmp_checksum(&buffer[..buffer_len]);
+        buffer[2..4].copy_from_slice(&checksum.to_be_bytes());
 
-        icmp_header.check = icmp_checksum;
         match dst_ip {
             net::IpAddr::V4(dst_v4) => {
                 let adapter = self.arp.get_socket().get_adapter().clone();
                 let ip_packet = IpPacket::<V4>::new(&adapter);
-                let payload = ip_packet.bytes(&mut self.arp, dst_v4, ICMP_PROTO as u8, &buffer)?;
+                let payload = ip_packet.bytes(&self.arp, dst_v4, ICMP_PROTO as u8, &buffer)?;
+
+                self.arp.get_socket().send_raw_packet(&payload)?;
 
-                self.arp.get_socket().send_raw_packet(&payload)
+                Ok(())
             }
             net::IpAddr::V6(_dst_v6) => todo!(),
         }
@@ -102,7 +102,7 @@ impl Icmp {
     /// Reads icmp request
     ///
     /// # Examples
-    /// ```
+    /// ```no_run
     /// use cursock::*;
     /// use cursock::utils::*;
     /// use std::net::Ipv4Addr;
@@ -160,14 +160,16 @@ impl Icmp {
 
     /// destroys structure
     ///
+    /// Socket is also closed when structure is dropped, so calling destroy is optional
+    ///
     /// # Examples
-    /// ```
+    /// ```no_run
     /// use cursock::*;
     /// let icmp = Icmp::new("wlan0").expect("initialize error");
     ///
     /// icmp.destroy()
     /// ```
-    pub fn destroy(&self) {
+    pub fn destroy(self) {
         self.arp.destroy()
     }
 }
@@ -184,13 +186,13 @@ impl<'p, 'd> IcmpConnection<'p, 'd> {
     /// icmp echo request
     ///
     /// # Examples
-    /// ```
+    /// ```no_run
     /// use cursock::*;
     /// use std::net;
     ///
     /// let mut icmp = Icmp::new("wlan0").expect("initialize error");
     ///
-    /// let ip = net::Ipv4Addr::new(8, 8, 8, 8);
+    /// let ip = net::IpAddr::V4(net::Ipv4Addr::new(8, 8, 8, 8));
     ///
     /// let mut buffer = [0; 0xffff];
     /// let message = [0; 10];
@@ -225,3 +227,203 @@ impl<'p, 'd> IcmpConnection<'p, 'd> {
         Ok(result)
     }
 }
+
+/// body of an icmp message, see [`IcmpPacket`]
+///
+/// Error messages carry the original ipv4 header with at least 8 bytes of its payload
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum IcmpMessage {
+    EchoReply {
+        id: u16,
+        sq: u16,
+        data: Vec<u8>,
+    },
+    EchoRequest {
+        id: u16,
+        sq: u16,
+        data: Vec<u8>,
+    },
+    DestinationUnreachable {
+        // set when code is 4 (fragmentation needed), zero otherwise
+        next_hop_mtu: u16,
+        original: Vec<u8>,
+    },
+    TimeExceeded {
+        original: Vec<u8>,
+    },
+    Redirect {
+        gateway: net::Ipv4Addr,
+        original: Vec<u8>,
+    },
+    /// other types, rest of the header is kept as is
+    Other {
+        type_: u8,
+        rest: [u8; 4],
+        data: Vec<u8>,
+    },
+}
+
+/// icmp message with its code and checksum
+///
+/// # Example
+/// ```
+/// use std::net::Ipv4Addr;
+/// use cursock::*;
+///
+/// let request = IcmpPacket::new(IcmpMessage::EchoRequest { id: 1, sq: 1, data: Vec::new() }, 0);
+/// let bytes = request.to_bytes();
+/// assert_eq!(bytes, [0x08, 0x00, 0xf7, 0xfd, 0x00, 0x01, 0x00, 0x01]);
+///
+/// let parsed = IcmpPacket::from_bytes(&bytes).expect("parse error");
+/// assert!(*parsed.get_checksum_valid());
+/// assert_eq!(parsed.get_message(), request.get_message());
+///
+/// // time exceeded, which quotes the probe
+/// let header = Ipv4Header::new(Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2), UDP_PROTO as u8);
+/// let probe = header.to_packet(&[0x9c, 0x41, 0x82, 0x9a, 0, 8, 0, 0]).expect("length error");
+///
+/// let error = IcmpPacket::new(IcmpMessage::TimeExceeded { original: probe.clone() }, 0);
+/// let parsed = IcmpPacket::from_bytes(&error.to_bytes()).expect("parse error");
+///
+/// assert_eq!(parsed.get_type(), 11);
+/// let (quoted, payload) = Ipv4Header::parse(parsed.get_original().expect("no datagram")).expect("parse error");
+/// assert_eq!(quoted.get_dst(), &Ipv4Addr::new(10, 0, 0, 2));
+/// assert_eq!(&payload[2..4], &[0x82, 0x9a]);
+///
+/// // damaged message
+/// let mut bytes = error.to_bytes();
+/// bytes[30] ^= 1;
+/// assert!(!*IcmpPacket::from_bytes(&bytes).expect("parse error").get_checksum_valid());
+///
+/// assert!(IcmpPacket::from_bytes(&[8, 0, 0, 0, 0, 1, 0]).is_err())
+/// ```
+#[derive(Clone, Debug)]
+pub struct IcmpPacket {
+    code: u8,
+    // checksum, which was received, or zero for created packets
+    checksum: u16,
+    checksum_valid: bool,
+    message: IcmpMessage,
+}
+
+impl IcmpPacket {
+    pub fn new(message: IcmpMessage, code: u8) -> Self {
+        Self {
+            code,
+            checksum: 0,
+            checksum_valid: true,
+            message,
+        }
+    }
+
+    /// Parses icmp message, returns InvalidData error if it's shorter than 8 bytes
+    ///
+    /// Message with invalid checksum is still returned, see [`IcmpPacket::get_checksum_valid`]
+    pub fn from_bytes(buffer: &[u8]) -> io::Result<Self> {
+        if buffer.len() < ICMP_HEADER_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "icmp header is {} bytes long, but buffer has only {}",
+                    ICMP_HEADER_SIZE,
+                    buffer.len()
+                ),
+            ));
+        }
+
+        let rest: [u8; 4] = [buffer[4], buffer[5], buffer[6], buffer[7]];
+        let data: Vec<u8> = buffer[ICMP_HEADER_SIZE..].to_vec();
+
+        let message: IcmpMessage = match buffer[0] {
+            ICMP_ECHO_RESPONSE | ICMP_ECHO_REQUEST => {
+                let id: u16 = u16::from_be_bytes([rest[0], rest[1]]);
+                let sq: u16 = u16::from_be_bytes([rest[2], rest[3]]);
+
+                match buffer[0] {
+                    ICMP_ECHO_RESPONSE => IcmpMessage::EchoReply { id, sq, data },
+                    _ => IcmpMessage::EchoRequest { id, sq, data },
+                }
+            }
+            ICMP_DESTINATION_UNREACHABLE => IcmpMessage::DestinationUnreachable {
+                next_hop_mtu: u16::from_be_bytes([rest[2], rest[3]]),
+                original: data,
+            },
+            ICMP_TIME_EXCEEDED => IcmpMessage::TimeExceeded { original: data },
+            ICMP_REDIRECT => IcmpMessage::Redirect {
+                gateway: net::Ipv4Addr::from(rest),
+                original: data,
+            },
+            type_ => IcmpMessage::Other { type_, rest, data },
+        };
+
+        Ok(Self {
+            code: buffer[1],
+            checksum: u16::from_be_bytes([buffer[2], buffer[3]]),
+            // sum of the message with its checksum is zero
+            checksum_valid: icmp_checksum(buffer) == 0,
+            message,
+        })
+    }
+
+    /// Serializes message, checksum is computed again
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (rest, data): ([u8; 4], &[u8]) = match &self.message {
+            IcmpMessage::EchoReply { id, sq, data } | IcmpMessage::EchoRequest { id, sq, data } => {
+                let mut rest: [u8; 4] = [0; 4];
+                rest[..2].copy_from_slice(&id.to_be_bytes());
+                rest[2..].copy_from_slice(&sq.to_be_bytes());
+
+                (rest, data)
+            }
+            IcmpMessage::DestinationUnreachable {
+                next_hop_mtu,
+                original,
+            } => {
+                let mtu: [u8; 2] = next_hop_mtu.to_be_bytes();
+
+                ([0, 0, mtu[0], mtu[1]], original)
+            }
+            IcmpMessage::TimeExceeded { original } => ([0; 4], original),
+            IcmpMessage::Redirect { gateway, original } => (gateway.octets(), original),
+            IcmpMessage::Other { rest, data, .. } => (*rest, data),
+        };
+
+        let mut bytes: Vec<u8> = Vec::with_capacity(ICMP_HEADER_SIZE + data.len());
+        bytes.extend_from_slice(&[self.get_type(), self.code, 0, 0]);
+        bytes.extend_from_slice(&rest);
+        bytes.extend_from_slice(data);
+
+        let checksum: u16 = icmp_checksum(&bytes);
+        bytes[2..4].copy_from_slice(&checksum.to_be_bytes());
+
+        bytes
+    }
+
+    pub fn get_type(&self) -> u8 {
+        match &self.message {
+            IcmpMessage::EchoReply { .. } => ICMP_ECHO_RESPONSE,
+            IcmpMessage::EchoRequest { .. } => ICMP_ECHO_REQUEST,
+            IcmpMessage::DestinationUnreachable { .. } => ICMP_DESTINATION_UNREACHABLE,
+            IcmpMessage::TimeExceeded { .. } => ICMP_TIME_EXCEEDED,
+            IcmpMessage::Redirect { .. } => ICMP_REDIRECT,
+            IcmpMessage::Other { type_, .. } => *type_,
+        }
+    }
+
+    /// Returns datagram, which caused an error message, it starts with its ipv4 header
+    pub fn get_original(&self) -> Option<&[u8]> {
+        match &self.message {
+            IcmpMessage::DestinationUnreachable { original, .. }
+            | IcmpMessage::TimeExceeded { original }
+            | IcmpMessage::Redirect { original, .. } => Some(original),
+            _ => None,
+        }
+    }
+
+    getters!(
+        pub get_code(code) -> u8;
+        pub get_checksum(checksum) -> u16;
+        pub get_checksum_valid(checksum_valid) -> bool;
+        pub get_message(message) -> IcmpMessage;
+    );
+}
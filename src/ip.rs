@@ -4,7 +4,9 @@ use std::io;
 use std::marker;
 use std::net;
 
-use crate::checksum;
+use crate::getters;
+use crate::ipv4_checksum;
+use crate::setters;
 use crate::Adapter;
 use crate::Arp;
 use crate::EthHeader;
@@ -34,7 +36,7 @@ impl<'a> IpPacket<'a, V4> {
     /// Generates ip packet
     pub fn bytes(
         self,
-        arp: &mut Arp,
+        arp: &Arp,
         dst_ip: &net::Ipv4Addr,
         protocol: u8,
         payload: &[u8],
@@ -43,7 +45,7 @@ impl<'a> IpPacket<'a, V4> {
             io::ErrorKind::NotFound,
             format!(
                 "To send ipv4 packets you need to have ipv4 source address ({})",
-                self.adapter.to_string()
+                self.adapter
             ),
         ))?;
 
@@ -61,17 +63,17 @@ impl<'a> IpPacket<'a, V4> {
                     io::ErrorKind::NotFound,
                     format!(
                         "To send ipv4 packets you need to have ipv4 gateway ({})",
-                        self.adapter.to_string()
+                        self.adapter
                     ),
                 ))?;
                 &gateway
             }
         };
 
-        let response = arp.who_has(eth_ip)?;
+        let dst_mac = arp.resolve(eth_ip, None)?;
 
-        eth_header.source = self.adapter.get_mac().clone().into();
-        eth_header.dest = response.get_src_mac().clone().into();
+        eth_header.source = (*self.adapter.get_mac()).into();
+        eth_header.dest = dst_mac.into();
         eth_header.proto = u16::from_be(IPV4_PROTO);
 
         ip_header.verihl = (4 << 4) + 5; // 4 - ip version - 5 header len (20)
@@ -81,9 +83,10 @@ impl<'a> IpPacket<'a, V4> {
         ip_header.saddr = src_ip.octets();
         ip_header.daddr = dst_ip.octets();
 
-        let ip_checksum: u16 = checksum(ip_header as *const _ as *const _, IPV4_HEADER_SIZE);
-
-        ip_header.check = ip_checksum;
+        let ip_checksum: u16 =
+            ipv4_checksum(&buffer[ETH_HEADER_SIZE..ETH_HEADER_SIZE + IPV4_HEADER_SIZE]);
+        buffer[ETH_HEADER_SIZE + 10..ETH_HEADER_SIZE + 12]
+            .copy_from_slice(&ip_checksum.to_be_bytes());
 
         for i in 0..payload.len() {
             buffer[ETH_HEADER_SIZE + IPV4_HEADER_SIZE + i] = payload[i]
@@ -91,4 +94,274 @@ impl<'a> IpPacket<'a, V4> {
 
         Ok(buffer)
     }
-}
\ No newline at end of file
+}
+
+/// ipv4 header, options are handled separately
+///
+/// # Example
+/// ```
+/// use cursock::*;
+/// use std::net::Ipv4Addr;
+///
+/// let mut header = Ipv4Header::new(Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2), UDP_PROTO as u8);
+/// header.set_total_len((IPV4_HEADER_SIZE + 4) as u16);
+/// header.set_id(0x1234);
+///
+/// let mut packet = header.to_bytes();
+/// packet.extend_from_slice(&[1, 2, 3, 4]);
+///
+/// let (parsed, options, payload) = Ipv4Header::from_bytes(&packet).expect("parse error");
+///
+/// assert_eq!(parsed.get_src(), &Ipv4Addr::new(10, 0, 0, 1));
+/// assert_eq!(parsed.get_id(), &0x1234);
+/// assert!(options.is_empty());
+/// assert_eq!(payload, &[1, 2, 3, 4]);
+///
+/// // options are padded to 4 bytes
+/// let with_options = header.to_bytes_with_options(&[1, 1, 1, 0]).expect("options error");
+/// let (_, options, _) = Ipv4Header::from_bytes(&with_options).expect("parse error");
+/// assert_eq!(options, &[1, 1, 1, 0]);
+///
+/// packet[8] ^= 1; // corrupt ttl
+/// assert!(Ipv4Header::from_bytes(&packet).is_err());
+/// assert!(Ipv4Header::from_bytes(&packet[..IPV4_HEADER_SIZE - 1]).is_err())
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Ipv4Header {
+    dscp: u8,
+    ecn: u8,
+    // length of header, options and payload
+    total_len: u16,
+    id: u16,
+    // reserved, don't fragment and more fragments bits
+    flags: u8,
+    // in 8 byte units
+    fragment_offset: u16,
+    ttl: u8,
+    protocol: u8,
+    src: net::Ipv4Addr,
+    dst: net::Ipv4Addr,
+}
+
+impl Ipv4Header {
+    pub const DONT_FRAGMENT: u8 = 0b010;
+    pub const MORE_FRAGMENTS: u8 = 0b001;
+
+    pub fn new(src: net::Ipv4Addr, dst: net::Ipv4Addr, protocol: u8) -> Self {
+        Self {
+            dscp: 0,
+            ecn: 0,
+            total_len: IPV4_HEADER_SIZE as u16,
+            id: 0,
+            flags: 0,
+            fragment_offset: 0,
+            ttl: 64,
+            protocol,
+            src,
+            dst,
+        }
+    }
+
+    /// Parses header, checking its version, length and checksum
+    ///
+    /// Returns header, options and payload, which is trimmed to total length, so ethernet padding is dropped
+    pub fn from_bytes(buffer: &[u8]) -> io::Result<(Self, &[u8], &[u8])> {
+        if buffer.len() < IPV4_HEADER_SIZE {
+            return Err(invalid_header(format!(
+                "buffer has only {} bytes",
+                buffer.len()
+            )));
+        }
+
+        let version: u8 = buffer[0] >> 4;
+        if version != 4 {
+            return Err(invalid_header(format!("version is {}", version)));
+        }
+
+        let header_len: usize = (buffer[0] & 0x0f) as usize * 4;
+        if header_len < IPV4_HEADER_SIZE {
+            return Err(invalid_header(format!(
+                "header length is {} bytes",
+                header_len
+            )));
+        }
+        if buffer.len() < header_len {
+            return Err(invalid_header(format!(
+                "header is {} bytes long, but buffer has only {}",
+                header_len,
+                buffer.len()
+            )));
+        }
+
+        if ipv4_checksum(&buffer[..header_len]) != 0 {
+            return Err(invalid_header("checksum doesn't match"));
+        }
+
+        let total_len: u16 = u16::from_be_bytes([buffer[2], buffer[3]]);
+        if (total_len as usize) < header_len {
+            return Err(invalid_header(format!(
+                "total length {} is less than header length",
+                total_len
+            )));
+        }
+
+        let frag: u16 = u16::from_be_bytes([buffer[6], buffer[7]]);
+
+        let header: Self = Self {
+            dscp: buffer[1] >> 2,
+            ecn: buffer[1] & 0b11,
+            total_len,
+            id: u16::from_be_bytes([buffer[4], buffer[5]]),
+            flags: (frag >> 13) as u8,
+            fragment_offset: frag & 0x1fff,
+            ttl: buffer[8],
+            protocol: buffer[9],
+            src: net::Ipv4Addr::new(buffer[12], buffer[13], buffer[14], buffer[15]),
+            dst: net::Ipv4Addr::new(buffer[16], buffer[17], buffer[18], buffer[19]),
+        };
+
+        // captured packet can be truncated
+        let end: usize = buffer.len().min(total_len as usize);
+
+        Ok((
+            header,
+            &buffer[IPV4_HEADER_SIZE..header_len],
+            &buffer[header_len..end],
+        ))
+    }
+
+    /// Parses header like [`Ipv4Header::from_bytes`], skipping options, returns header with the payload
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    /// use std::net::Ipv4Addr;
+    ///
+    /// let mut header = Ipv4Header::new(Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2), ICMP_PROTO as u8);
+    /// header.set_total_len((IPV4_HEADER_SIZE + 4 + 2) as u16);
+    ///
+    /// let mut packet = header.to_bytes_with_options(&[1, 1, 1, 1]).expect("options error");
+    /// packet.extend_from_slice(&[7, 7]);
+    ///
+    /// let (parsed, payload) = Ipv4Header::parse(&packet).expect("parse error");
+    ///
+    /// assert_eq!(parsed.get_protocol(), &(ICMP_PROTO as u8));
+    /// assert_eq!(payload, &[7, 7])
+    /// ```
+    pub fn parse(buffer: &[u8]) -> io::Result<(Self, &[u8])> {
+        let (header, _, payload) = Self::from_bytes(buffer)?;
+
+        Ok((header, payload))
+    }
+
+    /// Serializes header without options and computes its checksum
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.serialize(&[])
+    }
+
+    /// Serializes header with the payload, total length is set to their length
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    /// use std::net::Ipv4Addr;
+    ///
+    /// let header = Ipv4Header::new(Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2), UDP_PROTO as u8);
+    /// let packet = header.to_packet(&[0; 8]).expect("length error");
+    ///
+    /// assert_eq!(packet.len(), IPV4_HEADER_SIZE + 8);
+    /// assert_eq!(packet[0], 0x45);
+    /// assert_eq!(&packet[2..4], &[0, 28]);
+    /// assert_eq!(ipv4_checksum(&packet[..IPV4_HEADER_SIZE]), 0)
+    /// ```
+    pub fn to_packet(&self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        let total_len: u16 = u16::try_from(IPV4_HEADER_SIZE + payload.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("ipv4 payload of {} bytes is too long", payload.len()),
+            )
+        })?;
+
+        let mut header: Self = self.clone();
+        header.total_len = total_len;
+
+        let mut packet: Vec<u8> = header.serialize(&[]);
+        packet.extend_from_slice(payload);
+
+        Ok(packet)
+    }
+
+    /// Serializes header with options, padding them with zeros to 4 bytes, and computes its checksum
+    ///
+    /// Options can't be longer than 40 bytes
+    pub fn to_bytes_with_options(&self, options: &[u8]) -> io::Result<Vec<u8>> {
+        const MAX_OPTIONS_LEN: usize = 40;
+
+        if options.len() > MAX_OPTIONS_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "ipv4 options can't be longer than {} bytes, but got {}",
+                    MAX_OPTIONS_LEN,
+                    options.len()
+                ),
+            ));
+        }
+
+        Ok(self.serialize(options))
+    }
+
+    fn serialize(&self, options: &[u8]) -> Vec<u8> {
+        let header_len: usize = IPV4_HEADER_SIZE + options.len().div_ceil(4) * 4;
+        let mut bytes: Vec<u8> = vec![0; header_len];
+
+        bytes[0] = (4 << 4) | (header_len / 4) as u8;
+        bytes[1] = (self.dscp << 2) | (self.ecn & 0b11);
+        bytes[2..4].copy_from_slice(&self.total_len.to_be_bytes());
+        bytes[4..6].copy_from_slice(&self.id.to_be_bytes());
+        let frag: u16 = ((self.flags as u16) << 13) | (self.fragment_offset & 0x1fff);
+        bytes[6..8].copy_from_slice(&frag.to_be_bytes());
+        bytes[8] = self.ttl;
+        bytes[9] = self.protocol;
+        bytes[12..16].copy_from_slice(&self.src.octets());
+        bytes[16..20].copy_from_slice(&self.dst.octets());
+        bytes[IPV4_HEADER_SIZE..IPV4_HEADER_SIZE + options.len()].copy_from_slice(options);
+
+        let header_checksum: u16 = ipv4_checksum(&bytes);
+        bytes[10..12].copy_from_slice(&header_checksum.to_be_bytes());
+
+        bytes
+    }
+
+    getters!(
+        pub get_dscp(dscp) -> u8;
+        pub get_ecn(ecn) -> u8;
+        pub get_total_len(total_len) -> u16;
+        pub get_id(id) -> u16;
+        pub get_flags(flags) -> u8;
+        pub get_fragment_offset(fragment_offset) -> u16;
+        pub get_ttl(ttl) -> u8;
+        pub get_protocol(protocol) -> u8;
+        pub get_src(src) -> net::Ipv4Addr;
+        pub get_dst(dst) -> net::Ipv4Addr;
+    );
+    setters!(
+        pub set_dscp(u8) -> dscp;
+        pub set_ecn(u8) -> ecn;
+        pub set_total_len(u16) -> total_len;
+        pub set_id(u16) -> id;
+        pub set_flags(u8) -> flags;
+        pub set_fragment_offset(u16) -> fragment_offset;
+        pub set_ttl(u8) -> ttl;
+        pub set_protocol(u8) -> protocol;
+        pub set_src(net::Ipv4Addr) -> src;
+        pub set_dst(net::Ipv4Addr) -> dst;
+    );
+}
+
+fn invalid_header<S: Into<String>>(message: S) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid ipv4 header: {}", message.into()),
+    )
+}
@@ -0,0 +1,276 @@
+use std::io;
+use std::net;
+use std::time;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::socket::{open_cloexec_socket, permission_hint, poll_socket};
+use crate::*;
+
+/// protocol of ip datagrams, which [`IpSocket`] receives
+///
+/// # Example
+/// ```
+/// use cursock::*;
+///
+/// assert_eq!(IpProtocol::from(17), IpProtocol::Udp);
+/// assert_eq!(u8::from(IpProtocol::Icmp), ICMP_PROTO as u8);
+/// assert_eq!(u8::from(IpProtocol::from(253)), 253)
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IpProtocol {
+    Icmp,
+    Tcp,
+    Udp,
+    Other(u8),
+}
+
+impl From<u8> for IpProtocol {
+    fn from(value: u8) -> Self {
+        match value as u16 {
+            ICMP_PROTO => Self::Icmp,
+            TCP_PROTO => Self::Tcp,
+            UDP_PROTO => Self::Udp,
+            _ => Self::Other(value),
+        }
+    }
+}
+
+impl From<IpProtocol> for u8 {
+    fn from(value: IpProtocol) -> Self {
+        match value {
+            IpProtocol::Icmp => ICMP_PROTO as u8,
+            IpProtocol::Tcp => TCP_PROTO as u8,
+            IpProtocol::Udp => UDP_PROTO as u8,
+            IpProtocol::Other(value) => value,
+        }
+    }
+}
+
+/// Layer 3 socket, which sends and receives ipv4 datagrams with their headers
+///
+/// Kernel routes sent datagrams and resolves next hop mac addresses,
+/// so unlike [`Socket`] no ethernet header is needed.
+/// It's a raw ipv4 socket with IP_HDRINCL, which is only implemented on linux and android.
+/// Windows raw sockets can't send tcp and rewrite source addresses,
+/// and macos raw sockets take ip_len and ip_off in host byte order and never receive tcp and udp datagrams,
+/// so there [`IpSocket::new`] returns Unsupported error and [`Socket`] has to be used
+///
+/// # Examples
+/// ```
+/// use std::net::Ipv4Addr;
+/// use std::time::Duration;
+/// use cursock::*;
+///
+/// #[cfg(target_os = "linux")]
+/// {
+///     // opening raw sockets requires root or CAP_NET_RAW
+///     if let Ok(socket) = IpSocket::new(IpProtocol::Other(253)) {
+///         let localhost = Ipv4Addr::new(127, 0, 0, 1);
+///         let header = Ipv4Header::new(localhost, localhost, 253);
+///
+///         let packet = header.to_packet(b"hello").expect("length error");
+///         assert_eq!(socket.send_ip_packet(localhost, &packet).expect("send error"), 25);
+///
+///         let mut buffer = [0; 1500];
+///         let length = socket.read_ip_packet(&mut buffer).expect("read error");
+///
+///         let (received, payload) = Ipv4Header::parse(&buffer[..length]).expect("parse error");
+///         assert_eq!(received.get_src(), &localhost);
+///         assert_eq!(payload, b"hello");
+///
+///         let err = socket.read_ip_packet_timeout(&mut buffer, Duration::from_millis(50)).unwrap_err();
+///         assert_eq!(err.kind(), std::io::ErrorKind::TimedOut)
+///     }
+/// }
+/// ```
+pub struct IpSocket {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    socket: i32,
+    protocol: IpProtocol,
+}
+
+impl IpSocket {
+    /// Opens raw ipv4 socket, which receives datagrams of the protocol
+    ///
+    /// Sent datagrams carry their own headers, kernel only fills total length,
+    /// and checksum and id if they are zero.
+    /// Returns Unsupported error on windows and macos
+    pub fn new(protocol: IpProtocol) -> io::Result<Self> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let socket: i32 =
+                open_cloexec_socket(ccs::AF_INET, ccs::SOCK_RAW, u8::from(protocol) as i32)
+                    .map_err(permission_hint)?;
+
+            let include: i32 = 1;
+            let result: i32 = unsafe {
+                ccs::setsockopt(
+                    socket,
+                    ccs::IPPROTO_IP,
+                    ccs::IP_HDRINCL,
+                    &include as *const i32 as *const std::os::raw::c_void,
+                    std::mem::size_of::<i32>() as ccs::SocklenT,
+                )
+            };
+
+            if result < 0 {
+                let err: io::Error = io::Error::last_os_error();
+                unsafe { ccs::close(socket) };
+
+                return Err(err);
+            }
+
+            logger::debug(|| format!("opened ip socket for protocol {:?}", protocol));
+
+            Ok(Self { socket, protocol })
+        }
+        #[cfg(any(target_os = "windows", target_os = "macos"))]
+        {
+            let _ = protocol;
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "raw ip sockets aren't supported on {}, use Socket instead",
+                    std::env::consts::OS
+                ),
+            ))
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            let _ = protocol;
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+    /// Sends ipv4 datagram, which starts with its header, returns number of bytes sent
+    ///
+    /// Dst is where the datagram is routed, it's usually the destination of the header
+    pub fn send_ip_packet(&self, dst: net::Ipv4Addr, packet: &[u8]) -> io::Result<usize> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let addr: ccs::sockaddr_in = ccs::sockaddr_in {
+                sin_family: ccs::AF_INET as i16,
+                sin_port: 0,
+                sin_addr: ccs::in_addr {
+                    s_addr: u32::from_ne_bytes(dst.octets()),
+                },
+                sin_zero: [0; 8],
+            };
+
+            let sent: isize = unsafe {
+                ccs::sendto(
+                    self.socket,
+                    packet.as_ptr() as *const std::os::raw::c_void,
+                    packet.len(),
+                    0,
+                    &addr as *const ccs::sockaddr_in as *const ccs::sockaddr,
+                    std::mem::size_of::<ccs::sockaddr_in>() as ccs::SocklenT,
+                )
+            };
+
+            if sent < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(sent as usize)
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            let _ = (dst, packet);
+            Err(unsupported())
+        }
+    }
+    /// Reads ipv4 datagram with its header, returns its length
+    pub fn read_ip_packet(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            self.read_ip_packet_linux(buffer, 0)
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            let _ = buffer;
+            Err(unsupported())
+        }
+    }
+    /// Reads ipv4 datagram, returns TimedOut error if nothing was received until timeout
+    pub fn read_ip_packet_timeout(
+        &self,
+        buffer: &mut [u8],
+        timeout: time::Duration,
+    ) -> io::Result<usize> {
+        let deadline: time::Instant = time::Instant::now() + timeout;
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        loop {
+            if !poll_socket(self.socket, ccs::POLLIN, deadline)? {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "no packets received until timeout",
+                ));
+            }
+
+            match self.read_ip_packet_linux(buffer, ccs::MSG_DONTWAIT) {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+                length => return length,
+            }
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            let _ = (buffer, deadline);
+            Err(unsupported())
+        }
+    }
+    getters!(
+        pub get_protocol(protocol) -> IpProtocol;
+    );
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn read_ip_packet_linux(&self, buffer: &mut [u8], flags: i32) -> io::Result<usize> {
+        let length: isize = unsafe {
+            ccs::recvfrom(
+                self.socket,
+                buffer.as_mut_ptr() as *mut std::os::raw::c_void,
+                buffer.len(),
+                flags,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+
+        if length < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(length as usize)
+    }
+}
+
+impl Drop for IpSocket {
+    fn drop(&mut self) {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        unsafe {
+            ccs::close(self.socket);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "raw ip sockets aren't supported on {}",
+            std::env::consts::OS
+        ),
+    )
+}
@@ -3,17 +3,62 @@
 //! `cursock` is a crate that designed to help with socketing.
 
 pub mod ccs;
+pub mod dhcp;
+pub mod dns;
+pub mod pcap_file;
 pub mod utils;
 
 mod adapter;
 mod arp;
+mod bpf;
+mod capabilities;
+mod checksum;
+mod cidr;
+mod eth;
+mod filter;
+mod flow;
+mod fuzz;
 mod icmp;
 mod ip;
+mod ip_socket;
+mod logger;
+mod multi;
+mod options;
+mod packets;
+mod reassembly;
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "windows"))]
+mod ring;
 mod socket;
+mod split;
+mod tcp;
+mod traceroute;
+mod udp;
 
-pub use adapter::Adapter;
-pub use arp::Arp;
-pub use icmp::Icmp;
-pub use socket::Socket;
+pub use adapter::{list_interfaces, Adapter, Duplex, LinkSettings};
+#[cfg(target_os = "windows")]
+pub use adapter::{list_pcap_devices, PcapDevice};
+pub use arp::{arp_table, arp_table_for_interface, parse_arp, Arp, ArpEntry, ArpPacket, ArpState};
+pub use bpf::{BpfInstruction, BpfProgram};
+pub use capabilities::{capabilities, Capabilities};
+pub use checksum::{icmp_checksum, ipv4_checksum, tcp_checksum, udp_checksum};
+pub use cidr::{Ipv4Cidr, Ipv4CidrHosts};
+pub use eth::{insert_vlan_tag, strip_vlan_tag, EtherType, EthernetHeader, VlanTag};
+pub use flow::{FlowKey, FlowStats, FlowSummary};
+pub use fuzz::{FuzzConfig, Fuzzer};
+pub use icmp::{Icmp, IcmpMessage, IcmpPacket};
+pub use ip::Ipv4Header;
+pub use ip_socket::{IpProtocol, IpSocket};
+pub use logger::set_logger;
+pub use multi::MultiSocket;
+pub use options::{SocketBuilder, SocketOptions, TimestampPrecision};
+pub use packets::PacketIter;
+pub use reassembly::{Ipv4Reassembler, TcpFlow, TcpReassembler};
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "windows"))]
+pub use ring::{Frame, RingOptions, RingReader};
+pub use socket::{CaptureStats, PacketInfo, PacketType, Socket, Timestamp};
+pub use split::{SocketReader, SocketWriter};
+pub use tcp::{PortState, TcpHeader};
+pub use traceroute::{Hop, TracerouteOptions, TracerouteProbe};
+pub use udp::UdpPacket;
 
 pub use utils::*;
@@ -0,0 +1,56 @@
+use std::sync::RwLock;
+
+static LOGGER: RwLock<Option<fn(&str)>> = RwLock::new(None);
+
+/// Sets function, which receives debug messages of the crate, e.g. opened sockets and sizes of packets
+///
+/// Nothing is logged until it's set, crate never writes to stdout or stderr itself,
+/// unless it's asked by [`crate::SocketBuilder::debug`]
+///
+/// # Examples
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use cursock::*;
+///
+/// static MESSAGES: AtomicUsize = AtomicUsize::new(0);
+///
+/// set_logger(|message| {
+///     MESSAGES.fetch_add(1, Ordering::Relaxed);
+///     eprintln!("cursock: {}", message)
+/// });
+///
+/// #[cfg(target_os = "linux")]
+/// {
+///     // opening raw sockets requires root or CAP_NET_RAW
+///     if let Ok(socket) = Socket::new("lo") {
+///         socket.send_raw_packet(&[0; 60]).expect("send error");
+///
+///         // open and send
+///         assert!(MESSAGES.load(Ordering::Relaxed) >= 2)
+///     }
+/// }
+/// ```
+pub fn set_logger(logger: fn(&str)) {
+    *LOGGER
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(logger)
+}
+
+/// logger, which is set by [`crate::SocketBuilder::debug`]
+pub(crate) fn stderr(message: &str) {
+    eprintln!("cursock: {}", message)
+}
+
+/// message is only formatted, if there is a logger
+pub(crate) fn debug<F>(message: F)
+where
+    F: FnOnce() -> String,
+{
+    let logger: Option<fn(&str)> = *LOGGER
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    if let Some(logger) = logger {
+        logger(&message())
+    }
+}
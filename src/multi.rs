@@ -0,0 +1,174 @@
+use std::io;
+use std::sync::atomic;
+use std::time;
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+use crate::socket::poll_sockets;
+use crate::*;
+
+/// Several sockets, which are read from one thread, e.g. for capturing on multiple interfaces
+///
+/// # Examples
+/// ```
+/// use std::io;
+/// use std::time::Duration;
+/// use cursock::*;
+///
+/// #[cfg(target_os = "linux")]
+/// {
+///     let options = SocketOptions::new().ignore_outgoing(true);
+///
+///     // opening raw sockets requires root or CAP_NET_RAW
+///     if let (Ok(first), Ok(second), Ok(writer)) = (
+///         Socket::new_with("lo", options.clone()),
+///         Socket::new_with("lo", options),
+///         Socket::new("lo"),
+///     ) {
+///         first.set_filter("ether src 02:00:00:00:00:9b").expect("filter error");
+///         second.set_filter("ether src 02:00:00:00:00:9a").expect("filter error");
+///
+///         let sockets = MultiSocket::new(vec![first, second]).expect("initialize error");
+///
+///         let mut frame = vec![0; 60];
+///         let mut buffer = [0; 1514];
+///
+///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x9a]);
+///         writer.send_raw_packet(&frame).expect("send error");
+///         let (length, index) = sockets.read_any(&mut buffer, Duration::from_secs(1)).expect("read error");
+///         assert_eq!((length, index), (60, 1));
+///
+///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x9b]);
+///         writer.send_raw_packet(&frame).expect("send error");
+///         let (_, index) = sockets.read_any(&mut buffer, Duration::from_secs(1)).expect("read error");
+///         assert_eq!(index, 0);
+///
+///         let err = sockets.read_any(&mut buffer, Duration::from_millis(100)).unwrap_err();
+///         assert_eq!(err.kind(), io::ErrorKind::TimedOut)
+///     }
+/// }
+/// ```
+pub struct MultiSocket {
+    sockets: Vec<Socket>,
+    // socket, which is checked first, so a busy interface doesn't starve the others
+    next: atomic::AtomicUsize,
+}
+
+impl MultiSocket {
+    /// Returns InvalidInput error if there are no sockets
+    pub fn new(sockets: Vec<Socket>) -> io::Result<Self> {
+        if sockets.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "at least one socket is required",
+            ));
+        }
+
+        Ok(Self {
+            sockets,
+            next: atomic::AtomicUsize::new(0),
+        })
+    }
+    /// Reads packet from any of the sockets, returns its length and the index of the socket
+    ///
+    /// Returns TimedOut error if no packets were received until timeout.
+    /// On windows sockets are checked one by one, so an idle socket can delay
+    /// reads of the others by the pcap read timeout
+    pub fn read_any(
+        &self,
+        buffer: &mut [u8],
+        timeout: time::Duration,
+    ) -> io::Result<(usize, usize)> {
+        let deadline: time::Instant = time::Instant::now() + timeout;
+        let count: usize = self.sockets.len();
+
+        loop {
+            let ready: Vec<bool> = self.wait(deadline)?;
+            let start: usize = self.next.load(atomic::Ordering::Relaxed);
+
+            for offset in 0..count {
+                let index: usize = (start + offset) % count;
+                if !ready[index] {
+                    continue;
+                }
+
+                match self.sockets[index].try_read_raw_packet(buffer) {
+                    Ok(length) => {
+                        self.next
+                            .store((index + 1) % count, atomic::Ordering::Relaxed);
+                        return Ok((length, index));
+                    }
+                    // packet can be taken by a clone of the socket
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(err) => return Err(err),
+                }
+            }
+
+            if time::Instant::now() >= deadline {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "no packets received until timeout",
+                ));
+            }
+        }
+    }
+    /// Returns sockets in the order they were given
+    pub fn get_sockets(&self) -> &[Socket] {
+        &self.sockets
+    }
+    pub fn into_sockets(self) -> Vec<Socket> {
+        self.sockets
+    }
+    /// waits until deadline, returns which sockets can have packets
+    fn wait(&self, deadline: time::Instant) -> io::Result<Vec<bool>> {
+        // packets can be left in the buffers from the previous reads
+        #[cfg(target_os = "macos")]
+        {
+            let buffered: Vec<bool> = self
+                .sockets
+                .iter()
+                .map(|socket| socket.has_read_packets())
+                .collect();
+
+            if buffered.contains(&true) {
+                return Ok(buffered);
+            }
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+        {
+            let mut fds: Vec<ccs::pollfd> = self
+                .sockets
+                .iter()
+                .map(|socket| ccs::pollfd {
+                    fd: socket.raw_socket(),
+                    events: ccs::POLLIN,
+                    revents: 0,
+                })
+                .collect();
+
+            poll_sockets(&mut fds, deadline)?;
+
+            Ok(fds.iter().map(|fd| fd.revents != 0).collect())
+        }
+        // pcap handles have no descriptors to poll, every read waits for the pcap read timeout
+        #[cfg(target_os = "windows")]
+        {
+            let _ = deadline;
+            Ok(vec![true; self.sockets.len()])
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            let _ = deadline;
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use std::io;
+
+use crate::*;
+
+/// timestamp precision requested from the capture driver
+///
+/// Only npcap lets you choose it, on linux kernel timestamps are always in nanoseconds
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimestampPrecision {
+    Micro,
+    Nano,
+}
+
+/// options which are applied while opening a socket
+///
+/// # Example
+/// ```
+/// use cursock::*;
+///
+/// let options = SocketOptions::new().tstamp_precision(TimestampPrecision::Nano);
+///
+/// assert_eq!(options.get_tstamp_precision(), &TimestampPrecision::Nano)
+/// ```
+#[derive(Clone)]
+pub struct SocketOptions {
+    tstamp_precision: TimestampPrecision,
+    recv_buffer_size: Option<usize>,
+    // ethertype of received frames, every frame is received if it's not set
+    protocol: Option<u16>,
+    promiscuous: bool,
+    // linux socket receives frames only from its interface
+    bind_interface: bool,
+    // frames sent from the host aren't received
+    ignore_outgoing: bool,
+    // frames longer than mtu are rejected before they are sent
+    enforce_mtu: bool,
+    // pcap filter expression, which is compiled when socket is opened
+    filter: Option<String>,
+    nonblocking: bool,
+}
+
+impl SocketOptions {
+    pub fn new() -> Self {
+        Self {
+            tstamp_precision: TimestampPrecision::Micro,
+            recv_buffer_size: None,
+            protocol: None,
+            // npcap adapters have always been opened in promiscuous mode
+            promiscuous: cfg!(target_os = "windows"),
+            bind_interface: true,
+            ignore_outgoing: false,
+            enforce_mtu: true,
+            filter: None,
+            nonblocking: false,
+        }
+    }
+
+    /// sets timestamp precision of captured packets (windows only)
+    pub fn tstamp_precision(mut self, precision: TimestampPrecision) -> Self {
+        self.tstamp_precision = precision;
+        self
+    }
+
+    /// sets receive buffer size of the socket, system default is used if it's not set
+    ///
+    /// npcap buffer size can only be set this way before the adapter is activated,
+    /// see [`Socket::set_recv_buffer_size`] for changing it later
+    pub fn recv_buffer_size(mut self, bytes: usize) -> Self {
+        self.recv_buffer_size = Some(bytes);
+        self
+    }
+
+    /// receives only frames with the ethertype, e.g. [`ARP_PROTO`] or [`EtherType::Arp`]
+    ///
+    /// On linux it's the protocol of the packet socket, which doesn't see outgoing frames,
+    /// elsewhere it's set as a filter,
+    /// so it's replaced by [`Socket::set_filter`] and [`Socket::attach_filter`]
+    pub fn protocol<P: Into<u16>>(mut self, protocol: P) -> Self {
+        self.protocol = Some(protocol.into());
+        self
+    }
+
+    /// receives frames addressed to other hosts too, it's enabled by default on windows,
+    /// where npcap adapters have always been opened in promiscuous mode, and disabled elsewhere
+    ///
+    /// See [`Socket::set_promiscuous`] for changing it later
+    ///
+    /// # Example
+    /// ```
+    /// use cursock::*;
+    ///
+    /// assert_eq!(*SocketOptions::new().get_promiscuous(), cfg!(target_os = "windows"));
+    /// assert!(!SocketOptions::new().promiscuous(false).get_promiscuous())
+    /// ```
+    pub fn promiscuous(mut self, promiscuous: bool) -> Self {
+        self.promiscuous = promiscuous;
+        self
+    }
+
+    /// binds linux socket to the interface, so frames from other interfaces aren't received,
+    /// it's enabled by default
+    ///
+    /// Unbound socket receives frames from every interface, but still sends them through its own one.
+    /// Npcap and bpf devices are always opened on one adapter, so it's ignored there
+    ///
+    /// # Example
+    /// ```
+    /// use cursock::*;
+    ///
+    /// assert!(SocketOptions::new().get_bind_interface());
+    /// assert!(!SocketOptions::new().bind_interface(false).get_bind_interface())
+    /// ```
+    pub fn bind_interface(mut self, bind_interface: bool) -> Self {
+        self.bind_interface = bind_interface;
+        self
+    }
+
+    /// receives only frames, which came from the network, not ones sent by this host
+    ///
+    /// Uses PACKET_IGNORE_OUTGOING on linux (kernel 4.20 and newer), pcap_setdirection on windows
+    /// and BIOCSSEESENT on macos
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let count = |socket: &Socket| {
+    ///         let mut buffer = [0; 1514];
+    ///         let mut count = 0;
+    ///
+    ///         while socket.read_raw_packet_timeout(&mut buffer, Duration::from_millis(200)).is_ok() {
+    ///             count += (buffer[11] == 0x93) as usize;
+    ///         }
+    ///
+    ///         count
+    ///     };
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(writer), Ok(all), Ok(incoming)) = (
+    ///         Socket::new("lo"),
+    ///         Socket::new("lo"),
+    ///         Socket::new_with("lo", SocketOptions::new().ignore_outgoing(true)),
+    ///     ) {
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x93]);
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///
+    ///         // loopback frame is seen when it's sent and when it comes back
+    ///         assert_eq!(count(&all), 2);
+    ///         assert_eq!(count(&incoming), 1)
+    ///     }
+    /// }
+    /// ```
+    pub fn ignore_outgoing(mut self, ignore_outgoing: bool) -> Self {
+        self.ignore_outgoing = ignore_outgoing;
+        self
+    }
+
+    /// rejects frames, which are longer than the interface mtu with the ethernet header,
+    /// with InvalidInput error instead of passing them to the system, it's enabled by default
+    ///
+    /// Mtu is read when the socket is opened, frames aren't checked if it can't be read.
+    /// It can be disabled for testing jumbo frames, which the driver may still drop
+    ///
+    /// # Example
+    /// ```
+    /// use std::io;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let Ok(socket) = Socket::new("lo") {
+    ///         let mtu = socket.get_mtu().expect("mtu error") as usize;
+    ///
+    ///         let frame = vec![0; mtu + ETH_HEADER_SIZE + 1];
+    ///         let err = socket.send_raw_packet(&frame).unwrap_err();
+    ///         assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    ///
+    ///         let frame = vec![0; mtu + ETH_HEADER_SIZE];
+    ///         assert_eq!(socket.send_raw_packet(&frame).expect("send error"), frame.len())
+    ///     }
+    /// }
+    /// ```
+    pub fn enforce_mtu(mut self, enforce_mtu: bool) -> Self {
+        self.enforce_mtu = enforce_mtu;
+        self
+    }
+
+    /// sets pcap filter expression like "arp or udp port 53", see [`Socket::set_filter`]
+    ///
+    /// On windows and macos it's joined with [`SocketOptions::protocol`] filter,
+    /// so both of them are applied. Opening fails if the expression can't be compiled
+    ///
+    /// # Example
+    /// ```
+    /// use std::io;
+    /// use std::time::Duration;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let options = SocketOptions::new()
+    ///         .filter("ether src 02:00:00:00:00:92")
+    ///         .ignore_outgoing(true)
+    ///         .nonblocking(true);
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new_with("lo", options), Socket::new("lo")) {
+    ///         let mut buffer = [0; 1514];
+    ///         let err = reader.read_raw_packet(&mut buffer).unwrap_err();
+    ///         assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x91]);
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x92]);
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///
+    ///         reader.read_raw_packet_timeout(&mut buffer, Duration::from_secs(1)).expect("read error");
+    ///         assert_eq!(buffer[11], 0x92);
+    ///
+    ///         assert!(Socket::new_with("lo", SocketOptions::new().filter("ether src")).is_err())
+    ///     }
+    /// }
+    /// ```
+    pub fn filter(mut self, filter: &str) -> Self {
+        self.filter = Some(filter.to_string());
+        self
+    }
+
+    /// opens socket in nonblocking mode, see [`Socket::set_nonblocking`]
+    pub fn nonblocking(mut self, nonblocking: bool) -> Self {
+        self.nonblocking = nonblocking;
+        self
+    }
+
+    getters!(
+        pub get_tstamp_precision(tstamp_precision) -> TimestampPrecision;
+        pub get_recv_buffer_size(recv_buffer_size) -> Option<usize>;
+        pub get_protocol(protocol) -> Option<u16>;
+        pub get_promiscuous(promiscuous) -> bool;
+        pub get_bind_interface(bind_interface) -> bool;
+        pub get_ignore_outgoing(ignore_outgoing) -> bool;
+        pub get_enforce_mtu(enforce_mtu) -> bool;
+        pub get_filter(filter) -> Option<String>;
+        pub get_nonblocking(nonblocking) -> bool;
+    );
+}
+
+impl Default for SocketOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// builder of [`Socket`], which collects the interface name with [`SocketOptions`]
+///
+/// Interface is the name on unix and the index on windows, like in [`Socket::new`]
+///
+/// # Example
+/// ```
+/// use std::io;
+/// use cursock::*;
+///
+/// let result = SocketBuilder::new().promiscuous(true).build();
+/// assert!(matches!(result, Err(err) if err.kind() == io::ErrorKind::InvalidInput));
+///
+/// #[cfg(target_os = "linux")]
+/// {
+///     // opening raw sockets requires root or CAP_NET_RAW
+///     if let Ok(socket) = SocketBuilder::new()
+///         .interface("lo")
+///         .protocol(EtherType::Arp)
+///         .buffer_size(1 << 20)
+///         .filter("arp")
+///         .build()
+///     {
+///         assert!(!socket.is_promiscuous())
+///     }
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct SocketBuilder {
+    interface: Option<String>,
+    options: SocketOptions,
+    debug: bool,
+}
+
+impl SocketBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// sets interface, which is opened, it's required
+    pub fn interface(mut self, interface: &str) -> Self {
+        self.interface = Some(interface.to_string());
+        self
+    }
+
+    /// replaces all options, which were set before, see [`Socket::new_with`]
+    pub fn options(mut self, options: SocketOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// see [`SocketOptions::promiscuous`]
+    pub fn promiscuous(mut self, promiscuous: bool) -> Self {
+        self.options = self.options.promiscuous(promiscuous);
+        self
+    }
+
+    /// see [`SocketOptions::protocol`]
+    pub fn protocol<P: Into<u16>>(mut self, protocol: P) -> Self {
+        self.options = self.options.protocol(protocol);
+        self
+    }
+
+    /// see [`SocketOptions::recv_buffer_size`]
+    pub fn buffer_size(mut self, bytes: usize) -> Self {
+        self.options = self.options.recv_buffer_size(bytes);
+        self
+    }
+
+    /// see [`SocketOptions::filter`]
+    pub fn filter(mut self, filter: &str) -> Self {
+        self.options = self.options.filter(filter);
+        self
+    }
+
+    /// see [`SocketOptions::nonblocking`]
+    pub fn nonblocking(mut self, nonblocking: bool) -> Self {
+        self.options = self.options.nonblocking(nonblocking);
+        self
+    }
+
+    /// prints debug messages of the crate to stderr, when the socket is built
+    ///
+    /// It sets the logger of the whole crate like [`set_logger`], so it replaces one, which was set before.
+    /// Disabling it doesn't remove the logger
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// opens the socket, returns InvalidInput error if the interface isn't set
+    pub fn build(self) -> io::Result<Socket> {
+        let interface = self
+            .interface
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "interface isn't set"))?;
+
+        if self.debug {
+            set_logger(logger::stderr);
+        }
+
+        Socket::new_with(&interface, self.options)
+    }
+
+    getters!(
+        pub get_interface(interface) -> Option<String>;
+        pub get_options(options) -> SocketOptions;
+        pub get_debug(debug) -> bool;
+    );
+}
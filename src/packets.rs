@@ -0,0 +1,48 @@
+use std::io;
+
+use crate::*;
+
+/// Iterator over received frames, see [`Socket::packets`]
+pub struct PacketIter<'a> {
+    socket: &'a Socket,
+    mtu: usize,
+}
+
+impl Socket {
+    /// Returns endless iterator, which reads frames of at most mtu bytes
+    ///
+    /// Every frame is read into its own buffer with [`Socket::read_raw_packet_vec`].
+    /// Read errors are yielded as they are, so it's up to the caller whether to stop on them
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new("lo"), Socket::new("lo")) {
+    ///         reader.attach_filter(&BpfProgram::compile("ether src 02:00:00:00:00:97").unwrap()).unwrap();
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x97]);
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///
+    ///         for packet in reader.packets(1514).take(1) {
+    ///             assert_eq!(packet.expect("read error"), frame)
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn packets(&self, mtu: usize) -> PacketIter<'_> {
+        PacketIter { socket: self, mtu }
+    }
+}
+
+impl Iterator for PacketIter<'_> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.socket.read_raw_packet_vec(self.mtu))
+    }
+}
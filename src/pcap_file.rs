@@ -0,0 +1,839 @@
+//! Reading and writing capture files, which can be opened by Wireshark and tcpdump
+
+use std::fs;
+use std::io;
+use std::path;
+use std::thread;
+use std::time;
+
+use crate::*;
+
+/// magic of classic pcap with microsecond timestamps
+const PCAP_MAGIC: u32 = 0xa1b2c3d4;
+/// magic of classic pcap with nanosecond timestamps
+const PCAP_NSEC_MAGIC: u32 = 0xa1b23c4d;
+/// records longer than this and snaplen are treated as corruption, not allocated
+const MAX_RECORD_LEN: u32 = 0x40000;
+const PCAP_VERSION: (u16, u16) = (2, 4);
+const LINKTYPE_ETHERNET: u32 = 1;
+
+// pcapng block types
+const SHB_TYPE: u32 = 0x0a0d0d0a;
+const IDB_TYPE: u32 = 1;
+const EPB_TYPE: u32 = 6;
+const BYTE_ORDER_MAGIC: u32 = 0x1a2b3c4d;
+const PCAPNG_VERSION: (u16, u16) = (1, 0);
+// pcapng option codes
+const OPT_ENDOFOPT: u16 = 0;
+const IF_TSRESOL: u16 = 9;
+const IF_TSOFFSET: u16 = 14;
+/// blocks longer than this are treated as corruption, not allocated
+const MAX_BLOCK_LEN: u32 = 0x1000000;
+
+/// Writes frames in classic pcap format, file is little endian with microsecond timestamps
+///
+/// # Examples
+/// ```
+/// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+/// use cursock::pcap_file::PcapWriter;
+///
+/// let mut writer = PcapWriter::with_snaplen(Vec::new(), 4).expect("header error");
+///
+/// let ts = UNIX_EPOCH + Duration::new(1, 500_000_123);
+/// writer.write_packet(&[1, 2, 3, 4, 5, 6], ts).expect("write error");
+/// // times before 1970 are written as zero
+/// writer.write_packet(&[7], UNIX_EPOCH - Duration::from_secs(1)).expect("write error");
+///
+/// let file = writer.into_inner();
+/// assert_eq!(&file[..4], &[0xd4, 0xc3, 0xb2, 0xa1]);
+/// assert_eq!(&file[16..20], &4u32.to_le_bytes());
+///
+/// // secs, usecs, captured length, original length and captured bytes
+/// assert_eq!(&file[24..28], &1u32.to_le_bytes());
+/// assert_eq!(&file[28..32], &500_000u32.to_le_bytes());
+/// assert_eq!(&file[32..36], &4u32.to_le_bytes());
+/// assert_eq!(&file[36..40], &6u32.to_le_bytes());
+/// assert_eq!(&file[40..44], &[1, 2, 3, 4]);
+///
+/// assert_eq!(&file[44..52], &[0; 8]);
+/// assert_eq!(file.len(), 44 + 16 + 1)
+/// ```
+pub struct PcapWriter<W: io::Write> {
+    writer: W,
+    snaplen: u32,
+}
+
+impl<W: io::Write> PcapWriter<W> {
+    /// Writes global header with snaplen of 65535 bytes
+    pub fn new(writer: W) -> io::Result<Self> {
+        Self::with_snaplen(writer, u16::MAX as u32)
+    }
+
+    /// Writes global header, longer frames are truncated to snaplen
+    pub fn with_snaplen(mut writer: W, snaplen: u32) -> io::Result<Self> {
+        let mut header: [u8; 24] = [0; 24];
+        header[0..4].copy_from_slice(&PCAP_MAGIC.to_le_bytes());
+        header[4..6].copy_from_slice(&PCAP_VERSION.0.to_le_bytes());
+        header[6..8].copy_from_slice(&PCAP_VERSION.1.to_le_bytes());
+        // thiszone and sigfigs are always zero
+        header[16..20].copy_from_slice(&snaplen.to_le_bytes());
+        header[20..24].copy_from_slice(&LINKTYPE_ETHERNET.to_le_bytes());
+
+        writer.write_all(&header)?;
+
+        Ok(Self { writer, snaplen })
+    }
+
+    /// Writes frame record, frame is truncated to snaplen
+    pub fn write_packet(&mut self, data: &[u8], ts: time::SystemTime) -> io::Result<()> {
+        let ts: time::Duration = ts
+            .duration_since(time::UNIX_EPOCH)
+            .unwrap_or(time::Duration::ZERO);
+        let secs: u32 = ts.as_secs().min(u32::MAX as u64) as u32;
+
+        let orig_len: u32 = data.len().min(u32::MAX as usize) as u32;
+        let incl_len: u32 = orig_len.min(self.snaplen);
+
+        let mut header: [u8; 16] = [0; 16];
+        header[0..4].copy_from_slice(&secs.to_le_bytes());
+        header[4..8].copy_from_slice(&ts.subsec_micros().to_le_bytes());
+        header[8..12].copy_from_slice(&incl_len.to_le_bytes());
+        header[12..16].copy_from_slice(&orig_len.to_le_bytes());
+
+        self.writer.write_all(&header)?;
+        self.writer.write_all(&data[..incl_len as usize])
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns underlying writer, it isn't flushed
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    getters!(
+        pub get_snaplen(snaplen) -> u32;
+    );
+}
+
+/// Writes frames in pcapng format, file is little endian with a single ethernet interface
+/// and nanosecond timestamps
+///
+/// # Examples
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use cursock::pcap_file::{PcapReader, PcapngWriter};
+///
+/// let mut writer = PcapngWriter::new(Vec::new()).expect("header error");
+///
+/// let ts = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+/// writer.write_packet(&[1, 2, 3, 4, 5], ts).expect("write error");
+///
+/// let file = writer.into_inner();
+/// // section header block
+/// assert_eq!(&file[..4], &[0x0a, 0x0d, 0x0d, 0x0a]);
+///
+/// let mut reader = PcapReader::new(&file[..]).expect("header error");
+/// assert_eq!(reader.get_linktype(), &1);
+///
+/// let (read_ts, data) = reader.next_packet().expect("read error").expect("no packet");
+/// assert_eq!(read_ts, ts);
+/// assert_eq!(data, &[1, 2, 3, 4, 5]);
+/// assert!(reader.next_packet().expect("read error").is_none())
+/// ```
+pub struct PcapngWriter<W: io::Write> {
+    writer: W,
+    snaplen: u32,
+}
+
+impl<W: io::Write> PcapngWriter<W> {
+    /// Writes section header and interface description with snaplen of 65535 bytes
+    pub fn new(writer: W) -> io::Result<Self> {
+        Self::with_snaplen(writer, u16::MAX as u32)
+    }
+
+    /// Writes section header and interface description, longer frames are truncated to snaplen
+    pub fn with_snaplen(mut writer: W, snaplen: u32) -> io::Result<Self> {
+        let mut section: Vec<u8> = Vec::with_capacity(16);
+        section.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+        section.extend_from_slice(&PCAPNG_VERSION.0.to_le_bytes());
+        section.extend_from_slice(&PCAPNG_VERSION.1.to_le_bytes());
+        // section length isn't known, while it's written
+        section.extend_from_slice(&(-1i64).to_le_bytes());
+        write_block(&mut writer, SHB_TYPE, &section)?;
+
+        let mut interface: Vec<u8> = Vec::with_capacity(20);
+        interface.extend_from_slice(&(LINKTYPE_ETHERNET as u16).to_le_bytes());
+        interface.extend_from_slice(&[0, 0]);
+        interface.extend_from_slice(&snaplen.to_le_bytes());
+        // if_tsresol of 10^-9, padded to 4 bytes, and end of options
+        interface.extend_from_slice(&IF_TSRESOL.to_le_bytes());
+        interface.extend_from_slice(&1u16.to_le_bytes());
+        interface.extend_from_slice(&[9, 0, 0, 0]);
+        interface.extend_from_slice(&[0; 4]);
+        write_block(&mut writer, IDB_TYPE, &interface)?;
+
+        Ok(Self { writer, snaplen })
+    }
+
+    /// Writes enhanced packet block, frame is truncated to snaplen
+    pub fn write_packet(&mut self, data: &[u8], ts: time::SystemTime) -> io::Result<()> {
+        let nanos: u64 = ts
+            .duration_since(time::UNIX_EPOCH)
+            .unwrap_or(time::Duration::ZERO)
+            .as_nanos()
+            .min(u64::MAX as u128) as u64;
+
+        let orig_len: u32 = data.len().min(u32::MAX as usize) as u32;
+        let cap_len: u32 = orig_len.min(self.snaplen);
+
+        let mut packet: Vec<u8> = Vec::with_capacity(20 + cap_len as usize + 3);
+        // interface 0
+        packet.extend_from_slice(&[0; 4]);
+        packet.extend_from_slice(&((nanos >> 32) as u32).to_le_bytes());
+        packet.extend_from_slice(&(nanos as u32).to_le_bytes());
+        packet.extend_from_slice(&cap_len.to_le_bytes());
+        packet.extend_from_slice(&orig_len.to_le_bytes());
+        packet.extend_from_slice(&data[..cap_len as usize]);
+
+        write_block(&mut self.writer, EPB_TYPE, &packet)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns underlying writer, it isn't flushed
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    getters!(
+        pub get_snaplen(snaplen) -> u32;
+    );
+}
+
+/// Reads frames from pcap or pcapng file, format is detected from its first 4 bytes
+///
+/// Pcap files can have either byte order and timestamp precision.
+/// Of pcapng blocks only section headers, interface descriptions and enhanced packets are read,
+/// other blocks are skipped. Records are borrowed from the reader until the next one is read
+///
+/// # Examples
+/// ```
+/// use std::io::ErrorKind;
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use cursock::pcap_file::{PcapReader, PcapWriter};
+///
+/// let mut writer = PcapWriter::new(Vec::new()).expect("header error");
+/// writer.write_packet(&[1, 2, 3], UNIX_EPOCH + Duration::from_micros(1_000_005)).expect("write error");
+/// writer.write_packet(&[4, 5], UNIX_EPOCH).expect("write error");
+/// let file = writer.into_inner();
+///
+/// let mut reader = PcapReader::new(&file[..]).expect("header error");
+/// assert_eq!(reader.get_snaplen(), &65535);
+///
+/// let (ts, data) = reader.next_packet().expect("read error").expect("no packet");
+/// assert_eq!(ts, UNIX_EPOCH + Duration::from_micros(1_000_005));
+/// assert_eq!(data, &[1, 2, 3]);
+///
+/// assert_eq!(reader.next_packet().expect("read error").map(|(_, data)| data.to_vec()), Some(vec![4, 5]));
+/// assert!(reader.next_packet().expect("read error").is_none());
+///
+/// // big endian file with nanosecond timestamps
+/// let mut file = vec![0xa1, 0xb2, 0x3c, 0x4d, 0, 2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 0, 0, 0, 1];
+/// file.extend_from_slice(&[0, 0, 0, 2, 0, 0, 0, 7, 0, 0, 0, 1, 0, 0, 0, 1, 9]);
+///
+/// let mut reader = PcapReader::new(&file[..]).expect("header error");
+/// let (ts, data) = reader.next_packet().expect("read error").expect("no packet");
+/// assert_eq!(ts, UNIX_EPOCH + Duration::new(2, 7));
+/// assert_eq!(data, &[9]);
+///
+/// // record is cut in the middle
+/// let mut reader = PcapReader::new(&file[..file.len() - 1]).expect("header error");
+/// assert_eq!(reader.next_packet().err().map(|err| err.kind()), Some(ErrorKind::InvalidData))
+/// ```
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use cursock::pcap_file::PcapReader;
+///
+/// // big endian pcapng blocks
+/// fn block(block_type: u32, body: &[u8]) -> Vec<u8> {
+///     let length = (12 + body.len() as u32).to_be_bytes();
+///
+///     let mut block = block_type.to_be_bytes().to_vec();
+///     block.extend_from_slice(&length);
+///     block.extend_from_slice(body);
+///     block.extend_from_slice(&length);
+///     block
+/// }
+///
+/// fn packet(interface: u32, ts: u64, data: &[u8; 4]) -> Vec<u8> {
+///     let mut body = interface.to_be_bytes().to_vec();
+///     body.extend_from_slice(&((ts >> 32) as u32).to_be_bytes());
+///     body.extend_from_slice(&(ts as u32).to_be_bytes());
+///     body.extend_from_slice(&[0, 0, 0, 4, 0, 0, 0, 4]);
+///     body.extend_from_slice(data);
+///     block(6, &body)
+/// }
+///
+/// let mut file = block(0x0a0d0d0a, &[0x1a, 0x2b, 0x3c, 0x4d, 0, 1, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+/// // ethernet, snaplen 96, if_tsresol of 10^-3
+/// file.extend(block(1, &[0, 1, 0, 0, 0, 0, 0, 96, 0, 9, 0, 1, 3, 0, 0, 0, 0, 0, 0, 0]));
+/// // unknown block
+/// file.extend(block(0x0bad, &[1, 2, 3, 4]));
+/// file.extend(packet(0, 1500, &[1, 2, 3, 4]));
+/// // there is no interface 1
+/// file.extend(packet(1, 0, &[5, 6, 7, 8]));
+///
+/// let mut reader = PcapReader::new(&file[..]).expect("header error");
+/// assert_eq!(reader.get_snaplen(), &96);
+///
+/// let (ts, data) = reader.next_packet().expect("read error").expect("no packet");
+/// assert_eq!(ts, UNIX_EPOCH + Duration::from_millis(1500));
+/// assert_eq!(data, &[1, 2, 3, 4]);
+///
+/// assert_eq!(reader.next_packet().err().map(|err| err.kind()), Some(ErrorKind::InvalidData));
+///
+/// // block trailer doesn't match its length
+/// let mut reader = PcapReader::new(&file[..file.len() - 1]).expect("header error");
+/// reader.next_packet().expect("read error");
+/// assert_eq!(reader.next_packet().err().map(|err| err.kind()), Some(ErrorKind::InvalidData))
+/// ```
+pub struct PcapReader<R: io::Read> {
+    reader: R,
+    big_endian: bool,
+    format: Format,
+    // of the first interface in pcapng files
+    snaplen: u32,
+    linktype: u32,
+    buffer: Vec<u8>,
+}
+
+enum Format {
+    Pcap { nanosecond: bool },
+    // interfaces, which were described in the current section
+    Pcapng { interfaces: Vec<Interface> },
+}
+
+struct Interface {
+    linktype: u16,
+    snaplen: u32,
+    // timestamp units per second
+    resolution: u128,
+    // seconds, which are added to the timestamps
+    offset: i64,
+}
+
+impl<R: io::Read> PcapReader<R> {
+    /// Reads global header, for pcapng files blocks are read until the first interface description
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut magic: [u8; 4] = [0; 4];
+        if !read_record(&mut reader, &mut magic)? {
+            return Err(invalid_file("file is empty"));
+        }
+
+        if magic == SHB_TYPE.to_le_bytes() {
+            return Self::new_pcapng(reader);
+        }
+
+        let mut header: [u8; 24] = [0; 24];
+        header[..4].copy_from_slice(&magic);
+        read_rest(&mut reader, &mut header[4..])?;
+
+        let (big_endian, nanosecond) = match (u32::from_le_bytes(magic), u32::from_be_bytes(magic))
+        {
+            (PCAP_MAGIC, _) => (false, false),
+            (PCAP_NSEC_MAGIC, _) => (false, true),
+            (_, PCAP_MAGIC) => (true, false),
+            (_, PCAP_NSEC_MAGIC) => (true, true),
+            _ => return Err(invalid_file(format!("unknown magic {:02x?}", magic))),
+        };
+
+        Ok(Self {
+            reader,
+            big_endian,
+            format: Format::Pcap { nanosecond },
+            snaplen: u32_at(&header, 16, big_endian),
+            linktype: u32_at(&header, 20, big_endian),
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Reads next record, returns None at the end of the file
+    ///
+    /// Returns InvalidData error, if the file is truncated or the record is corrupted
+    pub fn next_packet(&mut self) -> io::Result<Option<(time::SystemTime, &[u8])>> {
+        match self.format {
+            Format::Pcap { nanosecond } => self.next_pcap_packet(nanosecond),
+            Format::Pcapng { .. } => self.next_pcapng_packet(),
+        }
+    }
+
+    getters!(
+        pub get_snaplen(snaplen) -> u32;
+        pub get_linktype(linktype) -> u32;
+    );
+
+    fn next_pcap_packet(
+        &mut self,
+        nanosecond: bool,
+    ) -> io::Result<Option<(time::SystemTime, &[u8])>> {
+        let mut header: [u8; 16] = [0; 16];
+        if !read_record(&mut self.reader, &mut header)? {
+            return Ok(None);
+        }
+
+        let secs: u32 = u32_at(&header, 0, self.big_endian);
+        let fraction: u32 = u32_at(&header, 4, self.big_endian);
+        let incl_len: u32 = u32_at(&header, 8, self.big_endian);
+
+        if incl_len > self.snaplen.max(MAX_RECORD_LEN) {
+            return Err(invalid_file(format!(
+                "record of {} bytes is longer than snaplen {}",
+                incl_len, self.snaplen
+            )));
+        }
+
+        let nanos: u32 = match nanosecond {
+            true => fraction,
+            false => fraction.saturating_mul(1000),
+        };
+        let ts: time::SystemTime = time::UNIX_EPOCH
+            + time::Duration::from_secs(secs as u64)
+            + time::Duration::from_nanos(nanos as u64);
+
+        self.buffer.resize(incl_len as usize, 0);
+        read_rest(&mut self.reader, &mut self.buffer)?;
+
+        Ok(Some((ts, &self.buffer)))
+    }
+
+    /// section header type was already read
+    fn new_pcapng(reader: R) -> io::Result<Self> {
+        let mut this: Self = Self {
+            reader,
+            big_endian: false,
+            format: Format::Pcapng {
+                interfaces: Vec::new(),
+            },
+            snaplen: 0,
+            linktype: 0,
+            buffer: Vec::new(),
+        };
+
+        this.read_block_body(SHB_TYPE)?;
+        this.start_section()?;
+
+        while let Some(block_type) = this.read_block()? {
+            match block_type {
+                SHB_TYPE => this.start_section()?,
+                IDB_TYPE => {
+                    this.add_interface()?;
+
+                    if let Format::Pcapng { interfaces } = &this.format {
+                        this.linktype = interfaces[0].linktype as u32;
+                        this.snaplen = interfaces[0].snaplen;
+                    }
+                    break;
+                }
+                EPB_TYPE => return Err(invalid_file("packet block comes before interfaces")),
+                _ => {}
+            }
+        }
+
+        Ok(this)
+    }
+
+    fn next_pcapng_packet(&mut self) -> io::Result<Option<(time::SystemTime, &[u8])>> {
+        loop {
+            match self.read_block()? {
+                None => return Ok(None),
+                Some(SHB_TYPE) => self.start_section()?,
+                Some(IDB_TYPE) => self.add_interface()?,
+                Some(EPB_TYPE) => break,
+                // unknown blocks are skipped
+                Some(_) => {}
+            }
+        }
+
+        let body: &[u8] = &self.buffer;
+        if body.len() < 20 {
+            return Err(invalid_file("packet block is too short"));
+        }
+
+        let interface_id: u32 = u32_at(body, 0, self.big_endian);
+        let units: u64 = ((u32_at(body, 4, self.big_endian) as u64) << 32)
+            | u32_at(body, 8, self.big_endian) as u64;
+        let cap_len: usize = u32_at(body, 12, self.big_endian) as usize;
+
+        let interface: &Interface = match &self.format {
+            Format::Pcapng { interfaces } => interfaces.get(interface_id as usize),
+            Format::Pcap { .. } => None,
+        }
+        .ok_or(invalid_file(format!(
+            "packet block refers to unknown interface {}",
+            interface_id
+        )))?;
+
+        if cap_len > body.len() - 20 {
+            return Err(invalid_file(format!(
+                "packet of {} bytes doesn't fit into its block",
+                cap_len
+            )));
+        }
+        // some writers don't truncate to snaplen, so it's not checked
+
+        let nanos: u128 = units as u128 * 1_000_000_000 / interface.resolution;
+        let secs: i128 = (nanos / 1_000_000_000) as i128 + interface.offset as i128;
+
+        let ts: time::SystemTime = match u64::try_from(secs) {
+            Ok(secs) => time::UNIX_EPOCH
+                .checked_add(time::Duration::new(secs, (nanos % 1_000_000_000) as u32))
+                .ok_or(invalid_file("timestamp is out of range"))?,
+            // times before 1970 are clamped
+            Err(_) => time::UNIX_EPOCH,
+        };
+
+        Ok(Some((ts, &self.buffer[20..20 + cap_len])))
+    }
+
+    /// reads block into the buffer, returns its type or None at the end of the file
+    fn read_block(&mut self) -> io::Result<Option<u32>> {
+        let mut block_type: [u8; 4] = [0; 4];
+        if !read_record(&mut self.reader, &mut block_type)? {
+            return Ok(None);
+        }
+
+        // section header type is the same in both byte orders
+        let block_type: u32 = u32_at(&block_type, 0, self.big_endian);
+        self.read_block_body(block_type)?;
+
+        Ok(Some(block_type))
+    }
+
+    /// reads length, body and trailer of the block, section header sets byte order
+    fn read_block_body(&mut self, block_type: u32) -> io::Result<()> {
+        let mut length: [u8; 4] = [0; 4];
+        read_rest(&mut self.reader, &mut length)?;
+
+        let mut magic: [u8; 4] = [0; 4];
+        if block_type == SHB_TYPE {
+            read_rest(&mut self.reader, &mut magic)?;
+
+            self.big_endian = match u32::from_le_bytes(magic) {
+                BYTE_ORDER_MAGIC => false,
+                magic if magic.swap_bytes() == BYTE_ORDER_MAGIC => true,
+                _ => {
+                    return Err(invalid_file(format!(
+                        "unknown byte order magic {:02x?}",
+                        magic
+                    )))
+                }
+            };
+        }
+
+        let total_len: u32 = u32_at(&length, 0, self.big_endian);
+        let min_len: u32 = match block_type == SHB_TYPE {
+            true => 28,
+            false => 12,
+        };
+
+        if total_len < min_len || !total_len.is_multiple_of(4) || total_len > MAX_BLOCK_LEN {
+            return Err(invalid_file(format!(
+                "block {:#x} has invalid length {}",
+                block_type, total_len
+            )));
+        }
+
+        self.buffer.resize(total_len as usize - 8, 0);
+        if block_type == SHB_TYPE {
+            self.buffer[..4].copy_from_slice(&magic);
+            read_rest(&mut self.reader, &mut self.buffer[4..])?;
+        } else {
+            read_rest(&mut self.reader, &mut self.buffer)?;
+        }
+
+        let trailer_offset: usize = self.buffer.len() - 4;
+        if u32_at(&self.buffer, trailer_offset, self.big_endian) != total_len {
+            return Err(invalid_file(format!(
+                "block {:#x} length doesn't match its trailer",
+                block_type
+            )));
+        }
+        self.buffer.truncate(trailer_offset);
+
+        Ok(())
+    }
+
+    fn start_section(&mut self) -> io::Result<()> {
+        let major: u16 = u16_at(&self.buffer, 4, self.big_endian);
+        if major != PCAPNG_VERSION.0 {
+            return Err(invalid_file(format!(
+                "pcapng version {} isn't supported",
+                major
+            )));
+        }
+
+        // interface ids start from zero in every section
+        self.format = Format::Pcapng {
+            interfaces: Vec::new(),
+        };
+
+        Ok(())
+    }
+
+    fn add_interface(&mut self) -> io::Result<()> {
+        let body: &[u8] = &self.buffer;
+        if body.len() < 8 {
+            return Err(invalid_file("interface block is too short"));
+        }
+
+        let mut interface: Interface = Interface {
+            linktype: u16_at(body, 0, self.big_endian),
+            snaplen: u32_at(body, 4, self.big_endian),
+            resolution: 1_000_000,
+            offset: 0,
+        };
+
+        let mut options: &[u8] = &body[8..];
+        while options.len() >= 4 {
+            let code: u16 = u16_at(options, 0, self.big_endian);
+            let length: usize = u16_at(options, 2, self.big_endian) as usize;
+
+            let value: &[u8] = match options.get(4..4 + length) {
+                Some(value) => value,
+                None => return Err(invalid_file("interface option doesn't fit into its block")),
+            };
+
+            match (code, value) {
+                (OPT_ENDOFOPT, _) => break,
+                (IF_TSRESOL, [resolution]) => {
+                    // high bit tells, whether it's a negative power of 2 or of 10
+                    interface.resolution = match resolution & 0x80 {
+                        0 if *resolution <= 38 => 10u128.pow(*resolution as u32),
+                        0 => return Err(invalid_file("timestamp resolution is too high")),
+                        _ => 1u128 << (resolution & 0x7f),
+                    }
+                }
+                (IF_TSOFFSET, [..]) if value.len() == 8 => {
+                    let mut offset: [u8; 8] = [0; 8];
+                    offset.copy_from_slice(value);
+
+                    interface.offset = match self.big_endian {
+                        true => i64::from_be_bytes(offset),
+                        false => i64::from_le_bytes(offset),
+                    }
+                }
+                _ => {}
+            }
+
+            options = options.get(4 + length.div_ceil(4) * 4..).unwrap_or(&[]);
+        }
+
+        if let Format::Pcapng { interfaces } = &mut self.format {
+            interfaces.push(interface);
+        }
+
+        Ok(())
+    }
+}
+
+impl Socket {
+    /// Reads count frames into a pcap file, file is truncated if it exists
+    ///
+    /// Frames are timestamped with [`Socket::read_raw_packet_ts`]
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let options = SocketOptions::new().ignore_outgoing(true);
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new_with("lo", options), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:97").expect("filter error");
+    ///
+    ///         let mut frame = [0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x97]);
+    ///         for _ in 0..3 {
+    ///             writer.send_raw_packet(&frame).expect("send error");
+    ///         }
+    ///
+    ///         let path = std::env::temp_dir().join("cursock-capture.pcap");
+    ///         reader.capture_to_file(&path, 3).expect("capture error");
+    ///
+    ///         let file = std::fs::read(&path).expect("read error");
+    ///         std::fs::remove_file(&path).expect("remove error");
+    ///
+    ///         assert_eq!(file.len(), 24 + 3 * (16 + 60));
+    ///         assert_eq!(&file[24 + 16 + 6..][..6], &frame[6..12])
+    ///     }
+    /// }
+    /// ```
+    pub fn capture_to_file(&self, path: &path::Path, count: usize) -> io::Result<()> {
+        let file: io::BufWriter<fs::File> = io::BufWriter::new(fs::File::create(path)?);
+        let mut writer: PcapWriter<io::BufWriter<fs::File>> = PcapWriter::new(file)?;
+
+        let mut buffer: Vec<u8> = vec![0; *writer.get_snaplen() as usize];
+
+        for _ in 0..count {
+            let (length, ts) = self.read_raw_packet_ts(&mut buffer)?;
+            writer.write_packet(&buffer[..length], ts.get_time())?;
+        }
+
+        writer.flush()
+    }
+    /// Sends every frame of a pcap file, returns number of sent frames
+    ///
+    /// If timing is respected, frames are sent at the same offsets from the first one
+    /// as they were captured
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, Instant, UNIX_EPOCH};
+    /// use cursock::pcap_file::PcapWriter;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let options = SocketOptions::new().ignore_outgoing(true);
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new_with("lo", options), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:96").expect("filter error");
+    ///
+    ///         let mut frame = [0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x96]);
+    ///
+    ///         let path = std::env::temp_dir().join("cursock-replay.pcap");
+    ///         let mut file = PcapWriter::new(std::fs::File::create(&path).expect("create error"))
+    ///             .expect("header error");
+    ///         file.write_packet(&frame, UNIX_EPOCH).expect("write error");
+    ///         file.write_packet(&frame, UNIX_EPOCH + Duration::from_millis(100)).expect("write error");
+    ///         drop(file);
+    ///
+    ///         let start = Instant::now();
+    ///         let sent = writer.replay_file(&path, true).expect("replay error");
+    ///         std::fs::remove_file(&path).expect("remove error");
+    ///
+    ///         assert_eq!(sent, 2);
+    ///         assert!(start.elapsed() >= Duration::from_millis(100));
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         for _ in 0..2 {
+    ///             let length = reader.read_raw_packet_timeout(&mut buffer, Duration::from_secs(1)).expect("read error");
+    ///             assert_eq!(&buffer[..length], &frame);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn replay_file(&self, path: &path::Path, respect_timing: bool) -> io::Result<usize> {
+        let file: io::BufReader<fs::File> = io::BufReader::new(fs::File::open(path)?);
+        let mut reader: PcapReader<io::BufReader<fs::File>> = PcapReader::new(file)?;
+
+        let start: time::Instant = time::Instant::now();
+        let mut first_ts: Option<time::SystemTime> = None;
+        let mut count: usize = 0;
+
+        while let Some((ts, data)) = reader.next_packet()? {
+            if respect_timing {
+                let first_ts: time::SystemTime = *first_ts.get_or_insert(ts);
+                // frames, which go back in time, are sent at once
+                let offset: time::Duration = ts.duration_since(first_ts).unwrap_or_default();
+
+                let elapsed: time::Duration = start.elapsed();
+                if offset > elapsed {
+                    thread::sleep(offset - elapsed);
+                }
+            }
+
+            self.send_raw_packet(data)?;
+            count += 1;
+        }
+
+        logger::debug(|| format!("replayed {} frames from {}", count, path.display()));
+
+        Ok(count)
+    }
+}
+
+/// reads the whole buffer, returns false if the reader is at its end,
+/// fails if it ends in the middle of the buffer
+fn read_record<R: io::Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<bool> {
+    let mut read: usize = 0;
+
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) if read == 0 => return Ok(false),
+            Ok(0) => {
+                return Err(invalid_file(format!(
+                    "file is truncated, {} of {} bytes were read",
+                    read,
+                    buffer.len()
+                )))
+            }
+            Ok(length) => read += length,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(true)
+}
+
+/// reads the whole buffer, end of the file is an error
+fn read_rest<R: io::Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<()> {
+    match read_record(reader, buffer)? {
+        true => Ok(()),
+        false => Err(invalid_file("file is truncated")),
+    }
+}
+
+/// writes pcapng block, body is padded to 4 bytes
+fn write_block<W: io::Write>(writer: &mut W, block_type: u32, body: &[u8]) -> io::Result<()> {
+    let padding: usize = body.len().div_ceil(4) * 4 - body.len();
+    let total_len: u32 = (12 + body.len() + padding) as u32;
+
+    writer.write_all(&block_type.to_le_bytes())?;
+    writer.write_all(&total_len.to_le_bytes())?;
+    writer.write_all(body)?;
+    writer.write_all(&[0; 3][..padding])?;
+    writer.write_all(&total_len.to_le_bytes())
+}
+
+fn invalid_file<S: Into<String>>(message: S) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid pcap file: {}", message.into()),
+    )
+}
+
+fn u32_at(bytes: &[u8], offset: usize, big_endian: bool) -> u32 {
+    let word: [u8; 4] = [
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ];
+
+    match big_endian {
+        true => u32::from_be_bytes(word),
+        false => u32::from_le_bytes(word),
+    }
+}
+
+fn u16_at(bytes: &[u8], offset: usize, big_endian: bool) -> u16 {
+    let word: [u8; 2] = [bytes[offset], bytes[offset + 1]];
+
+    match big_endian {
+        true => u16::from_be_bytes(word),
+        false => u16::from_le_bytes(word),
+    }
+}
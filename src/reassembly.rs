@@ -0,0 +1,539 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::net;
+use std::time;
+
+use crate::*;
+
+const IP_MF: u16 = 0x2000;
+const IP_DF: u16 = 0x4000;
+const IP_OFFSET_MASK: u16 = 0x1fff;
+const MAX_DATAGRAM_SIZE: usize = 0xffff;
+const TCP_HEADER_SIZE: usize = 20;
+const TCP_FIN: u8 = 0x01;
+const TCP_SYN: u8 = 0x02;
+const TCP_RST: u8 = 0x04;
+// out of order bytes, which are buffered for one direction, farthest segments are dropped above it
+const MAX_PENDING_SIZE: usize = 4 << 20;
+
+/// Reassembles fragmented ipv4 datagrams from captured ethernet frames
+///
+/// # Example
+/// ```
+/// use cursock::*;
+/// use std::time::Duration;
+///
+/// fn fragment(offset: u16, more: bool, payload: &[u8]) -> Vec<u8> {
+///     let mut frame = vec![0; ETH_HEADER_SIZE + IPV4_HEADER_SIZE];
+///     frame[12..14].copy_from_slice(&IPV4_PROTO.to_be_bytes());
+///     frame[14] = 0x45;
+///     frame[16..18].copy_from_slice(&((IPV4_HEADER_SIZE + payload.len()) as u16).to_be_bytes());
+///     frame[18..20].copy_from_slice(&0x1234u16.to_be_bytes());
+///     frame[20..22].copy_from_slice(&((offset / 8) | if more { 0x2000 } else { 0 }).to_be_bytes());
+///     frame[23] = 17;
+///     frame.extend_from_slice(payload);
+///     frame
+/// }
+///
+/// let mut reassembler = Ipv4Reassembler::new(Duration::from_secs(30));
+///
+/// assert!(reassembler.push(&fragment(8, false, &[2; 4])).is_none());
+///
+/// let datagram = reassembler.push(&fragment(0, true, &[1; 8])).expect("datagram is complete");
+///
+/// assert_eq!(datagram.len(), IPV4_HEADER_SIZE + 12);
+/// assert_eq!(&datagram[IPV4_HEADER_SIZE..], &[1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2]);
+/// assert_eq!(reassembler.pending(), 0)
+/// ```
+pub struct Ipv4Reassembler {
+    timeout: time::Duration,
+    fragments: HashMap<FragmentKey, FragmentSet>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct FragmentKey {
+    src: net::Ipv4Addr,
+    dst: net::Ipv4Addr,
+    id: u16,
+    protocol: u8,
+}
+
+struct FragmentSet {
+    header: Option<Vec<u8>>,
+    parts: Vec<(usize, Vec<u8>)>,
+    total_len: Option<usize>,
+    first_seen: time::Instant,
+}
+
+impl Ipv4Reassembler {
+    /// Initializes reassembler, incomplete datagrams older than timeout are dropped
+    pub fn new(timeout: time::Duration) -> Self {
+        Self {
+            timeout,
+            fragments: HashMap::new(),
+        }
+    }
+
+    /// Feeds captured ethernet frame into the reassembler
+    ///
+    /// Returns ipv4 datagram (header + payload) after its last missing fragment has arrived,
+    /// not fragmented datagrams are returned immediately, non ipv4 frames are ignored
+    pub fn push(&mut self, frame: &[u8]) -> Option<Vec<u8>> {
+        self.evict_expired();
+
+        if frame.len() < ETH_HEADER_SIZE + IPV4_HEADER_SIZE
+            || u16::from_be_bytes([frame[12], frame[13]]) != IPV4_PROTO
+        {
+            return None;
+        }
+
+        let packet: &[u8] = &frame[ETH_HEADER_SIZE..];
+
+        let header_len: usize = ((packet[0] & 0x0f) as usize) * 4;
+        let total_len: usize = u16::from_be_bytes([packet[2], packet[3]]) as usize;
+        if packet[0] >> 4 != 4
+            || header_len < IPV4_HEADER_SIZE
+            || total_len < header_len
+            || packet.len() < total_len
+        {
+            return None;
+        }
+
+        let frag: u16 = u16::from_be_bytes([packet[6], packet[7]]);
+        let offset: usize = ((frag & IP_OFFSET_MASK) as usize) * 8;
+        let more_fragments: bool = frag & IP_MF != 0;
+
+        if offset == 0 && !more_fragments {
+            return Some(packet[..total_len].to_vec());
+        }
+
+        let payload: &[u8] = &packet[header_len..total_len];
+        if offset + payload.len() > MAX_DATAGRAM_SIZE - header_len {
+            return None;
+        }
+
+        let key: FragmentKey = FragmentKey {
+            src: net::Ipv4Addr::new(packet[12], packet[13], packet[14], packet[15]),
+            dst: net::Ipv4Addr::new(packet[16], packet[17], packet[18], packet[19]),
+            id: u16::from_be_bytes([packet[4], packet[5]]),
+            protocol: packet[9],
+        };
+
+        let set: &mut FragmentSet = self.fragments.entry(key.clone()).or_insert(FragmentSet {
+            header: None,
+            parts: Vec::new(),
+            total_len: None,
+            first_seen: time::Instant::now(),
+        });
+
+        if offset == 0 {
+            set.header = Some(packet[..header_len].to_vec());
+        }
+        if !more_fragments {
+            set.total_len = Some(offset + payload.len());
+        }
+        set.parts.push((offset, payload.to_vec()));
+
+        let datagram: Vec<u8> = set.assemble()?;
+        self.fragments.remove(&key);
+
+        Some(datagram)
+    }
+
+    /// Returns count of datagrams waiting for missing fragments
+    pub fn pending(&self) -> usize {
+        self.fragments.len()
+    }
+
+    fn evict_expired(&mut self) {
+        let timeout: time::Duration = self.timeout;
+
+        self.fragments
+            .retain(|_, set| set.first_seen.elapsed() < timeout)
+    }
+}
+
+impl FragmentSet {
+    fn assemble(&mut self) -> Option<Vec<u8>> {
+        let header: &Vec<u8> = self.header.as_ref()?;
+        let payload_len: usize = self.total_len?;
+
+        self.parts.sort_by_key(|(offset, _)| *offset);
+
+        let mut covered: usize = 0;
+        for (offset, data) in self.parts.iter() {
+            if *offset > covered {
+                return None;
+            }
+            covered = covered.max(offset + data.len());
+        }
+        if covered < payload_len {
+            return None;
+        }
+
+        let header_len: usize = header.len();
+        let mut datagram: Vec<u8> = vec![0; header_len + payload_len];
+        datagram[..header_len].copy_from_slice(header);
+
+        for (offset, data) in self.parts.iter() {
+            let end: usize = (offset + data.len()).min(payload_len);
+            if *offset < end {
+                datagram[header_len + offset..header_len + end]
+                    .copy_from_slice(&data[..end - offset]);
+            }
+        }
+
+        let frag: u16 = u16::from_be_bytes([datagram[6], datagram[7]]) & IP_DF;
+        datagram[2..4].copy_from_slice(&((header_len + payload_len) as u16).to_be_bytes());
+        datagram[6..8].copy_from_slice(&frag.to_be_bytes());
+        datagram[10..12].copy_from_slice(&[0, 0]);
+
+        let ip_checksum: u16 = ipv4_checksum(&datagram[..header_len]);
+        datagram[10..12].copy_from_slice(&ip_checksum.to_be_bytes());
+
+        Some(datagram)
+    }
+}
+
+/// one direction of a tcp connection
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TcpFlow {
+    src: net::SocketAddrV4,
+    dst: net::SocketAddrV4,
+}
+
+/// Reassembles tcp byte streams from captured ethernet frames
+///
+/// Every direction of a connection is tracked separately, segments are ordered by their
+/// sequence numbers and retransmitted or overlapping bytes are delivered only once.
+/// At most 4 MiB of out of order bytes are buffered per direction, segments farthest from the gap are dropped above it
+///
+/// # Example
+/// ```
+/// use cursock::*;
+/// use std::net::{Ipv4Addr, SocketAddrV4};
+///
+/// fn segment(seq: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+///     let mut frame = vec![0; ETH_HEADER_SIZE + IPV4_HEADER_SIZE + 20];
+///     frame[12..14].copy_from_slice(&IPV4_PROTO.to_be_bytes());
+///     frame[14] = 0x45;
+///     frame[16..18].copy_from_slice(&((IPV4_HEADER_SIZE + 20 + payload.len()) as u16).to_be_bytes());
+///     frame[23] = 6;
+///     frame[26..30].copy_from_slice(&[10, 0, 0, 1]);
+///     frame[30..34].copy_from_slice(&[10, 0, 0, 2]);
+///     frame[34..36].copy_from_slice(&4000u16.to_be_bytes());
+///     frame[36..38].copy_from_slice(&80u16.to_be_bytes());
+///     frame[38..42].copy_from_slice(&seq.to_be_bytes());
+///     frame[46] = 5 << 4;
+///     frame[47] = flags;
+///     frame.extend_from_slice(payload);
+///     frame
+/// }
+///
+/// let mut reassembler = TcpReassembler::new();
+///
+/// reassembler.push(&segment(100, 0x02, b""));  // SYN
+/// reassembler.push(&segment(107, 0x18, b"world")); // out of order
+/// reassembler.push(&segment(101, 0x18, b"hello ")); // fills the gap
+/// reassembler.push(&segment(101, 0x18, b"hello ")); // retransmission
+///
+/// let flow = TcpFlow::new(
+///     SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 1), 4000),
+///     SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 2), 80),
+/// );
+///
+/// assert_eq!(reassembler.take_stream(&flow), b"hello world");
+/// assert!(!reassembler.is_closed(&flow));
+/// ```
+pub struct TcpReassembler {
+    streams: HashMap<TcpFlow, TcpStream>,
+}
+
+struct TcpStream {
+    isn: u32,
+    // offsets are relative to isn and aren't wrapped, so streams can be longer than 4 GiB
+    next: u64,
+    fin: Option<u64>,
+    reset: bool,
+    pending: BTreeMap<u64, Vec<u8>>,
+    pending_size: usize,
+    data: Vec<u8>,
+}
+
+impl TcpFlow {
+    pub fn new(src: net::SocketAddrV4, dst: net::SocketAddrV4) -> Self {
+        Self { src, dst }
+    }
+
+    /// Returns opposite direction of the connection
+    pub fn reverse(&self) -> Self {
+        Self {
+            src: self.dst,
+            dst: self.src,
+        }
+    }
+
+    getters!(
+        pub get_src(src) -> net::SocketAddrV4;
+        pub get_dst(dst) -> net::SocketAddrV4;
+    );
+}
+
+impl TcpReassembler {
+    pub fn new() -> Self {
+        Self {
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Feeds captured ethernet frame into the reassembler
+    ///
+    /// Returns flow which got new in-order bytes, non tcp frames are ignored
+    pub fn push(&mut self, frame: &[u8]) -> Option<TcpFlow> {
+        if frame.len() < ETH_HEADER_SIZE + IPV4_HEADER_SIZE
+            || u16::from_be_bytes([frame[12], frame[13]]) != IPV4_PROTO
+        {
+            return None;
+        }
+
+        let packet: &[u8] = &frame[ETH_HEADER_SIZE..];
+
+        let ip_header_len: usize = ((packet[0] & 0x0f) as usize) * 4;
+        let total_len: usize = u16::from_be_bytes([packet[2], packet[3]]) as usize;
+        let frag: u16 = u16::from_be_bytes([packet[6], packet[7]]);
+        if packet[0] >> 4 != 4
+            || packet[9] != TCP_PROTO as u8
+            || frag & (IP_MF | IP_OFFSET_MASK) != 0
+            || ip_header_len < IPV4_HEADER_SIZE
+            || total_len < ip_header_len + TCP_HEADER_SIZE
+            || packet.len() < total_len
+        {
+            return None;
+        }
+
+        let segment: &[u8] = &packet[ip_header_len..total_len];
+        let tcp_header_len: usize = ((segment[12] >> 4) as usize) * 4;
+        if tcp_header_len < TCP_HEADER_SIZE || segment.len() < tcp_header_len {
+            return None;
+        }
+
+        let flow: TcpFlow = TcpFlow {
+            src: net::SocketAddrV4::new(
+                net::Ipv4Addr::new(packet[12], packet[13], packet[14], packet[15]),
+                u16::from_be_bytes([segment[0], segment[1]]),
+            ),
+            dst: net::SocketAddrV4::new(
+                net::Ipv4Addr::new(packet[16], packet[17], packet[18], packet[19]),
+                u16::from_be_bytes([segment[2], segment[3]]),
+            ),
+        };
+
+        let seq: u32 = u32::from_be_bytes([segment[4], segment[5], segment[6], segment[7]]);
+        let flags: u8 = segment[13];
+        let payload: &[u8] = &segment[tcp_header_len..];
+
+        // SYN (unless it's a retransmission) starts a new stream,
+        // other streams start from the first captured segment
+        let is_new_syn: bool = flags & TCP_SYN != 0
+            && self
+                .streams
+                .get(&flow)
+                .is_none_or(|stream| stream.isn != seq);
+        if is_new_syn {
+            self.streams.insert(flow.clone(), TcpStream::new(seq, 1));
+        }
+
+        let stream: &mut TcpStream = self
+            .streams
+            .entry(flow.clone())
+            .or_insert(TcpStream::new(seq, 0));
+
+        let data_seq: u32 = match flags & TCP_SYN != 0 {
+            true => seq.wrapping_add(1),
+            false => seq,
+        };
+
+        let advanced: bool = stream.insert(data_seq, payload);
+
+        if flags & TCP_FIN != 0 {
+            if let Some(offset) = stream.offset(data_seq) {
+                stream.fin = Some(offset + payload.len() as u64);
+            }
+        }
+        if flags & TCP_RST != 0 {
+            stream.reset = true;
+        }
+
+        match advanced {
+            true => Some(flow),
+            false => None,
+        }
+    }
+
+    /// Drains bytes which were reassembled for the flow so far
+    pub fn take_stream(&mut self, flow: &TcpFlow) -> Vec<u8> {
+        match self.streams.get_mut(flow) {
+            Some(stream) => std::mem::take(&mut stream.data),
+            None => Vec::new(),
+        }
+    }
+
+    /// Checks whether flow was finished with FIN (and all its bytes arrived) or RST
+    pub fn is_closed(&self, flow: &TcpFlow) -> bool {
+        match self.streams.get(flow) {
+            Some(stream) => stream.reset || stream.fin.is_some_and(|fin| stream.next >= fin),
+            None => false,
+        }
+    }
+
+    /// Stops tracking the flow, returns its not taken bytes
+    pub fn remove(&mut self, flow: &TcpFlow) -> Option<Vec<u8>> {
+        self.streams.remove(flow).map(|stream| stream.data)
+    }
+
+    /// Iterates over tracked flows
+    pub fn flows(&self) -> impl Iterator<Item = &TcpFlow> {
+        self.streams.keys()
+    }
+}
+
+impl Default for TcpReassembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TcpStream {
+    fn new(isn: u32, next: u64) -> Self {
+        Self {
+            isn,
+            next,
+            fin: None,
+            reset: false,
+            pending: BTreeMap::new(),
+            pending_size: 0,
+            data: Vec::new(),
+        }
+    }
+
+    /// unwraps sequence number into offset from isn, taking the nearest one to the next expected byte,
+    /// None is returned for segments sent before the stream start
+    fn offset(&self, seq: u32) -> Option<u64> {
+        let expected: u32 = self.isn.wrapping_add(self.next as u32);
+        let distance: i64 = seq.wrapping_sub(expected) as i32 as i64;
+
+        u64::try_from(self.next as i64 + distance).ok()
+    }
+
+    /// stores segment and moves every in-order byte into data, returns whether data grew
+    fn insert(&mut self, seq: u32, payload: &[u8]) -> bool {
+        if payload.is_empty() {
+            return false;
+        }
+
+        let offset: u64 = match self.offset(seq) {
+            Some(offset) => offset,
+            None => return false,
+        };
+
+        let end: u64 = offset + payload.len() as u64;
+        if end <= self.next {
+            return false;
+        }
+
+        match self.pending.get(&offset) {
+            Some(stored) if stored.len() >= payload.len() => {}
+            _ => {
+                let replaced: Option<Vec<u8>> = self.pending.insert(offset, payload.to_vec());
+                self.pending_size += payload.len();
+                self.pending_size -= replaced.map_or(0, |stored| stored.len());
+            }
+        }
+
+        let len: usize = self.data.len();
+
+        while let Some((&offset, _)) = self.pending.first_key_value() {
+            if offset > self.next {
+                break;
+            }
+
+            let segment: Vec<u8> = self.pending.remove(&offset).unwrap_or_default();
+            let end: u64 = offset + segment.len() as u64;
+            self.pending_size -= segment.len();
+
+            if end > self.next {
+                self.data
+                    .extend_from_slice(&segment[(self.next - offset) as usize..]);
+                self.next = end;
+            }
+        }
+
+        // peer, which leaves a gap, could grow the buffer without bound otherwise
+        while self.pending_size > MAX_PENDING_SIZE {
+            match self.pending.pop_last() {
+                Some((_, segment)) => self.pending_size -= segment.len(),
+                None => break,
+            }
+        }
+
+        self.data.len() > len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tcp_stream_continues_past_sequence_space() {
+        let isn: u32 = 0xffff_ff00;
+        // 5 GiB were already delivered, so sequence numbers wrapped and offsets passed u32
+        let mut stream: TcpStream = TcpStream::new(isn, 5 << 30);
+        let seq: u32 = isn.wrapping_add((5u64 << 30) as u32);
+
+        assert!(!stream.insert(seq.wrapping_add(4), b"world"));
+        assert!(stream.insert(seq, b"hey "));
+        assert_eq!(stream.data, b"hey world");
+        assert_eq!(stream.next, (5 << 30) + 9);
+
+        // retransmission of the delivered bytes
+        assert!(!stream.insert(seq, b"hey "));
+        assert_eq!(stream.offset(seq), Some(5 << 30))
+    }
+
+    #[test]
+    fn tcp_stream_drops_segments_before_start() {
+        let mut stream: TcpStream = TcpStream::new(1000, 0);
+
+        assert_eq!(stream.offset(990), None);
+        assert!(!stream.insert(990, b"old"));
+        assert!(stream.insert(1000, b"new"));
+        assert_eq!(stream.data, b"new")
+    }
+
+    #[test]
+    fn tcp_stream_limits_pending_bytes() {
+        let mut stream: TcpStream = TcpStream::new(0, 0);
+        let segment: Vec<u8> = vec![7; 1 << 16];
+
+        // first segment is missing, so everything else is kept out of order
+        for i in 1..=(MAX_PENDING_SIZE / segment.len() + 8) {
+            assert!(!stream.insert((i * segment.len()) as u32, &segment));
+        }
+
+        assert!(stream.pending_size <= MAX_PENDING_SIZE);
+        assert_eq!(
+            stream.pending_size,
+            stream.pending.values().map(Vec::len).sum::<usize>()
+        );
+        // segments nearest to the gap are kept
+        assert_eq!(
+            stream.pending.first_key_value().map(|(offset, _)| *offset),
+            Some(1 << 16)
+        );
+
+        assert!(stream.insert(0, &segment));
+        assert_eq!(stream.data.len(), MAX_PENDING_SIZE + segment.len());
+        assert_eq!(stream.pending_size, 0)
+    }
+}
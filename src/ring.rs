@@ -0,0 +1,405 @@
+use std::io;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::sync::atomic;
+use std::time;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::socket::poll_socket;
+use crate::*;
+
+/// layout of the linux receive ring, pcap uses its own buffer on windows, so they are ignored there
+///
+/// Block size has to be a multiple of the page size and of the frame size,
+/// frame has to fit tpacket header with the captured packet, longer packets are truncated
+///
+/// # Example
+/// ```
+/// use cursock::*;
+///
+/// let options = RingOptions::new().block_count(128);
+///
+/// assert_eq!(options.get_block_count(), &128);
+/// assert_eq!(options.get_frame_size(), &2048)
+/// ```
+#[derive(Clone)]
+pub struct RingOptions {
+    block_size: usize,
+    block_count: usize,
+    frame_size: usize,
+}
+
+impl RingOptions {
+    pub fn new() -> Self {
+        Self {
+            block_size: 1 << 16,
+            block_count: 64,
+            frame_size: 2048,
+        }
+    }
+
+    /// sets size of the ring blocks in bytes
+    pub fn block_size(mut self, bytes: usize) -> Self {
+        self.block_size = bytes;
+        self
+    }
+
+    /// sets count of the ring blocks
+    pub fn block_count(mut self, count: usize) -> Self {
+        self.block_count = count;
+        self
+    }
+
+    /// sets size of the ring frames in bytes
+    pub fn frame_size(mut self, bytes: usize) -> Self {
+        self.frame_size = bytes;
+        self
+    }
+
+    getters!(
+        pub get_block_size(block_size) -> usize;
+        pub get_block_count(block_count) -> usize;
+        pub get_frame_size(frame_size) -> usize;
+    );
+}
+
+impl Default for RingOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reader of packets, which aren't copied out of the kernel ring or pcap buffer, see [`Socket::ring_reader`]
+pub struct RingReader<'a> {
+    socket: &'a Socket,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ring: *mut u8,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ring_size: usize,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    options: RingOptions,
+    // frame, which is checked next
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    index: usize,
+}
+
+/// Packet borrowed from the ring, its slot is given back when the frame is dropped
+pub struct Frame<'a> {
+    data: &'a [u8],
+    len: usize,
+    timestamp: time::SystemTime,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    status: &'a atomic::AtomicU32,
+    // pcap buffer is only valid until the next pcap_next_ex call
+    #[cfg(target_os = "windows")]
+    _guard: std::sync::MutexGuard<'a, Option<crate::socket::PcapPacket>>,
+}
+
+impl Socket {
+    /// Creates reader, which borrows packets from the kernel or driver buffer instead of copying them
+    ///
+    /// On linux PACKET_RX_RING is mapped into memory, so packets are read without syscalls,
+    /// until the ring is empty. While the reader exists, packets only get into the ring,
+    /// so the socket methods don't receive them, ring is removed when the reader is dropped.
+    /// On windows frames are views of pcap buffer and other reads of the socket wait for them to be dropped
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     let options = SocketOptions::new().ignore_outgoing(true);
+    ///
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new_with("lo", options), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:9b").expect("filter error");
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x9b]);
+    ///
+    ///         // ring of 4 frames, so it wraps around
+    ///         let mut ring = reader
+    ///             .ring_reader(RingOptions::new().block_size(4096).block_count(2))
+    ///             .expect("ring error");
+    ///
+    ///         for i in 0..10 {
+    ///             frame[14] = i;
+    ///             writer.send_raw_packet(&frame).expect("send error");
+    ///
+    ///             let received = ring.next_frame_timeout(Duration::from_secs(1)).expect("read error");
+    ///
+    ///             assert_eq!(received.data(), &frame[..]);
+    ///             assert_eq!(received.get_caplen(), 60);
+    ///             assert_eq!(received.get_len(), &60);
+    ///             assert!(received.get_timestamp() <= &SystemTime::now())
+    ///         }
+    ///
+    ///         drop(ring);
+    ///
+    ///         // socket receives packets itself again
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         assert_eq!(
+    ///             reader.read_raw_packet_timeout(&mut buffer, Duration::from_secs(1)).expect("read error"),
+    ///             60
+    ///         )
+    ///     }
+    /// }
+    /// ```
+    pub fn ring_reader(&self, options: RingOptions) -> io::Result<RingReader<'_>> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            RingReader::open_linux(self, options)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let _ = options;
+            Ok(RingReader { socket: self })
+        }
+    }
+}
+
+impl<'a> RingReader<'a> {
+    /// Waits for the next packet
+    ///
+    /// In non-blocking mode returns WouldBlock error, if there are no packets
+    pub fn next_frame(&mut self) -> io::Result<Frame<'_>> {
+        self.next_frame_until(None)
+    }
+
+    /// Waits for the next packet no longer than timeout, returns TimedOut error on expiry
+    pub fn next_frame_timeout(&mut self, timeout: time::Duration) -> io::Result<Frame<'_>> {
+        self.next_frame_until(Some(time::Instant::now() + timeout))
+    }
+
+    /// Returns socket, which the ring belongs to
+    pub fn get_socket(&self) -> &Socket {
+        self.socket
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn open_linux(socket: &'a Socket, options: RingOptions) -> io::Result<Self> {
+        let header_size: usize = std::mem::size_of::<ccs::tpacket2_hdr>();
+
+        if options.frame_size <= header_size
+            || options.block_size < options.frame_size
+            || !options.block_size.is_multiple_of(options.frame_size)
+            || options.block_count == 0
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "ring of {} blocks of {} bytes can't hold frames of {} bytes",
+                    options.block_count, options.block_size, options.frame_size
+                ),
+            ));
+        }
+
+        let frame_count: usize = options.block_size / options.frame_size * options.block_count;
+        let request: ccs::tpacket_req = ccs::tpacket_req {
+            tp_block_size: options.block_size as u32,
+            tp_block_nr: options.block_count as u32,
+            tp_frame_size: options.frame_size as u32,
+            tp_frame_nr: frame_count as u32,
+        };
+
+        set_packet_option(socket.raw_socket(), ccs::PACKET_VERSION, &ccs::TPACKET_V2)?;
+        set_packet_option(socket.raw_socket(), ccs::PACKET_RX_RING, &request)?;
+
+        let ring_size: usize = options.block_size * options.block_count;
+        let ring: *mut std::os::raw::c_void = unsafe {
+            ccs::mmap(
+                std::ptr::null_mut(),
+                ring_size,
+                ccs::PROT_READ | ccs::PROT_WRITE,
+                ccs::MAP_SHARED,
+                socket.raw_socket(),
+                0,
+            )
+        };
+
+        // mmap returns MAP_FAILED, which is -1
+        if ring as isize == -1 {
+            let err: io::Error = io::Error::last_os_error();
+            let _ = remove_ring(socket.raw_socket());
+
+            return Err(err);
+        }
+
+        Ok(Self {
+            socket,
+            ring: ring as *mut u8,
+            ring_size,
+            options,
+            index: 0,
+        })
+    }
+
+    /// frames don't cross blocks, so the rest of a block can be unused
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn frame_header(&self, index: usize) -> *mut ccs::tpacket2_hdr {
+        let frames_per_block: usize = self.options.block_size / self.options.frame_size;
+        let offset: usize = index / frames_per_block * self.options.block_size
+            + index % frames_per_block * self.options.frame_size;
+
+        unsafe { self.ring.add(offset) as *mut ccs::tpacket2_hdr }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn next_frame_until(&mut self, deadline: Option<time::Instant>) -> io::Result<Frame<'_>> {
+        let header: *mut ccs::tpacket2_hdr = self.frame_header(self.index);
+        // kernel changes the status concurrently, packet is written before it's given to user
+        let status: &atomic::AtomicU32 =
+            unsafe { atomic::AtomicU32::from_ptr(&raw mut (*header).tp_status) };
+
+        while status.load(atomic::Ordering::Acquire) & ccs::TP_STATUS_USER == 0 {
+            let ready: bool = match deadline {
+                Some(deadline) => poll_socket(self.socket.raw_socket(), ccs::POLLIN, deadline)?,
+                None if self.socket.is_nonblocking()? => {
+                    return Err(io::Error::from(io::ErrorKind::WouldBlock))
+                }
+                None => {
+                    let deadline: time::Instant =
+                        time::Instant::now() + time::Duration::from_secs(60);
+                    poll_socket(self.socket.raw_socket(), ccs::POLLIN, deadline)?;
+                    continue;
+                }
+            };
+
+            if !ready {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "no packets received until timeout",
+                ));
+            }
+        }
+
+        let frame_count: usize = self.ring_size / self.options.block_size
+            * (self.options.block_size / self.options.frame_size);
+        self.index = (self.index + 1) % frame_count;
+
+        let header: &ccs::tpacket2_hdr = unsafe { &*header };
+        let data: &[u8] = unsafe {
+            std::slice::from_raw_parts(
+                (header as *const ccs::tpacket2_hdr as *const u8).add(header.tp_mac as usize),
+                header.tp_snaplen as usize,
+            )
+        };
+
+        logger::debug(|| format!("received {} bytes", data.len()));
+
+        Ok(Frame {
+            data,
+            len: header.tp_len as usize,
+            timestamp: time::UNIX_EPOCH + time::Duration::new(header.tp_sec as u64, header.tp_nsec),
+            status,
+        })
+    }
+
+    #[cfg(target_os = "windows")]
+    fn next_frame_until(&mut self, deadline: Option<time::Instant>) -> io::Result<Frame<'_>> {
+        let mut guard = self.socket.lock_reads();
+
+        let packet: crate::socket::PcapPacket = loop {
+            match self.socket.next_pcap_packet(&mut guard)? {
+                Some(packet) => break packet,
+                None => match deadline {
+                    Some(deadline) if time::Instant::now() >= deadline => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "no packets received until timeout",
+                        ))
+                    }
+                    None if self.socket.is_nonblocking()? => {
+                        return Err(io::Error::from(io::ErrorKind::WouldBlock))
+                    }
+                    _ => continue,
+                },
+            }
+        };
+
+        let header: &ccs::pcap_pkthdr = unsafe { &*packet.header };
+        let data: &[u8] =
+            unsafe { std::slice::from_raw_parts(packet.data, header.caplen as usize) };
+
+        logger::debug(|| format!("received {} bytes", data.len()));
+
+        Ok(Frame {
+            data,
+            len: header.len as usize,
+            timestamp: self.socket.pcap_timestamp(header),
+            _guard: guard,
+        })
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Drop for RingReader<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            ccs::munmap(self.ring as *mut std::os::raw::c_void, self.ring_size);
+        }
+        // socket receives packets itself again
+        let _ = remove_ring(self.socket.raw_socket());
+    }
+}
+
+impl Frame<'_> {
+    /// Returns captured bytes of the packet
+    pub fn data(&self) -> &[u8] {
+        self.data
+    }
+
+    /// Returns count of captured bytes, it's less than the length if the packet was truncated
+    pub fn get_caplen(&self) -> usize {
+        self.data.len()
+    }
+
+    getters!(
+        pub get_len(len) -> usize;
+        pub get_timestamp(timestamp) -> time::SystemTime;
+    );
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Drop for Frame<'_> {
+    fn drop(&mut self) {
+        // slot is given back to the kernel, after the packet isn't used anymore
+        self.status
+            .store(ccs::TP_STATUS_KERNEL, atomic::Ordering::Release)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_packet_option<T>(socket: i32, option: i32, value: &T) -> io::Result<()> {
+    let result: i32 = unsafe {
+        ccs::setsockopt(
+            socket,
+            ccs::SOL_PACKET,
+            option,
+            value as *const T as *const std::os::raw::c_void,
+            std::mem::size_of::<T>() as ccs::SocklenT,
+        )
+    };
+
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// ring of zero blocks frees the ring of the socket
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn remove_ring(socket: i32) -> io::Result<()> {
+    let request: ccs::tpacket_req = ccs::tpacket_req {
+        tp_block_size: 0,
+        tp_block_nr: 0,
+        tp_frame_size: 0,
+        tp_frame_nr: 0,
+    };
+
+    set_packet_option(socket, ccs::PACKET_RX_RING, &request)
+}
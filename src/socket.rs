@@ -1,14 +1,33 @@
 use std::io;
+use std::net;
+use std::sync::atomic;
+use std::sync::Mutex;
+use std::thread;
+use std::time;
 
-#[cfg(any(target_os = "windows",))]
+#[cfg(target_os = "windows")]
 use std::ffi::CString;
 
 use crate::*;
 
-/// Struct for raw socketing
+/// Struct for raw socketing, socket is closed when the structure is dropped
 ///
-/// # Examples
+/// Socket is Send and Sync, so it can be shared between a reader and a writer thread.
+/// Linux socket and macos bpf device can be used from several threads at once,
+/// packets, which were read from bpf device, are taken under a lock.
+/// Pcap handle on windows isn't thread-safe for reads, since pcap_next_ex returns
+/// a pointer into its own buffer, so reads are serialized, while sends and reads still can run concurrently
+///
+/// ```
+/// use cursock::*;
+///
+/// fn assert_send_sync<T: Send + Sync>() {}
+///
+/// assert_send_sync::<Socket>()
 /// ```
+///
+/// # Examples
+/// ```no_run
 /// use cursock::*;
 /// use cursock::utils::*;
 ///
@@ -24,283 +43,2790 @@ use crate::*;
 /// socket.destroy()
 /// ```
 pub struct Socket {
-    #[cfg(target_os = "linux")]
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
     socket: i32,
     #[cfg(target_os = "windows")]
     adapter: usize,
+    // packets, which were read from bpf device at once, but weren't taken yet
+    #[cfg(target_os = "macos")]
+    bpf_buffer: Mutex<BpfBuffer>,
     interface: Adapter,
+    options: SocketOptions,
+    // mtu, which frames are checked against, None if they aren't
+    mtu: Option<u32>,
+    // 0 if frames are sent as is, otherwise VLAN_PRESENT | vlan id
+    egress_vlan: atomic::AtomicU32,
+    promiscuous: atomic::AtomicBool,
+    // PACKET_STATISTICS counters are reset when they are read, so they are summed here
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    packet_stats: Mutex<CaptureStats>,
+    // rx_dropped counter of the interface, when the socket was opened
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    rx_dropped: u64,
+    // pcap can't report its buffer size, so the last set one is kept
+    #[cfg(target_os = "windows")]
+    recv_buffer_size: atomic::AtomicUsize,
+    // held from pcap_next_ex until the packet is copied out of pcap buffer,
+    // keeps the packet, which was taken by available, but wasn't read yet
+    #[cfg(target_os = "windows")]
+    read_lock: Mutex<Option<PcapPacket>>,
+}
+
+/// npcap kernel buffer size, which is used if it's not set before activating
+#[cfg(target_os = "windows")]
+const PCAP_DEFAULT_BUFFER_SIZE: usize = 1000000;
+
+const VLAN_PRESENT: u32 = 0x10000;
+
+/// packet counters of a socket, see [`Socket::stats`]
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct CaptureStats {
+    // packets accepted by the filter, including dropped ones
+    received: u64,
+    // packets, which didn't fit into the socket buffer
+    dropped_by_kernel: u64,
+    // packets dropped by the interface or its driver
+    dropped_by_interface: u64,
+}
+
+impl CaptureStats {
+    getters!(
+        pub get_received(received) -> u64;
+        pub get_dropped_by_kernel(dropped_by_kernel) -> u64;
+        pub get_dropped_by_interface(dropped_by_interface) -> u64;
+    );
+}
+
+/// time of a received packet, see [`Socket::read_raw_packet_ts`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Timestamp {
+    /// packet was timestamped by the kernel or capture driver
+    Capture(time::SystemTime),
+    /// packet had no timestamp, so it's the time of the read
+    Read(time::SystemTime),
+}
+
+impl Timestamp {
+    pub fn get_time(&self) -> time::SystemTime {
+        match self {
+            Self::Capture(time) | Self::Read(time) => *time,
+        }
+    }
+
+    pub fn is_capture(&self) -> bool {
+        matches!(self, Self::Capture(_))
+    }
+}
+
+/// whom the received frame was addressed to, see [`PacketInfo`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PacketType {
+    /// frame is addressed to this host
+    Host,
+    Broadcast,
+    Multicast,
+    /// frame is addressed to another host and was received in promiscuous mode
+    OtherHost,
+    /// frame was sent from this host
+    Outgoing,
+    /// other linux packet type, e.g. PACKET_LOOPBACK
+    Other(u8),
+}
+
+/// source of a received frame, see [`Socket::read_raw_packet_from`]
+#[derive(Clone)]
+pub struct PacketInfo {
+    pkttype: PacketType,
+    src_mac: Mac,
+    // ethertype in host byte order
+    protocol: u16,
+    // outer vlan tag, taken from the frame or from the kernel, if it was stripped
+    vlan: Option<VlanTag>,
+}
+
+impl PacketInfo {
+    getters!(
+        pub get_pkttype(pkttype) -> PacketType;
+        pub get_src_mac(src_mac) -> Mac;
+        pub get_protocol(protocol) -> u16;
+        pub get_vlan(vlan) -> Option<VlanTag>;
+    );
+}
+
+/// packet returned by pcap_next_ex, it stays in pcap buffer until the next call
+#[cfg(target_os = "windows")]
+pub(crate) struct PcapPacket {
+    pub(crate) header: *const ccs::pcap_pkthdr,
+    pub(crate) data: *const u8,
+}
+
+// pointers are only used under the read lock of the socket, which owns the buffer
+#[cfg(target_os = "windows")]
+unsafe impl Send for PcapPacket {}
+
+/// bpf device returns every captured packet in one read, each of them prefixed with bpf_hdr
+#[cfg(any(target_os = "macos", test))]
+struct BpfBuffer {
+    data: Vec<u8>,
+    offset: usize,
+    length: usize,
 }
 
 impl Socket {
     /// Initializes socket structure
     ///
+    /// Interface is identified by its name on linux and macos and by its index on windows,
+    /// both can be taken from [`list_interfaces`].
+    /// Returns PermissionDenied error, telling how to get the needed privileges, if the process lacks them.
+    /// It's a shortcut for [`SocketBuilder`] with default options
+    ///
     /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// let adapter = list_interfaces()
+    ///     .expect("list error")
+    ///     .into_iter()
+    ///     .find(|adapter| {
+    ///         *adapter.get_is_up() && !*adapter.get_is_loopback() && adapter.get_ipv4().is_some()
+    ///     })
+    ///     .expect("there is no usable interface");
+    ///
+    /// #[cfg(any(target_os = "linux", target_os = "macos"))]
+    /// let socket = Socket::new(adapter.get_name()).expect("initialize error"); // Linux, macOS
+    /// #[cfg(target_os = "windows")]
+    /// let socket = Socket::new(&adapter.get_index().to_string()).expect("initialize error"); // Windows
     /// ```
+    pub fn new(interface: &str) -> io::Result<Self> {
+        SocketBuilder::new().interface(interface).build()
+    }
+    /// Initializes socket structure with custom options
+    ///
+    /// # Examples
+    /// ```no_run
     /// use cursock::*;
-    /// use cursock::utils::*;
+    ///
+    /// let options = SocketOptions::new().tstamp_precision(TimestampPrecision::Nano);
     ///
     /// #[cfg(target_os = "linux")]
-    /// let socket = Socket::new("wlan0").expect("initialize error"); // Linux
+    /// let socket = Socket::new_with("wlan0", options).expect("initialize error"); // Linux
     /// #[cfg(target_os = "windows")]
-    /// let socket = Socket::new("10").expect("initialize error"); // Windows, id of the interface you can get running "route PRINT"
+    /// let socket = Socket::new_with("10", options).expect("initialize error"); // Windows
     /// ```
-    pub fn new(interface: &str) -> io::Result<Self> {
-        #[cfg(target_os = "linux")]
+    pub fn new_with(interface: &str, options: SocketOptions) -> io::Result<Self> {
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
         {
-            Self::new_linux(interface)
+            Self::open(Adapter::get_by_ifname(interface)?, options)
         }
         #[cfg(target_os = "windows")]
         {
-            Self::new_windows(interface)
+            let id = interface
+                .parse::<u32>()
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+
+            Self::open(Adapter::get_by_id(id)?, options)
         }
 
-        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
         {
-            let _ = interface;
+            let _ = (interface, options);
             Err(io::Error::new(
                 io::ErrorKind::NotFound,
                 format!("{} is not supported yet!", std::env::consts::OS),
             ))
         }
     }
-    /// Sends raw packet
+    /// Initializes socket, which receives only frames with the ethertype
+    ///
+    /// It's the same as [`Socket::new_with`] with [`SocketOptions::protocol`]
     ///
     /// # Examples
     /// ```
+    /// use std::time::Duration;
     /// use cursock::*;
-    /// use cursock::utils::*;
     ///
-    /// let socket = Socket::new("wlan0").expect("initialize error");
-    /// let buffer = [0; 100];
-    /// socket.send_raw_packet(&buffer).expect("send error")
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     fn frame(proto: u16) -> Vec<u8> {
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x95]);
+    ///         frame[12..14].copy_from_slice(&proto.to_be_bytes());
+    ///         frame
+    ///     }
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::with_protocol("lo", ARP_PROTO), Socket::new("lo")) {
+    ///         writer.send_raw_packet(&frame(IPV4_PROTO)).expect("send error");
+    ///         writer.send_raw_packet(&frame(ARP_PROTO)).expect("send error");
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         let mut received_arp = false;
+    ///
+    ///         while reader.read_raw_packet_timeout(&mut buffer, Duration::from_millis(200)).is_ok() {
+    ///             assert_eq!(&buffer[12..14], &ARP_PROTO.to_be_bytes());
+    ///             received_arp |= buffer[11] == 0x95;
+    ///         }
+    ///
+    ///         assert!(received_arp)
+    ///     }
+    /// }
     /// ```
-    pub fn send_raw_packet(&self, buffer: &[u8]) -> io::Result<()> {
-        #[cfg(target_os = "linux")]
-        {
-            self.send_raw_packet_linux(buffer)
-        }
-        #[cfg(target_os = "windows")]
-        {
-            self.send_raw_packet_windows(buffer)
-        }
-
-        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
-        {
-            let _ = buffer;
-            Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("{} is not supported yet!", std::env::consts::OS),
-            ))
-        }
+    pub fn with_protocol<P: Into<u16>>(interface: &str, protocol: P) -> io::Result<Self> {
+        Self::new_with(interface, SocketOptions::new().protocol(protocol))
     }
-    /// Reads raw packet, can be used for sniffing
+    /// Initializes socket on the adapter, e.g. one returned from [`list_interfaces`]
     ///
     /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// for adapter in list_interfaces().expect("list error") {
+    ///     if *adapter.get_is_up() {
+    ///         let socket = Socket::from_adapter(&adapter).expect("initialize error");
+    ///     }
+    /// }
     /// ```
+    pub fn from_adapter(adapter: &Adapter) -> io::Result<Self> {
+        Self::open(adapter.clone(), SocketOptions::default())
+    }
+    /// Initializes socket on the interface with the index
+    ///
+    /// Returns InvalidInput error, if there is no such interface
+    ///
+    /// # Examples
+    /// ```no_run
     /// use cursock::*;
-    /// use cursock::utils::*;
     ///
-    /// let socket = Socket::new("wlan0").expect("initialize error");
-    /// let mut buffer = [0; 1000];
-    /// socket.read_raw_packet(&mut buffer).expect("read error")
+    /// let socket = Socket::from_index(2).expect("initialize error");
     /// ```
-    pub fn read_raw_packet(&self, buffer: &mut [u8]) -> io::Result<()> {
-        #[cfg(target_os = "linux")]
+    pub fn from_index(ifindex: u32) -> io::Result<Self> {
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
         {
-            self.read_raw_packet_linux(buffer)
+            let mut name: [i8; ccs::IFNAMSIZ] = [0; ccs::IFNAMSIZ];
+
+            if unsafe { ccs::if_indextoname(ifindex, name.as_mut_ptr()) }.is_null() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("there isn\'t any interface with index {}", ifindex),
+                ));
+            }
+
+            let adapter: Adapter = Adapter::get_by_ifname(&str_from_cstr(name.as_ptr()))?;
+
+            Self::open(adapter, SocketOptions::default())
         }
         #[cfg(target_os = "windows")]
         {
-            self.read_raw_packet_windows(buffer)
+            let adapter: Adapter = Adapter::get_by_id(ifindex)?;
+
+            Self::open(adapter, SocketOptions::default())
         }
 
-        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
         {
-            let _ = buffer;
+            let _ = ifindex;
             Err(io::Error::new(
                 io::ErrorKind::NotFound,
                 format!("{} is not supported yet!", std::env::consts::OS),
             ))
         }
     }
-
-    pub fn get_src_mac(&self) -> &Mac {
-        self.interface.get_mac()
-    }
-    pub fn get_adapter(&self) -> &Adapter {
-        &self.interface
-    }
-
-    /// Destroys socket structure
+    /// Wraps AF_PACKET socket, which was opened and configured elsewhere, e.g. with fanout
+    ///
+    /// Socket takes ownership of the descriptor, so it's closed when socket is dropped,
+    /// or if the interface can't be found. Descriptor isn't bound and options aren't applied,
+    /// only kernel timestamps are enabled for [`Socket::read_raw_packet_ts`]
     ///
     /// # Examples
     /// ```
     /// use cursock::*;
     ///
-    /// let socket = Socket::new("wlan0").expect("initialize error");
-    /// socket.destroy()
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let fd = unsafe {
+    ///         ccs::socket(ccs::AF_PACKET, ccs::SOCK_RAW, (ccs::ETH_P_ALL as u16).to_be() as i32)
+    ///     };
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if fd >= 0 {
+    ///         let socket = Socket::from_raw_fd(fd, "lo").expect("initialize error");
+    ///
+    ///         assert_eq!(socket.get_adapter().get_name(), "lo");
+    ///         socket.send_raw_packet(&[0; 60]).expect("send error");
+    ///     }
+    ///
+    ///     assert!(Socket::from_raw_fd(-1, "lo").is_err())
+    /// }
     /// ```
-    pub fn destroy(&self) {
-        #[cfg(target_os = "linux")]
-        {
-            self.destroy_linux()
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn from_raw_fd(fd: i32, interface: &str) -> io::Result<Self> {
+        if fd < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a valid descriptor", fd),
+            ));
         }
-    }
-    #[cfg(target_os = "linux")]
-    fn new_linux(interface: &str) -> io::Result<Self> {
-        let socket: i32 = unsafe {
-            ccs::socket(
-                ccs::AF_PACKET,
-                ccs::SOCK_RAW,
-                (ccs::ETH_P_ALL as u16).to_be() as i32,
-            )
+
+        let adapter: Adapter = match Adapter::get_by_ifname(interface) {
+            Ok(adapter) => adapter,
+            Err(err) => {
+                unsafe { ccs::close(fd) };
+                return Err(err);
+            }
         };
 
-        if socket < 0 {
-            return Err(io::Error::last_os_error());
-        }
+        enable_timestamps_linux(fd);
+        enable_auxdata_linux(fd);
 
-        let adapter = Adapter::get_by_ifname(interface)?;
+        let rx_dropped: u64 = read_rx_dropped(adapter.get_name()).unwrap_or(0);
 
         Ok(Self {
-            socket,
+            socket: fd,
+            mtu: enforced_mtu(&adapter, &SocketOptions::default()),
             interface: adapter,
+            options: SocketOptions::default(),
+            egress_vlan: atomic::AtomicU32::new(0),
+            promiscuous: atomic::AtomicBool::new(false),
+            packet_stats: Mutex::new(CaptureStats::default()),
+            rx_dropped,
         })
     }
-    #[cfg(target_os = "windows")]
-    fn new_windows(interface: &str) -> io::Result<Self> {
-        let id = interface
-            .parse::<u32>()
-            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
-
-        let interface = Adapter::get_by_id(id)?;
-
-        let guid = interface.get_guid();
-        let pcap_interface: String = format!("rpcap://\\Device\\NPF_{}", guid);
-        let pcap_interface: CString = CString::new(pcap_interface)
-            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
-
-        let mut error_buffer: [i8; 256] = [0; 256];
-
-        let adapter: *mut ccs::pcap = unsafe {
-            ccs::pcap_open(
-                pcap_interface.as_ptr(),
-                65535,
-                ccs::PCAP_OPENFLAG_PROMISCUOUS,
-                100,
-                std::ptr::null_mut(),
-                error_buffer.as_mut_ptr(),
-            )
-        };
+    /// Initializes socket on the interface, which has the ipv4 address
+    ///
+    /// Returns InvalidInput error, if there is no such interface
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::net::Ipv4Addr;
+    /// use cursock::*;
+    ///
+    /// let socket = Socket::from_ip(Ipv4Addr::new(192, 168, 1, 50)).expect("initialize error");
+    ///
+    /// println!("{}", socket.get_adapter().get_name())
+    /// ```
+    pub fn from_ip(ip: net::Ipv4Addr) -> io::Result<Self> {
+        let adapter: Adapter = list_interfaces()?
+            .into_iter()
+            .find(|adapter| adapter.get_ipv4() == &Some(ip))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("there isn\'t any interface with {} address", ip),
+                )
+            })?;
 
-        if adapter as usize == 0 {
-            return Err(io::Error::new(
-                io::ErrorKind::Interrupted,
-                format!(
-                    "Can\'t open adapted due to {}",
-                    str_from_cstr(error_buffer.as_ptr())
-                ),
-            ));
+        Self::open(adapter, SocketOptions::default())
+    }
+    /// Sends raw packet, returns count of bytes from the buffer, which were sent
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    /// use cursock::utils::*;
+    ///
+    /// let socket = Socket::new("wlan0").expect("initialize error");
+    /// let buffer = [0; 100];
+    /// let sent: usize = socket.send_raw_packet(&buffer).expect("send error");
+    ///
+    /// assert_eq!(sent, buffer.len())
+    /// ```
+    pub fn send_raw_packet(&self, buffer: &[u8]) -> io::Result<usize> {
+        self.send_raw_packet_with(buffer, None)
+    }
+    /// Sends raw packet, waiting for space in the transmit queue no longer than timeout
+    ///
+    /// Returns TimedOut error, if the frame couldn't be queued in time.
+    /// On windows pcap_inject and on macos bpf write can't be bounded,
+    /// so the frame is sent as with send_raw_packet
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use cursock::*;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    /// let buffer = [0; 100];
+    ///
+    /// socket
+    ///     .send_raw_packet_timeout(&buffer, Duration::from_millis(100))
+    ///     .expect("send error");
+    /// ```
+    pub fn send_raw_packet_timeout(
+        &self,
+        buffer: &[u8],
+        timeout: time::Duration,
+    ) -> io::Result<usize> {
+        self.send_raw_packet_with(buffer, Some(timeout))
+    }
+    /// Sends several frames with as few syscalls as possible, returns how many of them were sent
+    ///
+    /// On linux frames are sent with sendmmsg and on windows with npcap send queue,
+    /// macos bpf device has no batch write, so there they are written one by one.
+    /// Frames are sent in order, if only some of them were sent, their count is returned instead of error,
+    /// error is only returned, if none of them was sent
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new("lo"), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:99").expect("filter error");
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x99]);
+    ///         let frames: Vec<&[u8]> = vec![&frame; 100];
+    ///
+    ///         assert_eq!(writer.send_raw_packets(&frames).expect("send error"), 100);
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         for _ in 0..100 {
+    ///             assert_eq!(reader.read_raw_packet(&mut buffer).expect("read error"), 60)
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn send_raw_packets(&self, buffers: &[&[u8]]) -> io::Result<usize> {
+        if buffers.is_empty() {
+            return Ok(0);
         }
 
-        Ok(Self {
-            adapter: adapter as usize,
-            interface,
-        })
-    }
-    #[cfg(target_os = "linux")]
-    fn read_raw_packet_linux(&self, buffer: &mut [u8]) -> io::Result<()> {
-        let length: isize = unsafe {
-            ccs::recvfrom(
-                self.socket,
-                buffer.as_mut_ptr() as *mut std::os::raw::c_void,
-                buffer.len(),
-                0,
-                std::ptr::null_mut(),
-                std::ptr::null_mut(),
-            )
+        let tagged: Vec<Option<Vec<u8>>> = match self.get_egress_vlan() {
+            Some(vlan_id) => buffers
+                .iter()
+                .map(|buffer| needs_vlan_tag(buffer).then(|| insert_vlan_tag(buffer, vlan_id, 0)))
+                .collect(),
+            None => Vec::new(),
         };
+        let frames: Vec<&[u8]> = buffers
+            .iter()
+            .enumerate()
+            .map(|(i, buffer)| match tagged.get(i) {
+                Some(Some(tagged)) => tagged,
+                _ => *buffer,
+            })
+            .collect();
 
-        if length < 0 {
-            return Err(io::Error::last_os_error());
+        for frame in &frames {
+            self.check_mtu(frame)?;
         }
 
-        Ok(())
-    }
-    #[cfg(target_os = "windows")]
-    fn read_raw_packet_windows(&self, buffer: &mut [u8]) -> io::Result<()> {
-        let mut header: *mut ccs::pcap_pkthdr = std::ptr::null_mut();
-        let mut pkt_data: *const u8 = std::ptr::null();
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let sent: io::Result<usize> = self.send_raw_packets_linux(&frames);
+        #[cfg(target_os = "windows")]
+        let sent: io::Result<usize> = self.send_raw_packets_windows(&frames);
+        #[cfg(target_os = "macos")]
+        let sent: io::Result<usize> = self.send_raw_packets_macos(&frames);
 
-        let result: i32 = unsafe {
-            ccs::pcap_next_ex(self.adapter as *mut ccs::pcap, &mut header, &mut pkt_data)
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        let sent: io::Result<usize> = {
+            let _ = frames;
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
         };
 
-        if result == 0 {
-            return self.read_raw_packet_windows(buffer);
-        } else if result != 1 {
+        sent.inspect(|sent| logger::debug(|| format!("sent {} frames", sent)))
+    }
+    /// Sends count copies of the frame at about pps frames per second, returns how many of them were sent
+    ///
+    /// Sends are scheduled from the start time, so oversleeping doesn't slow the whole run down.
+    /// If a send fails, count of the sent frames is returned, error is only returned if none of them was sent.
+    /// InvalidInput error is returned if pps is zero
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new("lo"), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:93").expect("filter error");
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x93]);
+    ///
+    ///         let start = Instant::now();
+    ///         assert_eq!(writer.send_raw_packet_repeat(&frame, 20, 200).expect("send error"), 20);
+    ///         // the last frame is sent 19 intervals after the first one
+    ///         assert!(start.elapsed() >= Duration::from_millis(95));
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         for _ in 0..20 {
+    ///             assert_eq!(reader.read_raw_packet(&mut buffer).expect("read error"), 60)
+    ///         }
+    ///
+    ///         assert!(writer.send_raw_packet_repeat(&frame, 1, 0).is_err())
+    ///     }
+    /// }
+    /// ```
+    pub fn send_raw_packet_repeat(
+        &self,
+        buffer: &[u8],
+        count: usize,
+        pps: u32,
+    ) -> io::Result<usize> {
+        if pps == 0 {
             return Err(io::Error::new(
-                io::ErrorKind::Interrupted,
-                format!("pcap_next_ex call failed with code {}", result),
+                io::ErrorKind::InvalidInput,
+                "rate must be at least 1 frame per second",
             ));
         }
 
-        let header: &mut ccs::pcap_pkthdr = unsafe { &mut *header };
-
-        let size: usize = if buffer.len() < header.caplen as usize {
-            buffer.len()
-        } else {
-            header.caplen as usize
-        };
+        let start: time::Instant = time::Instant::now();
 
-        memcpy(buffer.as_mut_ptr(), pkt_data, size);
+        for sent in 0..count {
+            let offset: u128 = sent as u128 * 1_000_000_000 / pps as u128;
+            let at: time::Instant = start + time::Duration::from_nanos(offset as u64);
 
-        Ok(())
-    }
-    #[cfg(target_os = "windows")]
-    fn send_raw_packet_windows(&self, buffer: &[u8]) -> io::Result<()> {
-        let length: i32 = unsafe {
-            ccs::pcap_inject(
-                self.adapter as *mut ccs::pcap,
-                buffer.as_ptr() as *const std::os::raw::c_void,
-                buffer.len(),
-            )
-        };
+            let now: time::Instant = time::Instant::now();
+            if at > now {
+                thread::sleep(at - now);
+            }
 
-        if length < 0 {
-            let error: String =
-                unsafe { str_from_cstr(ccs::pcap_geterr(self.adapter as *mut ccs::pcap)) };
+            if let Err(err) = self.send_raw_packet(buffer) {
+                if sent == 0 {
+                    return Err(err);
+                }
 
-            return Err(io::Error::new(
-                io::ErrorKind::Interrupted,
-                format!("can\'t send buffer due to \"{}\"", error),
-            ));
+                logger::debug(|| format!("repeated send stopped after {} frames: {}", sent, err));
+                return Ok(sent);
+            }
         }
 
-        Ok(())
+        Ok(count)
     }
-    #[cfg(target_os = "linux")]
-    fn send_raw_packet_linux(&self, buffer: &[u8]) -> io::Result<()> {
-        let raw_src_mac: [u8; MAC_LEN] = self.interface.get_mac().clone().into();
-        let mut addr: ccs::sockaddr_ll = ccs::sockaddr_ll {
-            sll_family: 0,
-            sll_protocol: 0,
-            sll_ifindex: *self.get_adapter().get_index(),
-            sll_hatype: 0,
-            sll_pkttype: 0,
-            sll_halen: MAC_LEN as u8,
-            sll_addr: [0; 8],
-        };
-        addr.sll_addr[..MAC_LEN].copy_from_slice(&raw_src_mac);
-
-        let addrlen: ccs::SocklenT = std::mem::size_of_val(&addr) as ccs::SocklenT;
+    /// Reads raw packet, can be used for sniffing, returns length of the packet written to the buffer
+    ///
+    /// In non-blocking mode returns WouldBlock error, if there are no packets
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    /// use cursock::utils::*;
+    ///
+    /// let socket = Socket::new("wlan0").expect("initialize error");
+    /// let mut buffer = [0; 1000];
+    /// let length: usize = socket.read_raw_packet(&mut buffer).expect("read error");
+    ///
+    /// println!("{:?}", &buffer[..length])
+    /// ```
+    pub fn read_raw_packet(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let length: io::Result<usize> = self.read_raw_packet_linux(buffer, 0);
+        #[cfg(target_os = "windows")]
+        let length: io::Result<usize> = self
+            .read_raw_packet_windows(buffer, true)
+            .map(|(length, _)| length);
+        #[cfg(target_os = "macos")]
+        let length: io::Result<usize> = self
+            .read_raw_packet_macos(buffer, true)
+            .map(|(length, _)| length);
 
-        let length: isize = unsafe {
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        let length: io::Result<usize> = {
+            let _ = buffer;
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        };
+
+        length.inspect(|length| log_received(*length))
+    }
+    /// Reads raw packet into a new buffer of max bytes, which is truncated to the length of the packet
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new("lo"), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:96").expect("filter error");
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x96]);
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///
+    ///         let packet: Vec<u8> = reader.read_raw_packet_vec(1514).expect("read error");
+    ///
+    ///         assert_eq!(packet, frame)
+    ///     }
+    /// }
+    /// ```
+    pub fn read_raw_packet_vec(&self, max: usize) -> io::Result<Vec<u8>> {
+        let mut buffer: Vec<u8> = vec![0; max];
+
+        let length: usize = self.read_raw_packet(&mut buffer)?;
+        buffer.truncate(length);
+
+        Ok(buffer)
+    }
+    /// Reads raw packet with the time, when it was captured by the kernel or driver
+    ///
+    /// Linux socket is opened with SO_TIMESTAMPNS, timestamps of windows and macos come with the packets.
+    /// If the packet has no timestamp, time of the read is returned as [`Timestamp::Read`]
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new("lo"), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:9e").expect("filter error");
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x9e]);
+    ///
+    ///         let before = SystemTime::now();
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///         std::thread::sleep(Duration::from_millis(50));
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         let (length, timestamp) = reader.read_raw_packet_ts(&mut buffer).expect("read error");
+    ///
+    ///         assert_eq!(length, 60);
+    ///         assert!(timestamp.is_capture());
+    ///         // it's the time of capture, not of the read
+    ///         assert!(timestamp.get_time() >= before);
+    ///         assert!(SystemTime::now().duration_since(timestamp.get_time()).unwrap() >= Duration::from_millis(50))
+    ///     }
+    /// }
+    /// ```
+    pub fn read_raw_packet_ts(&self, buffer: &mut [u8]) -> io::Result<(usize, Timestamp)> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let packet: io::Result<(usize, Option<time::SystemTime>)> =
+            self.read_raw_packet_ts_linux(buffer);
+        #[cfg(target_os = "windows")]
+        let packet: io::Result<(usize, Option<time::SystemTime>)> = self
+            .read_raw_packet_windows(buffer, true)
+            .map(|(length, timestamp)| (length, Some(timestamp)));
+        #[cfg(target_os = "macos")]
+        let packet: io::Result<(usize, Option<time::SystemTime>)> = self
+            .read_raw_packet_macos(buffer, true)
+            .map(|(length, timestamp)| (length, Some(timestamp)));
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        let packet: io::Result<(usize, Option<time::SystemTime>)> = {
+            let _ = buffer;
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        };
+
+        let (length, timestamp) = packet?;
+        log_received(length);
+
+        let timestamp: Timestamp = match timestamp {
+            Some(timestamp) => Timestamp::Capture(timestamp),
+            None => Timestamp::Read(time::SystemTime::now()),
+        };
+
+        Ok((length, timestamp))
+    }
+    /// Reads raw packet with its type, source mac address and ethertype
+    ///
+    /// On linux they are taken from sockaddr_ll, which the kernel returns with the packet.
+    /// Windows and macos don't return it, so the frame header is compared with the mac address of the interface,
+    /// and frames with its source address are treated as outgoing
+    ///
+    /// Linux removes vlan tag from received frames before packet sockets see them,
+    /// so [`PacketInfo::get_vlan`] returns the tag from PACKET_AUXDATA in this case,
+    /// otherwise it's the outer tag of the frame
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new("lo"), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:9d").expect("filter error");
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x9d]);
+    ///         frame[12..14].copy_from_slice(&0x88b5u16.to_be_bytes());
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         let mut types = Vec::new();
+    ///
+    ///         // loopback frames are seen on the way out and on the way in
+    ///         for _ in 0..2 {
+    ///             let (length, info) = reader.read_raw_packet_from(&mut buffer).expect("read error");
+    ///
+    ///             assert_eq!(&buffer[..length], &frame[..]);
+    ///             assert_eq!(info.get_src_mac().to_string(), "2:0:0:0:0:9d");
+    ///             assert_eq!(info.get_protocol(), &0x88b5);
+    ///             types.push(*info.get_pkttype());
+    ///         }
+    ///
+    ///         assert!(types.contains(&PacketType::Outgoing) && types.contains(&PacketType::Host))
+    ///     }
+    /// }
+    /// ```
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let options = SocketOptions::new().ignore_outgoing(true);
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new_with("lo", options), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:99").expect("filter error");
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x99]);
+    ///         frame[12..16].copy_from_slice(&VlanTag::new(100, 3).expect("vlan error").to_bytes());
+    ///         frame[16..18].copy_from_slice(&0x88b5u16.to_be_bytes());
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         let (length, info) = reader.read_raw_packet_from(&mut buffer).expect("read error");
+    ///
+    ///         // received frame has no tag
+    ///         assert_eq!(length, frame.len() - VLAN_HEADER_SIZE);
+    ///         assert_eq!(&buffer[12..14], &0x88b5u16.to_be_bytes());
+    ///
+    ///         let vlan = info.get_vlan().expect("no vlan tag");
+    ///         assert_eq!((vlan.get_vlan_id(), vlan.get_priority()), (&100, &3))
+    ///     }
+    /// }
+    /// ```
+    pub fn read_raw_packet_from(&self, buffer: &mut [u8]) -> io::Result<(usize, PacketInfo)> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let packet: io::Result<(usize, PacketInfo)> = self.read_raw_packet_from_linux(buffer);
+        #[cfg(any(target_os = "windows", target_os = "macos"))]
+        let packet: io::Result<(usize, PacketInfo)> = self
+            .read_raw_packet(buffer)
+            .map(|length| (length, self.classify_frame(&buffer[..length])));
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        let packet: io::Result<(usize, PacketInfo)> = {
+            let _ = buffer;
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        };
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        if let Ok((length, _)) = packet {
+            log_received(length)
+        }
+
+        packet
+    }
+    /// Reads raw packet, waiting for it no longer than timeout, returns TimedOut error on expiry
+    ///
+    /// No threads are spawned, socket is polled on linux and macos and pcap read timeout is used on windows
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let thread_count = || {
+    ///         let status = std::fs::read_to_string("/proc/self/status").unwrap();
+    ///         let line = status.lines().find(|line| line.starts_with("Threads:")).unwrap();
+    ///         line[8..].trim().parse::<usize>().unwrap()
+    ///     };
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let Ok(socket) = Socket::new("lo") {
+    ///         let before: usize = thread_count();
+    ///         let mut buffer = [0; 1514];
+    ///
+    ///         for _ in 0..10000 {
+    ///             match socket.read_raw_packet_timeout(&mut buffer, Duration::ZERO) {
+    ///                 Ok(_) => {}
+    ///                 Err(err) => assert_eq!(err.kind(), std::io::ErrorKind::TimedOut),
+    ///             }
+    ///         }
+    ///
+    ///         assert_eq!(thread_count(), before)
+    ///     }
+    /// }
+    /// ```
+    pub fn read_raw_packet_timeout(
+        &self,
+        buffer: &mut [u8],
+        timeout: time::Duration,
+    ) -> io::Result<usize> {
+        let deadline: time::Instant = time::Instant::now() + timeout;
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        loop {
+            if !poll_socket(self.socket, ccs::POLLIN, deadline)? {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "no packets received until timeout",
+                ));
+            }
+
+            // packet can be taken by another reader between poll and recvfrom
+            match self.read_raw_packet_linux(buffer, ccs::MSG_DONTWAIT) {
+                Ok(0) => continue,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+                length => return length.inspect(|length| log_received(*length)),
+            }
+        }
+        // pcap_next_ex waits no longer than pcap read timeout, which is set on open
+        #[cfg(target_os = "windows")]
+        loop {
+            match self
+                .read_raw_packet_windows(buffer, false)
+                .map(|(length, _)| length)
+            {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    if time::Instant::now() >= deadline {
+                        return Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "no packets received until timeout",
+                        ));
+                    }
+                }
+                length => return length.inspect(|length| log_received(*length)),
+            }
+        }
+        // packets can be left in the buffer from the previous read, so it's checked before polling
+        #[cfg(target_os = "macos")]
+        loop {
+            match self
+                .read_raw_packet_macos(buffer, false)
+                .map(|(length, _)| length)
+            {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    if !poll_socket(self.socket, ccs::POLLIN, deadline)? {
+                        return Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "no packets received until timeout",
+                        ));
+                    }
+                }
+                length => return length.inspect(|length| log_received(*length)),
+            }
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            let _ = (buffer, deadline);
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+    /// Reads raw packet if one is already received, otherwise returns WouldBlock error
+    ///
+    /// Never waits for packets, even if socket is in blocking mode
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::io;
+    /// use cursock::*;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    /// let mut buffer = [0; 1514];
+    ///
+    /// loop {
+    ///     match socket.try_read_raw_packet(&mut buffer) {
+    ///         Ok(length) => println!("{:?}", &buffer[..length]),
+    ///         Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+    ///         Err(err) => panic!("read error: {}", err),
+    ///     }
+    /// }
+    /// ```
+    pub fn try_read_raw_packet(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.try_read(buffer)
+            .inspect(|length| log_received(*length))
+    }
+    /// Discards packets, which are already received, returns how many were discarded
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let options = SocketOptions::new().ignore_outgoing(true);
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new_with("lo", options), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:9c").expect("filter error");
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x9c]);
+    ///         for _ in 0..3 {
+    ///             writer.send_raw_packet(&frame).expect("send error");
+    ///         }
+    ///
+    ///         assert_eq!(reader.flush().expect("flush error"), 3);
+    ///
+    ///         let err = reader.try_read_raw_packet(&mut [0; 1514]).err().expect("packet was left");
+    ///         assert_eq!(err.kind(), io::ErrorKind::WouldBlock)
+    ///     }
+    /// }
+    /// ```
+    pub fn flush(&self) -> io::Result<usize> {
+        // packets are truncated to the buffer, only their count matters
+        let mut buffer: [u8; 64] = [0; 64];
+        let mut count: usize = 0;
+
+        loop {
+            match self.try_read(&mut buffer) {
+                Ok(_) => count += 1,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        logger::debug(|| format!("discarded {} packets", count));
+
+        Ok(count)
+    }
+    /// Waits for a packet no longer than timeout, returns whether it can be read without waiting
+    ///
+    /// Packet isn't read, on linux and macos socket is polled.
+    /// Pcap can only be checked by reading, so on windows the packet is kept until the next read,
+    /// waiting is as precise as pcap read timeout
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let options = SocketOptions::new().ignore_outgoing(true);
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new_with("lo", options), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:9d").expect("filter error");
+    ///
+    ///         assert!(!reader.available(Duration::from_millis(10)).expect("poll error"));
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x9d]);
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///
+    ///         assert!(reader.available(Duration::from_secs(1)).expect("poll error"));
+    ///         // packet is still there
+    ///         assert!(reader.available(Duration::ZERO).expect("poll error"));
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         assert_eq!(reader.try_read_raw_packet(&mut buffer).expect("read error"), 60);
+    ///
+    ///         assert!(!reader.available(Duration::ZERO).expect("poll error"))
+    ///     }
+    /// }
+    /// ```
+    pub fn available(&self, timeout: time::Duration) -> io::Result<bool> {
+        let deadline: time::Instant = time::Instant::now() + timeout;
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            poll_socket(self.socket, ccs::POLLIN, deadline)
+        }
+        // packets can be left in the buffer from the previous read
+        #[cfg(target_os = "macos")]
+        {
+            if !self.lock_bpf_buffer().is_empty() {
+                return Ok(true);
+            }
+
+            poll_socket(self.socket, ccs::POLLIN, deadline)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let mut pending = self.lock_reads();
+
+            loop {
+                if let Some(packet) = self.next_pcap_packet(&mut pending)? {
+                    *pending = Some(packet);
+                    return Ok(true);
+                }
+
+                let timeout: time::Duration =
+                    deadline.saturating_duration_since(time::Instant::now());
+                if timeout.is_zero() {
+                    return Ok(false);
+                }
+
+                // non-blocking pcap returns at once, so the capture event is waited instead of spinning
+                self.wait_pcap_event(timeout)?;
+            }
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            let _ = deadline;
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+    /// Switches socket between blocking and non-blocking mode
+    ///
+    /// In non-blocking mode read_raw_packet returns WouldBlock error instead of waiting for packets
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::io;
+    /// use cursock::*;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    /// socket.set_nonblocking(true).expect("mode error");
+    /// assert!(socket.is_nonblocking().expect("mode error"));
+    ///
+    /// let mut buffer = [0; 1514];
+    /// match socket.read_raw_packet(&mut buffer) {
+    ///     Ok(length) => println!("{:?}", &buffer[..length]),
+    ///     Err(err) if err.kind() == io::ErrorKind::WouldBlock => println!("no packets"),
+    ///     Err(err) => panic!("read error: {}", err),
+    /// }
+    /// ```
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+        {
+            self.set_nonblocking_fcntl(nonblocking)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            self.set_nonblocking_windows(nonblocking)
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            let _ = nonblocking;
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+    /// Checks whether socket is in non-blocking mode
+    pub fn is_nonblocking(&self) -> io::Result<bool> {
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+        {
+            let flags: i32 = unsafe { ccs::fcntl(self.socket, ccs::F_GETFL) };
+            if flags < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(flags & ccs::O_NONBLOCK != 0)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            self.is_nonblocking_windows()
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+
+    pub fn get_src_mac(&self) -> &Mac {
+        self.interface.get_mac()
+    }
+    pub fn get_src_ip(&self) -> Option<&net::Ipv4Addr> {
+        self.interface.get_ipv4().as_ref()
+    }
+    pub fn get_netmask(&self) -> Option<&net::Ipv4Addr> {
+        self.interface.get_netmask().as_ref()
+    }
+    pub fn get_broadcast(&self) -> Option<&net::Ipv4Addr> {
+        self.interface.get_broadcast().as_ref()
+    }
+    /// Returns true if the address is in the network of the interface, so frames to it
+    /// are sent directly, not through the gateway
+    ///
+    /// False is returned, if the interface has no ipv4 address or netmask
+    ///
+    /// # Examples
+    /// ```
+    /// use std::net::Ipv4Addr;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let Ok(socket) = Socket::new("lo") {
+    ///         assert!(socket.is_on_link(&Ipv4Addr::new(127, 1, 2, 3)));
+    ///         assert!(!socket.is_on_link(&Ipv4Addr::new(10, 0, 0, 1)))
+    ///     }
+    /// }
+    /// ```
+    pub fn is_on_link(&self, ip: &net::Ipv4Addr) -> bool {
+        self.interface
+            .get_ipv4_cidr()
+            .is_some_and(|cidr| cidr.contains(ip))
+    }
+    /// Returns ipv6 address of the interface, global one if there are several
+    ///
+    /// All addresses, including link-local ones, are available from [`Adapter::get_ipv6_addresses`]
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    ///
+    /// match socket.get_src_ipv6() {
+    ///     Some(ipv6) => println!("{}", ipv6),
+    ///     None => println!("interface has no ipv6 address"),
+    /// }
+    /// ```
+    pub fn get_src_ipv6(&self) -> Option<&net::Ipv6Addr> {
+        self.interface.get_ipv6().as_ref()
+    }
+    pub fn get_adapter(&self) -> &Adapter {
+        &self.interface
+    }
+    /// Reads current mtu of the interface, see [`Adapter::get_mtu`]
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    /// let mtu = socket.get_mtu().expect("mtu error") as usize;
+    ///
+    /// // room for the ethernet header and a vlan tag
+    /// for packet in socket.packets(mtu + ETH_HEADER_SIZE + 4) {
+    ///     println!("{:?}", packet.expect("read error"))
+    /// }
+    /// ```
+    pub fn get_mtu(&self) -> io::Result<u32> {
+        self.interface.get_mtu()
+    }
+    /// Returns index of the interface, socket was opened on
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    ///
+    /// println!("{} - {}", socket.get_ifindex(), socket.get_interface_name())
+    /// ```
+    pub fn get_ifindex(&self) -> u32 {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            *self.interface.get_index() as u32
+        }
+        #[cfg(any(target_os = "windows", target_os = "macos"))]
+        {
+            *self.interface.get_index()
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            0
+        }
+    }
+    /// Returns name of the interface, socket was opened on, on windows it's the friendly name
+    pub fn get_interface_name(&self) -> &str {
+        self.interface.get_name()
+    }
+    /// Reads duplex, auto-negotiation state and speed of the interface link
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    /// let settings = socket.get_link_settings().expect("link settings error");
+    ///
+    /// println!("{:?} Mb/s, {:?}", settings.get_speed(), settings.get_duplex())
+    /// ```
+    pub fn get_link_settings(&self) -> io::Result<LinkSettings> {
+        self.interface.get_link_settings()
+    }
+    pub fn get_options(&self) -> &SocketOptions {
+        &self.options
+    }
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+    pub(crate) fn raw_socket(&self) -> i32 {
+        self.socket
+    }
+    /// checks whether packets, which were read from bpf device, are left in the buffer
+    #[cfg(target_os = "macos")]
+    pub(crate) fn has_read_packets(&self) -> bool {
+        !self.lock_bpf_buffer().is_empty()
+    }
+    /// drops packets, which were read from bpf device, but weren't taken yet
+    #[cfg(target_os = "macos")]
+    pub(crate) fn discard_read_packets(&self) {
+        let mut bpf_buffer = self.lock_bpf_buffer();
+        bpf_buffer.offset = bpf_buffer.length;
+    }
+    #[cfg(target_os = "windows")]
+    pub(crate) fn raw_adapter(&self) -> *mut ccs::pcap {
+        self.adapter as *mut ccs::pcap
+    }
+    /// held while a packet is in pcap buffer, nothing is left inconsistent by a panic,
+    /// so poisoned lock is still usable
+    #[cfg(target_os = "windows")]
+    pub(crate) fn lock_reads(&self) -> std::sync::MutexGuard<'_, Option<PcapPacket>> {
+        self.read_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    /// takes the pending packet or calls pcap_next_ex once, None is returned if there were no packets
+    #[cfg(target_os = "windows")]
+    pub(crate) fn next_pcap_packet(
+        &self,
+        pending: &mut Option<PcapPacket>,
+    ) -> io::Result<Option<PcapPacket>> {
+        if let Some(packet) = pending.take() {
+            return Ok(Some(packet));
+        }
+
+        let adapter: *mut ccs::pcap = self.adapter as *mut ccs::pcap;
+
+        let mut header: *mut ccs::pcap_pkthdr = std::ptr::null_mut();
+        let mut data: *const u8 = std::ptr::null();
+
+        let result: i32 = unsafe { ccs::pcap_next_ex(adapter, &mut header, &mut data) };
+
+        match result {
+            1 => Ok(Some(PcapPacket { header, data })),
+            0 => Ok(None),
+            _ => {
+                let error: String = unsafe { str_from_cstr(ccs::pcap_geterr(adapter)) };
+
+                Err(io::Error::new(
+                    io::ErrorKind::Interrupted,
+                    format!("pcap_next_ex call failed with code {} ({})", result, error),
+                ))
+            }
+        }
+    }
+    /// waits until pcap signals captured packets or timeout expires,
+    /// event is also signaled when pcap read timeout expires
+    #[cfg(target_os = "windows")]
+    fn wait_pcap_event(&self, timeout: time::Duration) -> io::Result<()> {
+        let event = unsafe { ccs::pcap_getevent(self.adapter as *mut ccs::pcap) };
+
+        // rounded up, so waiting doesn't end before timeout, and kept below INFINITE
+        let millis: u32 = timeout
+            .as_nanos()
+            .div_ceil(1_000_000)
+            .min(ccs::INFINITE as u128 - 1) as u32;
+
+        if unsafe { ccs::WaitForSingleObject(event, millis) } == ccs::WAIT_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+    /// Sets VLAN which outgoing frames are tagged with, None sends frames untagged
+    ///
+    /// Neither AF_PACKET sendto nor pcap_inject let you ask the NIC to tag a single frame,
+    /// so an 802.1Q header is inserted into every untagged ethernet frame before it's sent.
+    /// Frames, which already carry 802.1Q or 802.1ad tag, are sent unchanged.
+    /// Result on the wire is the same as with hardware tagging,
+    /// but the interface must be the parent one, not a VLAN subinterface (e.g. eth0, not eth0.100),
+    /// otherwise the kernel tags frames again
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    ///
+    /// socket.set_egress_vlan(Some(100)).expect("vlan error");
+    /// socket.send_raw_packet(&[0; 60]).expect("send error"); // leaves interface with vlan 100 tag
+    ///
+    /// socket.set_egress_vlan(None).expect("vlan error");
+    /// ```
+    pub fn set_egress_vlan(&self, vlan_id: Option<u16>) -> io::Result<()> {
+        let value: u32 = match vlan_id {
+            Some(vlan_id) if !(1..=4094).contains(&vlan_id) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("vlan id must be in range 1..=4094, got {}", vlan_id),
+                ))
+            }
+            Some(vlan_id) => VLAN_PRESENT | vlan_id as u32,
+            None => 0,
+        };
+
+        self.egress_vlan.store(value, atomic::Ordering::Relaxed);
+
+        Ok(())
+    }
+    /// Returns VLAN which outgoing frames are tagged with
+    pub fn get_egress_vlan(&self) -> Option<u16> {
+        let value: u32 = self.egress_vlan.load(atomic::Ordering::Relaxed);
+
+        match value & VLAN_PRESENT != 0 {
+            true => Some(value as u16),
+            false => None,
+        }
+    }
+    /// Enables or disables promiscuous mode, so frames addressed to other hosts are received too
+    ///
+    /// On linux it's a membership of the socket, so the interface leaves promiscuous mode,
+    /// when the socket is closed, unless other sockets still need it.
+    /// Npcap only applies it when the adapter is activated and macos bpf device can't leave it,
+    /// so there it returns Unsupported error and the socket has to be reopened with [`SocketOptions::promiscuous`]
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let flags = || {
+    ///         let flags = std::fs::read_to_string("/sys/class/net/lo/flags").unwrap();
+    ///         u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16).unwrap()
+    ///     };
+    ///     const IFF_PROMISC: u32 = 0x100;
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let Ok(socket) = Socket::new_with("lo", SocketOptions::new().promiscuous(true)) {
+    ///         assert!(socket.is_promiscuous());
+    ///         assert_ne!(flags() & IFF_PROMISC, 0);
+    ///
+    ///         socket.set_promiscuous(false).expect("promiscuous mode error");
+    ///
+    ///         assert!(!socket.is_promiscuous());
+    ///         assert_eq!(flags() & IFF_PROMISC, 0)
+    ///     }
+    /// }
+    /// ```
+    pub fn set_promiscuous(&self, enable: bool) -> io::Result<()> {
+        if self.promiscuous.swap(enable, atomic::Ordering::SeqCst) == enable {
+            return Ok(());
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let result: io::Result<()> =
+            set_promiscuous_linux(self.socket, *self.interface.get_index(), enable);
+        #[cfg(target_os = "windows")]
+        let result: io::Result<()> = Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "npcap applies promiscuous mode only on activation, reopen the socket with SocketOptions::promiscuous",
+        ));
+        #[cfg(target_os = "macos")]
+        let result: io::Result<()> = match enable {
+            true => {
+                let result: i32 = unsafe { ccs::ioctl(self.socket, ccs::BIOCPROMISC) };
+                match result < 0 {
+                    true => Err(io::Error::last_os_error()),
+                    false => Ok(()),
+                }
+            }
+            false => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "bpf device can't leave promiscuous mode, reopen the socket without SocketOptions::promiscuous",
+            )),
+        };
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        let result: io::Result<()> = Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not supported yet!", std::env::consts::OS),
+        ));
+
+        if result.is_err() {
+            self.promiscuous.store(!enable, atomic::Ordering::SeqCst);
+        }
+
+        result
+    }
+    /// Checks whether socket is in promiscuous mode
+    ///
+    /// Only the socket, which enabled the mode, reports it, see [`Socket::try_clone`]
+    pub fn is_promiscuous(&self) -> bool {
+        self.promiscuous.load(atomic::Ordering::SeqCst)
+    }
+    /// Sets size of the buffer, which received frames are queued in until they are read
+    ///
+    /// Bigger buffer lets the socket survive bursts of traffic without dropping frames.
+    /// On linux kernel doubles the value for bookkeeping and caps it with net.core.rmem_max,
+    /// so [`Socket::get_recv_buffer_size`] can return different size
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let Ok(socket) = Socket::new("lo") {
+    ///         socket.set_recv_buffer_size(64 * 1024).expect("buffer size error");
+    ///
+    ///         assert!(socket.get_recv_buffer_size().expect("buffer size error") >= 64 * 1024);
+    ///
+    ///         assert!(socket.set_recv_buffer_size(usize::MAX).is_err())
+    ///     }
+    /// }
+    /// ```
+    pub fn set_recv_buffer_size(&self, bytes: usize) -> io::Result<()> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            set_recv_buffer_size_linux(self.socket, bytes)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let dim: i32 = i32::try_from(bytes).map_err(|_| invalid_buffer_size(bytes))?;
+
+            if unsafe { ccs::pcap_setbuff(self.raw_adapter(), dim) } != 0 {
+                return Err(io::Error::other(format!(
+                    "can\'t set buffer size due to {}",
+                    unsafe { str_from_cstr(ccs::pcap_geterr(self.raw_adapter())) }
+                )));
+            }
+
+            self.recv_buffer_size
+                .store(bytes, atomic::Ordering::Relaxed);
+
+            Ok(())
+        }
+        #[cfg(target_os = "macos")]
+        {
+            let _ = bytes;
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "bpf buffer size can only be set before opening, use SocketOptions::recv_buffer_size",
+            ))
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            let _ = bytes;
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+    /// Returns size of receive buffer
+    ///
+    /// On windows it's the last size set, since npcap can't report it,
+    /// on macos it's the size of bpf buffer, which every read takes packets from
+    pub fn get_recv_buffer_size(&self) -> io::Result<usize> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let mut size: i32 = 0;
+            let mut size_len: ccs::SocklenT = std::mem::size_of::<i32>() as ccs::SocklenT;
+
+            let result: i32 = unsafe {
+                ccs::getsockopt(
+                    self.socket,
+                    ccs::SOL_SOCKET,
+                    ccs::SO_RCVBUF,
+                    &mut size as *mut i32 as *mut std::os::raw::c_void,
+                    &mut size_len,
+                )
+            };
+
+            if result < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(size as usize)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            Ok(self.recv_buffer_size.load(atomic::Ordering::Relaxed))
+        }
+        #[cfg(target_os = "macos")]
+        {
+            Ok(self.lock_bpf_buffer().data.len())
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+
+    /// Sets socket option, which isn't wrapped by the socket, value is passed to setsockopt as is
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let Ok(socket) = Socket::new("lo") {
+    ///         socket
+    ///             .set_sockopt(ccs::SOL_SOCKET, ccs::SO_RCVBUF, &65536i32.to_ne_bytes())
+    ///             .expect("sockopt error");
+    ///
+    ///         let value = socket
+    ///             .get_sockopt(ccs::SOL_SOCKET, ccs::SO_RCVBUF, 4)
+    ///             .expect("sockopt error");
+    ///         assert!(i32::from_ne_bytes(value[..4].try_into().unwrap()) >= 65536);
+    ///
+    ///         assert!(socket.set_sockopt(ccs::SOL_PACKET, -1, &[0; 4]).is_err())
+    ///     }
+    /// }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_sockopt(&self, level: i32, name: i32, value: &[u8]) -> io::Result<()> {
+        let result: i32 = unsafe {
+            ccs::setsockopt(
+                self.socket,
+                level,
+                name,
+                value.as_ptr() as *const std::os::raw::c_void,
+                value.len() as ccs::SocklenT,
+            )
+        };
+
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+    /// Returns value of socket option, which is at most max_len bytes long
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn get_sockopt(&self, level: i32, name: i32, max_len: usize) -> io::Result<Vec<u8>> {
+        let mut value: Vec<u8> = vec![0; max_len];
+        let mut value_len: ccs::SocklenT = max_len as ccs::SocklenT;
+
+        let result: i32 = unsafe {
+            ccs::getsockopt(
+                self.socket,
+                level,
+                name,
+                value.as_mut_ptr() as *mut std::os::raw::c_void,
+                &mut value_len,
+            )
+        };
+
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        value.truncate(value_len as usize);
+
+        Ok(value)
+    }
+
+    /// Returns packet counters since the socket was opened
+    ///
+    /// Received packets are the ones accepted by the filter, including dropped ones.
+    /// On linux kernel counters are taken with PACKET_STATISTICS and interface drops are
+    /// rx_dropped of the interface since the socket was opened, which counts packets of every socket.
+    /// Pcap counters are used on windows and BIOCGSTATS on macos, where interface drops are unknown
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let options = SocketOptions::new().ignore_outgoing(true);
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new_with("lo", options), Socket::new("lo")) {
+    ///         reader.set_filter("ether src 02:00:00:00:00:9f").expect("filter error");
+    ///         reader.set_recv_buffer_size(4096).expect("buffer size error");
+    ///
+    ///         let mut frame = vec![0; 60];
+    ///         frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x9f]);
+    ///
+    ///         // nothing is read, so most of the frames don't fit into the buffer
+    ///         for _ in 0..1000 {
+    ///             writer.send_raw_packet(&frame).expect("send error");
+    ///         }
+    ///
+    ///         let stats = reader.stats().expect("stats error");
+    ///
+    ///         assert_eq!(stats.get_received(), &1000);
+    ///         assert!(*stats.get_dropped_by_kernel() > 0);
+    ///
+    ///         // counters are cumulative
+    ///         writer.send_raw_packet(&frame).expect("send error");
+    ///
+    ///         assert_eq!(reader.stats().expect("stats error").get_received(), &1001)
+    ///     }
+    /// }
+    /// ```
+    pub fn stats(&self) -> io::Result<CaptureStats> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let mut stats: ccs::tpacket_stats = ccs::tpacket_stats {
+                tp_packets: 0,
+                tp_drops: 0,
+            };
+            let mut stats_len: ccs::SocklenT =
+                std::mem::size_of::<ccs::tpacket_stats>() as ccs::SocklenT;
+
+            let mut total = self
+                .packet_stats
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+            let result: i32 = unsafe {
+                ccs::getsockopt(
+                    self.socket,
+                    ccs::SOL_PACKET,
+                    ccs::PACKET_STATISTICS,
+                    &mut stats as *mut ccs::tpacket_stats as *mut std::os::raw::c_void,
+                    &mut stats_len,
+                )
+            };
+
+            if result < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            // kernel counts dropped packets as received too
+            total.received += stats.tp_packets as u64;
+            total.dropped_by_kernel += stats.tp_drops as u64;
+            total.dropped_by_interface = read_rx_dropped(self.interface.get_name())
+                .map_or(0, |rx_dropped| rx_dropped.saturating_sub(self.rx_dropped));
+
+            Ok(*total)
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let mut stats: ccs::pcap_stat = ccs::pcap_stat {
+                ps_recv: 0,
+                ps_drop: 0,
+                ps_ifdrop: 0,
+            };
+
+            if unsafe { ccs::pcap_stats(self.adapter as *mut ccs::pcap, &mut stats) } < 0 {
+                let error: String =
+                    unsafe { str_from_cstr(ccs::pcap_geterr(self.adapter as *mut ccs::pcap)) };
+
+                return Err(io::Error::new(
+                    io::ErrorKind::Interrupted,
+                    format!("can\'t get statistics due to \"{}\"", error),
+                ));
+            }
+
+            Ok(CaptureStats {
+                received: stats.ps_recv as u64,
+                dropped_by_kernel: stats.ps_drop as u64,
+                dropped_by_interface: stats.ps_ifdrop as u64,
+            })
+        }
+        #[cfg(target_os = "macos")]
+        {
+            let mut stats: ccs::bpf_stat = ccs::bpf_stat {
+                bs_recv: 0,
+                bs_drop: 0,
+            };
+
+            bpf_ioctl(self.socket, ccs::BIOCGSTATS, &mut stats)?;
+
+            Ok(CaptureStats {
+                received: stats.bs_recv as u64,
+                dropped_by_kernel: stats.bs_drop as u64,
+                dropped_by_interface: 0,
+            })
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+    /// Creates a new socket on the same interface, which can be moved to another thread
+    ///
+    /// On linux and macos descriptor is duplicated, so both sockets share received frames,
+    /// attached filter and promiscuous mode, each frame is read by only one of them.
+    /// Promiscuous mode stays owned by the socket, which enabled it, so the clone reports it as disabled
+    /// and can't turn it off for the original one. On linux promiscuous memberships are counted,
+    /// so the interface leaves the mode when every socket, which enabled it, disables it or every descriptor is closed.
+    /// Pcap handle can't be duplicated, so on windows the adapter is opened again with the same options
+    /// and filter has to be set on the new socket
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let Ok(reader) = Socket::new("lo") {
+    ///         reader.set_filter("ether src 02:00:00:00:00:94").expect("filter error");
+    ///
+    ///         let writer = reader.try_clone().expect("clone error");
+    ///
+    ///         let sender = std::thread::spawn(move || {
+    ///             let mut frame = vec![0; 60];
+    ///             frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x94]);
+    ///             writer.send_raw_packet(&frame).expect("send error");
+    ///         });
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         reader.read_raw_packet(&mut buffer).expect("read error");
+    ///
+    ///         assert_eq!(buffer[11], 0x94);
+    ///
+    ///         sender.join().unwrap()
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let flags = || {
+    ///         let flags = std::fs::read_to_string("/sys/class/net/lo/flags").unwrap();
+    ///         u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16).unwrap()
+    ///     };
+    ///     const IFF_PROMISC: u32 = 0x100;
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let Ok(socket) = Socket::new_with("lo", SocketOptions::new().promiscuous(true)) {
+    ///         let clone = socket.try_clone().expect("clone error");
+    ///         assert!(!clone.is_promiscuous());
+    ///
+    ///         // disabling isn't needed by the clone, so the original keeps the mode
+    ///         clone.set_promiscuous(false).expect("promiscuous mode error");
+    ///         assert_ne!(flags() & IFF_PROMISC, 0);
+    ///
+    ///         clone.set_promiscuous(true).expect("promiscuous mode error");
+    ///         socket.set_promiscuous(false).expect("promiscuous mode error");
+    ///         // clone still holds its own membership
+    ///         assert_ne!(flags() & IFF_PROMISC, 0);
+    ///
+    ///         clone.set_promiscuous(false).expect("promiscuous mode error");
+    ///         assert_eq!(flags() & IFF_PROMISC, 0)
+    ///     }
+    /// }
+    /// ```
+    pub fn try_clone(&self) -> io::Result<Self> {
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+        {
+            let socket: i32 = unsafe { ccs::fcntl(self.socket, ccs::F_DUPFD_CLOEXEC, 0) };
+            if socket < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(Self {
+                socket,
+                #[cfg(target_os = "macos")]
+                bpf_buffer: Mutex::new(BpfBuffer {
+                    data: vec![0; self.lock_bpf_buffer().data.len()],
+                    offset: 0,
+                    length: 0,
+                }),
+                interface: self.interface.clone(),
+                options: self.options.clone(),
+                mtu: self.mtu,
+                egress_vlan: atomic::AtomicU32::new(
+                    self.egress_vlan.load(atomic::Ordering::Relaxed),
+                ),
+                // mode is owned by the socket, which enabled it
+                promiscuous: atomic::AtomicBool::new(false),
+                // descriptors share kernel counters, so they are split between the sockets, which read them
+                #[cfg(any(target_os = "linux", target_os = "android"))]
+                packet_stats: Mutex::new(CaptureStats::default()),
+                #[cfg(any(target_os = "linux", target_os = "android"))]
+                rx_dropped: read_rx_dropped(self.interface.get_name()).unwrap_or(0),
+            })
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let socket: Self = Self::open(self.interface.clone(), self.options.clone())?;
+            socket.egress_vlan.store(
+                self.egress_vlan.load(atomic::Ordering::Relaxed),
+                atomic::Ordering::Relaxed,
+            );
+
+            Ok(socket)
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+    /// Destroys socket structure
+    ///
+    /// Socket is also closed when it's dropped, so calling destroy is optional
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     let fd_count = || std::fs::read_dir("/proc/self/fd").unwrap().count();
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let Ok(socket) = Socket::new("lo") {
+    ///         socket.destroy();
+    ///
+    ///         let before: usize = fd_count();
+    ///
+    ///         for i in 0..2000 {
+    ///             let socket = Socket::new("lo").expect("initialize error");
+    ///             if i % 2 == 0 {
+    ///                 socket.destroy()
+    ///             }
+    ///         }
+    ///
+    ///         assert_eq!(fd_count(), before)
+    ///     }
+    /// }
+    /// ```
+    pub fn destroy(self) {
+        drop(self)
+    }
+    fn open(adapter: Adapter, options: SocketOptions) -> io::Result<Self> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let socket: io::Result<Self> = Self::open_linux(adapter, options);
+        #[cfg(target_os = "windows")]
+        let socket: io::Result<Self> = Self::open_windows(adapter, options);
+        #[cfg(target_os = "macos")]
+        let socket: io::Result<Self> = Self::open_macos(adapter, options);
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        let socket: io::Result<Self> = {
+            let _ = (adapter, options);
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        };
+
+        // npcap applies promiscuous mode on activation, elsewhere it's enabled after opening
+        #[cfg(not(target_os = "windows"))]
+        let socket: io::Result<Self> = socket.and_then(|socket| {
+            if *socket.options.get_promiscuous() {
+                socket.set_promiscuous(true)?;
+            }
+
+            Ok(socket)
+        });
+
+        let socket: io::Result<Self> = socket.and_then(|socket| {
+            if *socket.options.get_ignore_outgoing() {
+                socket.ignore_outgoing()?;
+            }
+
+            Ok(socket)
+        });
+
+        // packet socket is opened with the protocol, other backends filter frames
+        let socket: io::Result<Self> = socket.and_then(|socket| {
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            let protocol: Option<String> = socket
+                .options
+                .get_protocol()
+                .map(|protocol| format!("ether proto {}", protocol));
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            let protocol: Option<String> = None;
+
+            let filter: Option<String> = match (protocol, socket.options.get_filter()) {
+                (Some(protocol), Some(filter)) => Some(format!("{} and ({})", protocol, filter)),
+                (protocol, filter) => protocol.or_else(|| filter.clone()),
+            };
+
+            if let Some(filter) = filter {
+                socket.set_filter(&filter)?;
+            }
+            if *socket.options.get_nonblocking() {
+                socket.set_nonblocking(true)?;
+            }
+
+            Ok(socket)
+        });
+
+        socket.inspect(|socket| logger::debug(|| format!("opened socket on {}", socket.interface)))
+    }
+    fn ignore_outgoing(&self) -> io::Result<()> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let ignore: i32 = 1;
+
+            let result: i32 = unsafe {
+                ccs::setsockopt(
+                    self.socket,
+                    ccs::SOL_PACKET,
+                    ccs::PACKET_IGNORE_OUTGOING,
+                    &ignore as *const i32 as *const std::os::raw::c_void,
+                    std::mem::size_of::<i32>() as ccs::SocklenT,
+                )
+            };
+
+            if result < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(())
+        }
+        #[cfg(target_os = "windows")]
+        {
+            if unsafe { ccs::pcap_setdirection(self.raw_adapter(), ccs::PCAP_D_IN) } < 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("can\'t ignore outgoing frames due to {}", unsafe {
+                        str_from_cstr(ccs::pcap_geterr(self.raw_adapter()))
+                    }),
+                ));
+            }
+
+            Ok(())
+        }
+        #[cfg(target_os = "macos")]
+        {
+            let mut see_sent: u32 = 0;
+            bpf_ioctl(self.socket, ccs::BIOCSSEESENT, &mut see_sent)
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        }
+    }
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn open_linux(adapter: Adapter, options: SocketOptions) -> io::Result<Self> {
+        let protocol: u16 = options.get_protocol().unwrap_or(ccs::ETH_P_ALL as u16);
+
+        // socket without protocol receives nothing, so no frames from other interfaces
+        // are queued before it's bound, bind sets the protocol then
+        let socket_protocol: u16 = match options.get_bind_interface() {
+            true => 0,
+            false => protocol,
+        };
+
+        let socket: i32 = open_cloexec_socket(
+            ccs::AF_PACKET,
+            ccs::SOCK_RAW,
+            socket_protocol.to_be() as i32,
+        )
+        .map_err(permission_hint)?;
+
+        if let Some(bytes) = options.get_recv_buffer_size() {
+            if let Err(err) = set_recv_buffer_size_linux(socket, *bytes) {
+                unsafe { ccs::close(socket) };
+                return Err(err);
+            }
+        }
+
+        enable_timestamps_linux(socket);
+        enable_auxdata_linux(socket);
+
+        if *options.get_bind_interface() {
+            if let Err(err) = bind_linux(socket, *adapter.get_index(), protocol) {
+                unsafe { ccs::close(socket) };
+                return Err(err);
+            }
+        }
+
+        let rx_dropped: u64 = read_rx_dropped(adapter.get_name()).unwrap_or(0);
+
+        Ok(Self {
+            socket,
+            mtu: enforced_mtu(&adapter, &options),
+            interface: adapter,
+            options,
+            egress_vlan: atomic::AtomicU32::new(0),
+            promiscuous: atomic::AtomicBool::new(false),
+            packet_stats: Mutex::new(CaptureStats::default()),
+            rx_dropped,
+        })
+    }
+    #[cfg(target_os = "windows")]
+    fn open_windows(interface: Adapter, options: SocketOptions) -> io::Result<Self> {
+        let guid = interface.get_guid();
+        let pcap_interface: String = format!("\\Device\\NPF_{}", guid);
+        let pcap_interface: CString = CString::new(pcap_interface)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+
+        let mut error_buffer: [i8; 256] = [0; 256];
+
+        let adapter: *mut ccs::pcap =
+            unsafe { ccs::pcap_create(pcap_interface.as_ptr(), error_buffer.as_mut_ptr()) };
+
+        if adapter as usize == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                format!(
+                    "Can\'t open adapted due to {}",
+                    str_from_cstr(error_buffer.as_ptr())
+                ),
+            ));
+        }
+
+        let buffer_size: usize = options
+            .get_recv_buffer_size()
+            .unwrap_or(PCAP_DEFAULT_BUFFER_SIZE);
+        let pcap_buffer_size: i32 = match i32::try_from(buffer_size) {
+            Ok(buffer_size) => buffer_size,
+            Err(_) => {
+                unsafe { ccs::pcap_close(adapter) };
+                return Err(invalid_buffer_size(buffer_size));
+            }
+        };
+
+        let precision: i32 = match options.get_tstamp_precision() {
+            TimestampPrecision::Micro => ccs::PCAP_TSTAMP_PRECISION_MICRO,
+            TimestampPrecision::Nano => ccs::PCAP_TSTAMP_PRECISION_NANO,
+        };
+
+        // pcap_activate reports errors with negative values, positive values are just warnings
+        let result: i32 = unsafe {
+            ccs::pcap_set_snaplen(adapter, 65535);
+            ccs::pcap_set_promisc(adapter, *options.get_promiscuous() as i32);
+            ccs::pcap_set_timeout(adapter, 100);
+            ccs::pcap_set_buffer_size(adapter, pcap_buffer_size);
+
+            if ccs::pcap_set_tstamp_precision(adapter, precision) != 0 {
+                ccs::pcap_close(adapter);
+
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "{:?} timestamp precision is not supported by the adapter",
+                        options.get_tstamp_precision()
+                    ),
+                ));
+            }
+
+            ccs::pcap_activate(adapter)
+        };
+
+        if result < 0 {
+            let error: String = unsafe { str_from_cstr(ccs::pcap_geterr(adapter)) };
+            unsafe { ccs::pcap_close(adapter) };
+
+            let kind: io::ErrorKind = match result {
+                ccs::PCAP_ERROR_PERM_DENIED => io::ErrorKind::PermissionDenied,
+                _ => io::ErrorKind::Interrupted,
+            };
+
+            return Err(permission_hint(io::Error::new(
+                kind,
+                format!("Can\'t activate adapter due to {}", error),
+            )));
+        }
+
+        Ok(Self {
+            adapter: adapter as usize,
+            mtu: enforced_mtu(&interface, &options),
+            interface,
+            egress_vlan: atomic::AtomicU32::new(0),
+            promiscuous: atomic::AtomicBool::new(*options.get_promiscuous()),
+            recv_buffer_size: atomic::AtomicUsize::new(buffer_size),
+            read_lock: Mutex::new(None),
+            options,
+        })
+    }
+    /// reads packet without waiting, it isn't logged
+    fn try_read(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let length: io::Result<usize> = self.read_raw_packet_linux(buffer, ccs::MSG_DONTWAIT);
+        #[cfg(target_os = "windows")]
+        let length: io::Result<usize> = self
+            .read_raw_packet_windows(buffer, false)
+            .map(|(length, _)| length);
+        #[cfg(target_os = "macos")]
+        let length: io::Result<usize> = self
+            .read_raw_packet_macos(buffer, false)
+            .map(|(length, _)| length);
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        let length: io::Result<usize> = {
+            let _ = buffer;
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        };
+
+        match length {
+            // nothing was read, so it's not a packet
+            Ok(0) => Err(io::Error::from(io::ErrorKind::WouldBlock)),
+            length => length,
+        }
+    }
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn read_raw_packet_linux(&self, buffer: &mut [u8], flags: i32) -> io::Result<usize> {
+        let length: isize = unsafe {
+            ccs::recvfrom(
+                self.socket,
+                buffer.as_mut_ptr() as *mut std::os::raw::c_void,
+                buffer.len(),
+                flags,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+
+        if length < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(length as usize)
+    }
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn read_raw_packet_from_linux(&self, buffer: &mut [u8]) -> io::Result<(usize, PacketInfo)> {
+        let mut addr: ccs::sockaddr_ll = ccs::sockaddr_ll {
+            sll_family: 0,
+            sll_protocol: 0,
+            sll_ifindex: 0,
+            sll_hatype: 0,
+            sll_pkttype: 0,
+            sll_halen: 0,
+            sll_addr: [0; 8],
+        };
+        let mut iovec: ccs::iovec = ccs::iovec {
+            iov_base: buffer.as_mut_ptr() as *mut std::os::raw::c_void,
+            iov_len: buffer.len(),
+        };
+        // aligned for cmsghdr, fits timestamp and auxdata
+        let mut control: [u64; 12] = [0; 12];
+
+        let mut message: ccs::msghdr = ccs::msghdr {
+            msg_name: &mut addr as *mut ccs::sockaddr_ll as *mut std::os::raw::c_void,
+            msg_namelen: std::mem::size_of::<ccs::sockaddr_ll>() as ccs::SocklenT,
+            msg_iov: &mut iovec,
+            msg_iovlen: 1,
+            msg_control: control.as_mut_ptr() as *mut std::os::raw::c_void,
+            msg_controllen: std::mem::size_of_val(&control),
+            msg_flags: 0,
+        };
+
+        let length: isize = unsafe { ccs::recvmsg(self.socket, &mut message, 0) };
+
+        if length < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let control: &[u8] = unsafe {
+            std::slice::from_raw_parts(control.as_ptr() as *const u8, message.msg_controllen)
+        };
+        let length: usize = length as usize;
+
+        let mut src_mac: [u8; MAC_LEN] = [0; MAC_LEN];
+        let mac_len: usize = (addr.sll_halen as usize).min(MAC_LEN);
+        src_mac[..mac_len].copy_from_slice(&addr.sll_addr[..mac_len]);
+
+        let info: PacketInfo = PacketInfo {
+            pkttype: match addr.sll_pkttype {
+                ccs::PACKET_HOST => PacketType::Host,
+                ccs::PACKET_BROADCAST => PacketType::Broadcast,
+                ccs::PACKET_MULTICAST => PacketType::Multicast,
+                ccs::PACKET_OTHERHOST => PacketType::OtherHost,
+                ccs::PACKET_OUTGOING => PacketType::Outgoing,
+                pkttype => PacketType::Other(pkttype),
+            },
+            src_mac: Mac::from(src_mac),
+            protocol: u16::from_be(addr.sll_protocol),
+            vlan: find_stripped_vlan(control).or_else(|| frame_vlan(&buffer[..length])),
+        };
+
+        Ok((length, info))
+    }
+    /// packet type is guessed from the addresses of the frame
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    fn classify_frame(&self, frame: &[u8]) -> PacketInfo {
+        let (header, _) = match EthernetHeader::parse(frame) {
+            Ok(header) => header,
+            Err(_) => {
+                return PacketInfo {
+                    pkttype: PacketType::Other(0),
+                    src_mac: Mac::ZERO,
+                    protocol: 0,
+                    vlan: None,
+                }
+            }
+        };
+
+        let pkttype: PacketType = match header.get_dst() {
+            _ if header.get_src() == self.get_src_mac() => PacketType::Outgoing,
+            dst if dst.is_broadcast() => PacketType::Broadcast,
+            dst if dst.is_multicast() => PacketType::Multicast,
+            dst if dst == self.get_src_mac() => PacketType::Host,
+            _ => PacketType::OtherHost,
+        };
+
+        PacketInfo {
+            pkttype,
+            src_mac: *header.get_src(),
+            protocol: *header.get_ethertype(),
+            vlan: frame_vlan(frame),
+        }
+    }
+    /// reads packet with recvmsg, timestamp is taken from SCM_TIMESTAMPNS control message
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn read_raw_packet_ts_linux(
+        &self,
+        buffer: &mut [u8],
+    ) -> io::Result<(usize, Option<time::SystemTime>)> {
+        let mut iovec: ccs::iovec = ccs::iovec {
+            iov_base: buffer.as_mut_ptr() as *mut std::os::raw::c_void,
+            iov_len: buffer.len(),
+        };
+        // aligned for cmsghdr, fits timestamp and auxdata
+        let mut control: [u64; 12] = [0; 12];
+
+        let mut message: ccs::msghdr = ccs::msghdr {
+            msg_name: std::ptr::null_mut(),
+            msg_namelen: 0,
+            msg_iov: &mut iovec,
+            msg_iovlen: 1,
+            msg_control: control.as_mut_ptr() as *mut std::os::raw::c_void,
+            msg_controllen: std::mem::size_of_val(&control),
+            msg_flags: 0,
+        };
+
+        let length: isize = unsafe { ccs::recvmsg(self.socket, &mut message, 0) };
+        if length < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let control: &[u8] = unsafe {
+            std::slice::from_raw_parts(control.as_ptr() as *const u8, message.msg_controllen)
+        };
+
+        Ok((length as usize, find_timestamp(control)))
+    }
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+    fn set_nonblocking_fcntl(&self, nonblocking: bool) -> io::Result<()> {
+        let flags: i32 = unsafe { ccs::fcntl(self.socket, ccs::F_GETFL) };
+        if flags < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let flags: i32 = match nonblocking {
+            true => flags | ccs::O_NONBLOCK,
+            false => flags & !ccs::O_NONBLOCK,
+        };
+
+        if unsafe { ccs::fcntl(self.socket, ccs::F_SETFL, flags) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+    /// pcap_next_ex returns 0 when its read timeout expires or in non-blocking mode,
+    /// with wait it's retried, unless socket is in non-blocking mode
+    #[cfg(target_os = "windows")]
+    fn read_raw_packet_windows(
+        &self,
+        buffer: &mut [u8],
+        wait: bool,
+    ) -> io::Result<(usize, time::SystemTime)> {
+        let mut pending = self.lock_reads();
+
+        let packet: PcapPacket = loop {
+            match self.next_pcap_packet(&mut pending)? {
+                Some(packet) => break packet,
+                None if wait && !self.is_nonblocking_windows()? => continue,
+                None => return Err(io::Error::from(io::ErrorKind::WouldBlock)),
+            }
+        };
+
+        let header: &ccs::pcap_pkthdr = unsafe { &*packet.header };
+        let pkt_data: *const u8 = packet.data;
+
+        let size: usize = if buffer.len() < header.caplen as usize {
+            buffer.len()
+        } else {
+            header.caplen as usize
+        };
+
+        memcpy(buffer.as_mut_ptr(), pkt_data, size);
+
+        Ok((size, self.pcap_timestamp(header)))
+    }
+    /// fraction is in nanoseconds, if nanosecond precision was requested
+    #[cfg(target_os = "windows")]
+    pub(crate) fn pcap_timestamp(&self, header: &ccs::pcap_pkthdr) -> time::SystemTime {
+        let fraction: u32 = match self.options.get_tstamp_precision() {
+            TimestampPrecision::Nano => header.ts.tv_usec as u32,
+            TimestampPrecision::Micro => header.ts.tv_usec as u32 * 1000,
+        };
+
+        time::UNIX_EPOCH + time::Duration::new(header.ts.tv_sec as u32 as u64, fraction)
+    }
+    #[cfg(target_os = "windows")]
+    fn set_nonblocking_windows(&self, nonblocking: bool) -> io::Result<()> {
+        let mut error_buffer: [i8; 256] = [0; 256];
+
+        let result: i32 = unsafe {
+            ccs::pcap_setnonblock(
+                self.adapter as *mut ccs::pcap,
+                nonblocking as i32,
+                error_buffer.as_mut_ptr(),
+            )
+        };
+
+        if result < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                format!(
+                    "can\'t change blocking mode due to {}",
+                    str_from_cstr(error_buffer.as_ptr())
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+    #[cfg(target_os = "windows")]
+    fn is_nonblocking_windows(&self) -> io::Result<bool> {
+        let mut error_buffer: [i8; 256] = [0; 256];
+
+        let result: i32 = unsafe {
+            ccs::pcap_getnonblock(self.adapter as *mut ccs::pcap, error_buffer.as_mut_ptr())
+        };
+
+        if result < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                format!(
+                    "can\'t get blocking mode due to {}",
+                    str_from_cstr(error_buffer.as_ptr())
+                ),
+            ));
+        }
+
+        Ok(result == 1)
+    }
+    #[cfg(target_os = "windows")]
+    fn send_raw_packet_windows(&self, buffer: &[u8]) -> io::Result<usize> {
+        let length: i32 = unsafe {
+            ccs::pcap_inject(
+                self.adapter as *mut ccs::pcap,
+                buffer.as_ptr() as *const std::os::raw::c_void,
+                buffer.len(),
+            )
+        };
+
+        if length < 0 {
+            let error: String =
+                unsafe { str_from_cstr(ccs::pcap_geterr(self.adapter as *mut ccs::pcap)) };
+
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                format!("can\'t send buffer due to \"{}\"", error),
+            ));
+        }
+
+        Ok(length as usize)
+    }
+    /// queues frames in a pcap send queue, which is transmitted at once
+    #[cfg(target_os = "windows")]
+    fn send_raw_packets_windows(&self, frames: &[&[u8]]) -> io::Result<usize> {
+        let header_size: usize = std::mem::size_of::<ccs::pcap_pkthdr>();
+        let queue_size: usize = frames.iter().map(|frame| header_size + frame.len()).sum();
+
+        let queue_size: u32 = u32::try_from(queue_size).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("batch of {} bytes is too large", queue_size),
+            )
+        })?;
+
+        let queue: *mut ccs::pcap_send_queue = unsafe { ccs::pcap_sendqueue_alloc(queue_size) };
+        if queue.is_null() {
+            return Err(io::Error::new(
+                io::ErrorKind::OutOfMemory,
+                "can\'t allocate send queue",
+            ));
+        }
+
+        for frame in frames {
+            let header: ccs::pcap_pkthdr = ccs::pcap_pkthdr {
+                ts: ccs::timeval {
+                    tv_sec: 0,
+                    tv_usec: 0,
+                },
+                caplen: frame.len() as u32,
+                len: frame.len() as u32,
+            };
+
+            if unsafe { ccs::pcap_sendqueue_queue(queue, &header, frame.as_ptr()) } < 0 {
+                unsafe { ccs::pcap_sendqueue_destroy(queue) };
+
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "can\'t queue frame",
+                ));
+            }
+        }
+
+        let transmitted: usize = unsafe {
+            ccs::pcap_sendqueue_transmit(self.adapter as *mut ccs::pcap, queue, 0) as usize
+        };
+        unsafe { ccs::pcap_sendqueue_destroy(queue) };
+
+        // frames are transmitted in order, so the count is got from the transmitted bytes
+        let mut queued: usize = 0;
+        let sent: usize = frames
+            .iter()
+            .take_while(|frame| {
+                queued += header_size + frame.len();
+                queued <= transmitted
+            })
+            .count();
+
+        if sent == 0 {
+            let error: String =
+                unsafe { str_from_cstr(ccs::pcap_geterr(self.adapter as *mut ccs::pcap)) };
+
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                format!("can\'t send buffer due to \"{}\"", error),
+            ));
+        }
+
+        Ok(sent)
+    }
+    #[cfg(target_os = "macos")]
+    fn open_macos(adapter: Adapter, options: SocketOptions) -> io::Result<Self> {
+        let socket: i32 = open_bpf_device().map_err(permission_hint)?;
+
+        let buffer_size: usize = match configure_bpf_device(
+            socket,
+            adapter.get_name(),
+            options.get_recv_buffer_size(),
+        ) {
+            Ok(buffer_size) => buffer_size,
+            Err(err) => {
+                unsafe { ccs::close(socket) };
+                return Err(err);
+            }
+        };
+
+        Ok(Self {
+            socket,
+            bpf_buffer: Mutex::new(BpfBuffer {
+                data: vec![0; buffer_size],
+                offset: 0,
+                length: 0,
+            }),
+            mtu: enforced_mtu(&adapter, &options),
+            interface: adapter,
+            options,
+            egress_vlan: atomic::AtomicU32::new(0),
+            promiscuous: atomic::AtomicBool::new(false),
+        })
+    }
+    /// takes the next packet from the bpf buffer, it's refilled with read when it's empty,
+    /// without wait WouldBlock error is returned instead of blocking in read
+    #[cfg(target_os = "macos")]
+    fn read_raw_packet_macos(
+        &self,
+        buffer: &mut [u8],
+        wait: bool,
+    ) -> io::Result<(usize, time::SystemTime)> {
+        let mut bpf_buffer = self.lock_bpf_buffer();
+
+        loop {
+            if let Some(packet) = bpf_buffer.next_packet(buffer) {
+                return Ok(packet);
+            }
+
+            if !wait && !poll_socket(self.socket, ccs::POLLIN, time::Instant::now())? {
+                return Err(io::Error::from(io::ErrorKind::WouldBlock));
+            }
+
+            let length: isize = unsafe {
+                ccs::read(
+                    self.socket,
+                    bpf_buffer.data.as_mut_ptr() as *mut std::os::raw::c_void,
+                    bpf_buffer.data.len(),
+                )
+            };
+
+            if length < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            bpf_buffer.offset = 0;
+            bpf_buffer.length = length as usize;
+        }
+    }
+    #[cfg(target_os = "macos")]
+    fn send_raw_packet_macos(&self, buffer: &[u8]) -> io::Result<usize> {
+        let length: isize = unsafe {
+            ccs::write(
+                self.socket,
+                buffer.as_ptr() as *const std::os::raw::c_void,
+                buffer.len(),
+            )
+        };
+
+        if length < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(length as usize)
+    }
+    /// bpf device has no batch write, so frames are written one by one
+    #[cfg(target_os = "macos")]
+    fn send_raw_packets_macos(&self, frames: &[&[u8]]) -> io::Result<usize> {
+        let mut sent: usize = 0;
+
+        for frame in frames {
+            match self.send_raw_packet_macos(frame) {
+                Ok(_) => sent += 1,
+                Err(err) if sent == 0 => return Err(err),
+                Err(_) => break,
+            }
+        }
+
+        Ok(sent)
+    }
+    /// buffer only holds offsets into the data, so it's still usable after a panic
+    #[cfg(target_os = "macos")]
+    fn lock_bpf_buffer(&self) -> std::sync::MutexGuard<'_, BpfBuffer> {
+        self.bpf_buffer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    /// returns InvalidInput error if the frame doesn't fit into mtu, which was read at opening,
+    /// 802.1Q tag isn't counted, like it's done by linux
+    fn check_mtu(&self, frame: &[u8]) -> io::Result<()> {
+        let mtu: usize = match self.mtu {
+            Some(mtu) => mtu as usize,
+            None => return Ok(()),
+        };
+
+        let header_len: usize = match needs_vlan_tag(frame) {
+            true => ETH_HEADER_SIZE,
+            false => ETH_HEADER_SIZE + VLAN_HEADER_SIZE,
+        };
+
+        if frame.len() > mtu + header_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "frame of {} bytes exceeds mtu {} of {}",
+                    frame.len(),
+                    mtu,
+                    self.interface.get_name()
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+    fn send_raw_packet_with(
+        &self,
+        buffer: &[u8],
+        timeout: Option<time::Duration>,
+    ) -> io::Result<usize> {
+        let tagged: Vec<u8>;
+        let (buffer, inserted): (&[u8], usize) = match self.get_egress_vlan() {
+            Some(vlan_id) if needs_vlan_tag(buffer) => {
+                tagged = insert_vlan_tag(buffer, vlan_id, 0);
+                (&tagged, VLAN_HEADER_SIZE)
+            }
+            _ => (buffer, 0),
+        };
+
+        self.check_mtu(buffer)?;
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let length: io::Result<usize> = match timeout {
+            Some(timeout) => self.send_raw_packet_timeout_linux(buffer, timeout),
+            None => self.send_raw_packet_linux(buffer, 0),
+        };
+        #[cfg(target_os = "windows")]
+        let length: io::Result<usize> = {
+            let _ = timeout;
+            self.send_raw_packet_windows(buffer)
+        };
+        #[cfg(target_os = "macos")]
+        let length: io::Result<usize> = {
+            let _ = timeout;
+            self.send_raw_packet_macos(buffer)
+        };
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "windows",
+            target_os = "macos"
+        )))]
+        let length: io::Result<usize> = {
+            let _ = (buffer, timeout);
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not supported yet!", std::env::consts::OS),
+            ))
+        };
+
+        // inserted vlan tag isn't part of the caller's buffer
+        length
+            .inspect(|length| logger::debug(|| format!("sent {} bytes", length)))
+            .map(|length| length.saturating_sub(inserted))
+    }
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn send_raw_packet_timeout_linux(
+        &self,
+        buffer: &[u8],
+        timeout: time::Duration,
+    ) -> io::Result<usize> {
+        let deadline: time::Instant = time::Instant::now() + timeout;
+
+        loop {
+            if !poll_socket(self.socket, ccs::POLLOUT, deadline)? {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "transmit queue stayed full until timeout",
+                ));
+            }
+
+            // queue can fill up again between poll and sendto
+            match self.send_raw_packet_linux(buffer, ccs::MSG_DONTWAIT) {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+                length => return length,
+            }
+        }
+    }
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn send_raw_packet_linux(&self, buffer: &[u8], flags: i32) -> io::Result<usize> {
+        let addr: ccs::sockaddr_ll = self.link_addr_linux();
+        let addrlen: ccs::SocklenT = std::mem::size_of_val(&addr) as ccs::SocklenT;
+
+        let length: isize = unsafe {
             ccs::sendto(
                 self.socket,
                 buffer.as_ptr() as *const std::os::raw::c_void,
                 buffer.len(),
-                0,
+                flags,
                 &addr as *const ccs::sockaddr_ll as *const ccs::sockaddr,
                 addrlen,
             )
@@ -310,33 +2836,613 @@ impl Socket {
             return Err(io::Error::last_os_error());
         }
 
-        Ok(())
+        Ok(length as usize)
+    }
+    /// sends frames with sendmmsg, which takes at most UIO_MAXIOV of them at once
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn send_raw_packets_linux(&self, frames: &[&[u8]]) -> io::Result<usize> {
+        let mut addr: ccs::sockaddr_ll = self.link_addr_linux();
+        let addrlen: u32 = std::mem::size_of_val(&addr) as u32;
+        let name: *mut std::os::raw::c_void =
+            &mut addr as *mut ccs::sockaddr_ll as *mut std::os::raw::c_void;
+
+        let mut sent: usize = 0;
+
+        for chunk in frames.chunks(ccs::UIO_MAXIOV) {
+            let mut iovecs: Vec<ccs::iovec> = chunk
+                .iter()
+                .map(|frame| ccs::iovec {
+                    iov_base: frame.as_ptr() as *mut std::os::raw::c_void,
+                    iov_len: frame.len(),
+                })
+                .collect();
+
+            let mut messages: Vec<ccs::mmsghdr> = iovecs
+                .iter_mut()
+                .map(|iovec| ccs::mmsghdr {
+                    msg_hdr: ccs::msghdr {
+                        msg_name: name,
+                        msg_namelen: addrlen,
+                        msg_iov: iovec,
+                        msg_iovlen: 1,
+                        msg_control: std::ptr::null_mut(),
+                        msg_controllen: 0,
+                        msg_flags: 0,
+                    },
+                    msg_len: 0,
+                })
+                .collect();
+
+            let count: i32 = unsafe {
+                ccs::sendmmsg(self.socket, messages.as_mut_ptr(), messages.len() as u32, 0)
+            };
+
+            if count < 0 {
+                // error after some frames were sent is reported by the count
+                match sent {
+                    0 => return Err(io::Error::last_os_error()),
+                    _ => break,
+                }
+            }
+
+            sent += count as usize;
+            if (count as usize) < chunk.len() {
+                break;
+            }
+        }
+
+        Ok(sent)
+    }
+    /// address of the socket interface, which frames are sent to
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn link_addr_linux(&self) -> ccs::sockaddr_ll {
+        let hw_addr: &[u8] = self.interface.get_hw_addr();
+        let mut addr: ccs::sockaddr_ll = ccs::sockaddr_ll {
+            sll_family: 0,
+            sll_protocol: 0,
+            sll_ifindex: *self.get_adapter().get_index(),
+            sll_hatype: *self.interface.get_hw_type(),
+            sll_pkttype: 0,
+            sll_halen: hw_addr.len() as u8,
+            sll_addr: [0; 8],
+        };
+        addr.sll_addr[..hw_addr.len()].copy_from_slice(hw_addr);
+
+        addr
     }
-    #[cfg(target_os = "linux")]
-    fn destroy_linux(&self) {
-        unsafe { ccs::close(self.socket) };
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+        unsafe {
+            ccs::close(self.socket);
+        }
+        #[cfg(target_os = "windows")]
+        unsafe {
+            ccs::pcap_close(self.adapter as *mut ccs::pcap);
+        }
     }
 }
 
-impl Clone for Socket {
+/// lack of privileges is the most common reason, why socket can't be opened, so it's told how to get them,
+/// os error code stays in the message
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "windows",
+    target_os = "macos"
+))]
+pub(crate) fn permission_hint(err: io::Error) -> io::Error {
+    if err.kind() != io::ErrorKind::PermissionDenied {
+        return err;
+    }
+
     #[cfg(target_os = "windows")]
-    fn clone(&self) -> Self {
-        Self {
-            adapter: self.adapter,
-            interface: self.interface.clone(),
+    let hint: &str = "npcap was probably installed with access restricted to administrators, run as administrator";
+    #[cfg(target_os = "macos")]
+    let hint: &str = "run as root or give the user read and write access to /dev/bpf*";
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let hint: &str = "run as root or grant CAP_NET_RAW, e.g. setcap cap_net_raw+ep <binary>";
+
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("{} ({})", err, hint),
+    )
+}
+
+/// walks control messages like CMSG_NXTHDR, every header and its data are aligned to 8 bytes,
+/// returns data of the first message with the level and type
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn find_control_message(control: &[u8], level: i32, type_: i32) -> Option<&[u8]> {
+    let header_size: usize = std::mem::size_of::<ccs::cmsghdr>();
+    let mut offset: usize = 0;
+
+    while offset + header_size <= control.len() {
+        let header: ccs::cmsghdr =
+            unsafe { std::ptr::read_unaligned(control[offset..].as_ptr() as *const ccs::cmsghdr) };
+
+        if header.cmsg_len < header_size || offset + header.cmsg_len > control.len() {
+            return None;
+        }
+
+        if header.cmsg_level == level && header.cmsg_type == type_ {
+            return Some(&control[offset + header_size..offset + header.cmsg_len]);
+        }
+
+        offset += header.cmsg_len.next_multiple_of(8);
+    }
+
+    None
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn find_timestamp(control: &[u8]) -> Option<time::SystemTime> {
+    // SCM_TIMESTAMPNS has the same value as SO_TIMESTAMPNS
+    let data: &[u8] = find_control_message(control, ccs::SOL_SOCKET, ccs::SO_TIMESTAMPNS)?;
+    if data.len() < std::mem::size_of::<ccs::timespec>() {
+        return None;
+    }
+
+    let timespec: ccs::timespec =
+        unsafe { std::ptr::read_unaligned(data.as_ptr() as *const ccs::timespec) };
+
+    Some(time::UNIX_EPOCH + time::Duration::new(timespec.tv_sec as u64, timespec.tv_nsec as u32))
+}
+
+/// takes vlan tag, which was stripped from the frame by the kernel or the driver, from PACKET_AUXDATA
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn find_stripped_vlan(control: &[u8]) -> Option<VlanTag> {
+    let data: &[u8] = find_control_message(control, ccs::SOL_PACKET, ccs::PACKET_AUXDATA)?;
+    if data.len() < std::mem::size_of::<ccs::tpacket_auxdata>() {
+        return None;
+    }
+
+    let auxdata: ccs::tpacket_auxdata =
+        unsafe { std::ptr::read_unaligned(data.as_ptr() as *const ccs::tpacket_auxdata) };
+
+    if auxdata.tp_status & ccs::TP_STATUS_VLAN_VALID == 0 {
+        return None;
+    }
+
+    // tpid is reported since linux 3.14, older kernels strip only 802.1Q tags
+    let tpid: u16 = match auxdata.tp_status & ccs::TP_STATUS_VLAN_TPID_VALID != 0 {
+        true => auxdata.tp_vlan_tpid,
+        false => VLAN_PROTO,
+    };
+
+    Some(VlanTag::from_tci(tpid, auxdata.tp_vlan_tci))
+}
+
+/// returns the outer tag of the frame
+fn frame_vlan(frame: &[u8]) -> Option<VlanTag> {
+    let (_, tags, _) = EthernetHeader::parse_tagged(frame).ok()?;
+
+    tags.first().copied()
+}
+
+/// interface drops aren't counted by packet sockets, so they are taken from sysfs
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn read_rx_dropped(name: &str) -> Option<u64> {
+    std::fs::read_to_string(format!("/sys/class/net/{}/statistics/rx_dropped", name))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn log_received(length: usize) {
+    logger::debug(|| format!("received {} bytes", length))
+}
+
+/// checks that the frame is an untagged ethernet frame
+fn needs_vlan_tag(frame: &[u8]) -> bool {
+    if frame.len() < ETH_HEADER_SIZE {
+        return false;
+    }
+
+    !matches!(
+        u16::from_be_bytes([frame[12], frame[13]]),
+        VLAN_PROTO | QINQ_PROTO
+    )
+}
+
+/// mtu, which is checked before sending, if it's enabled in options and can be read
+fn enforced_mtu(interface: &Adapter, options: &SocketOptions) -> Option<u32> {
+    match options.get_enforce_mtu() {
+        true => interface.get_mtu().ok(),
+        false => None,
+    }
+}
+
+#[cfg(any(target_os = "macos", test))]
+impl BpfBuffer {
+    /// checks whether every packet was taken
+    fn is_empty(&self) -> bool {
+        self.offset + std::mem::size_of::<ccs::bpf_hdr>() > self.length
+    }
+    /// copies the next packet into buffer, returns its length and timestamp,
+    /// None is returned when every packet was taken
+    fn next_packet(&mut self, buffer: &mut [u8]) -> Option<(usize, time::SystemTime)> {
+        if self.is_empty() {
+            return None;
         }
+
+        let header: ccs::bpf_hdr = unsafe {
+            std::ptr::read_unaligned(self.data[self.offset..].as_ptr() as *const ccs::bpf_hdr)
+        };
+
+        let start: usize = self.offset + header.bh_hdrlen as usize;
+        let caplen: usize = header.bh_caplen as usize;
+
+        if start + caplen > self.length {
+            self.offset = self.length;
+            return None;
+        }
+
+        let size: usize = caplen.min(buffer.len());
+        buffer[..size].copy_from_slice(&self.data[start..start + size]);
+
+        // every record is padded, so the next header is word aligned
+        self.offset += (header.bh_hdrlen as usize + caplen).next_multiple_of(ccs::BPF_ALIGNMENT);
+
+        let timestamp: time::SystemTime = time::UNIX_EPOCH
+            + time::Duration::new(
+                header.bh_tstamp.tv_sec as u32 as u64,
+                header.bh_tstamp.tv_usec as u32 * 1000,
+            );
+
+        Some((size, timestamp))
     }
-    #[cfg(target_os = "linux")]
-    fn clone(&self) -> Self {
-        Self {
-            socket: self.socket,
-            interface: self.interface.clone(),
+}
+
+/// bpf devices can be opened only by one process, so the first free one is taken
+#[cfg(target_os = "macos")]
+fn open_bpf_device() -> io::Result<i32> {
+    for i in 0..256 {
+        let path: String = format!("/dev/bpf{}\0", i);
+
+        let socket: i32 = unsafe { ccs::open(path.as_ptr() as *const i8, ccs::O_RDWR) };
+        if socket < 0 {
+            let err: io::Error = io::Error::last_os_error();
+            if err.raw_os_error() == Some(ccs::EBUSY) {
+                continue;
+            }
+
+            return Err(err);
+        }
+
+        if unsafe { ccs::fcntl(socket, ccs::F_SETFD, ccs::FD_CLOEXEC) } < 0 {
+            let err: io::Error = io::Error::last_os_error();
+            unsafe { ccs::close(socket) };
+
+            return Err(err);
         }
+
+        return Ok(socket);
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "every bpf device is busy",
+    ))
+}
+
+/// binds bpf device to the interface, returns size of its buffer
+#[cfg(target_os = "macos")]
+fn configure_bpf_device(socket: i32, name: &str, buffer_size: &Option<usize>) -> io::Result<usize> {
+    if name.len() >= ccs::IFNAMSIZ {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("interface name {} is too long", name),
+        ));
+    }
+
+    // buffer size can't be changed after the device is bound
+    if let Some(bytes) = buffer_size {
+        let mut size: u32 = u32::try_from(*bytes).map_err(|_| invalid_buffer_size(*bytes))?;
+        bpf_ioctl(socket, ccs::BIOCSBLEN, &mut size)?;
     }
-    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
-    fn clone(&self) -> Self {
-        Self {
-            interface: self.interface.clone(),
+
+    let mut if_request: ccs::ifreq = ccs::ifreq {
+        ifr_name: [0; ccs::IFNAMSIZ],
+        ifr_ifru: [0; 16],
+    };
+    for (dst, src) in if_request.ifr_name.iter_mut().zip(name.bytes()) {
+        *dst = src as i8;
+    }
+
+    bpf_ioctl(socket, ccs::BIOCSETIF, &mut if_request)?;
+
+    // packets are returned as soon as they arrive, not when the buffer is full
+    let mut enable: u32 = 1;
+    bpf_ioctl(socket, ccs::BIOCIMMEDIATE, &mut enable)?;
+    // source mac address of sent frames is kept as is
+    bpf_ioctl(socket, ccs::BIOCSHDRCMPLT, &mut enable)?;
+
+    let mut size: u32 = 0;
+    bpf_ioctl(socket, ccs::BIOCGBLEN, &mut size)?;
+
+    Ok(size as usize)
+}
+
+#[cfg(target_os = "macos")]
+fn bpf_ioctl<T>(socket: i32, request: u64, argument: *mut T) -> io::Result<()> {
+    if unsafe { ccs::ioctl(socket, request, argument) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// waits until socket is ready for events, returns false if deadline has passed
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+pub(crate) fn poll_socket(socket: i32, events: i16, deadline: time::Instant) -> io::Result<bool> {
+    let mut fd: ccs::pollfd = ccs::pollfd {
+        fd: socket,
+        events,
+        revents: 0,
+    };
+
+    Ok(poll_sockets(std::slice::from_mut(&mut fd), deadline)? > 0)
+}
+/// polls several descriptors until one of them is ready or deadline passes,
+/// returns the number of ready descriptors, which have revents set
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+pub(crate) fn poll_sockets(fds: &mut [ccs::pollfd], deadline: time::Instant) -> io::Result<usize> {
+    loop {
+        let remaining: time::Duration = deadline.saturating_duration_since(time::Instant::now());
+        // rounding up, so poll doesn't return right before the deadline
+        let timeout: i32 = remaining
+            .as_nanos()
+            .div_ceil(1_000_000)
+            .min(i32::MAX as u128) as i32;
+
+        let result: i32 = unsafe { ccs::poll(fds.as_mut_ptr(), fds.len() as _, timeout) };
+
+        if result < 0 {
+            let err: io::Error = io::Error::last_os_error();
+            if err.raw_os_error() == Some(ccs::EINTR) {
+                continue;
+            }
+
+            return Err(err);
         }
+
+        return Ok(result as usize);
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_recv_buffer_size_linux(socket: i32, bytes: usize) -> io::Result<()> {
+    let size: i32 = i32::try_from(bytes).map_err(|_| invalid_buffer_size(bytes))?;
+
+    let result: i32 = unsafe {
+        ccs::setsockopt(
+            socket,
+            ccs::SOL_SOCKET,
+            ccs::SO_RCVBUF,
+            &size as *const i32 as *const std::os::raw::c_void,
+            std::mem::size_of::<i32>() as ccs::SocklenT,
+        )
+    };
+
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// kernel timestamps packets only while someone asks for timestamps,
+/// without them read_raw_packet_ts falls back to the time of the read
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn enable_timestamps_linux(socket: i32) {
+    let enable: i32 = 1;
+    unsafe {
+        ccs::setsockopt(
+            socket,
+            ccs::SOL_SOCKET,
+            ccs::SO_TIMESTAMPNS,
+            &enable as *const i32 as *const std::os::raw::c_void,
+            std::mem::size_of::<i32>() as ccs::SocklenT,
+        )
+    };
+}
+
+/// stripped vlan tags are reported in PACKET_AUXDATA control messages, see [`Socket::read_raw_packet_from`]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn enable_auxdata_linux(socket: i32) {
+    let enable: i32 = 1;
+    unsafe {
+        ccs::setsockopt(
+            socket,
+            ccs::SOL_PACKET,
+            ccs::PACKET_AUXDATA,
+            &enable as *const i32 as *const std::os::raw::c_void,
+            std::mem::size_of::<i32>() as ccs::SocklenT,
+        )
+    };
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn bind_linux(socket: i32, ifindex: i32, protocol: u16) -> io::Result<()> {
+    let addr: ccs::sockaddr_ll = ccs::sockaddr_ll {
+        sll_family: ccs::AF_PACKET as u16,
+        sll_protocol: protocol.to_be(),
+        sll_ifindex: ifindex,
+        sll_hatype: 0,
+        sll_pkttype: 0,
+        sll_halen: 0,
+        sll_addr: [0; 8],
+    };
+
+    let result: i32 = unsafe {
+        ccs::bind(
+            socket,
+            &addr as *const ccs::sockaddr_ll as *const ccs::sockaddr,
+            std::mem::size_of::<ccs::sockaddr_ll>() as ccs::SocklenT,
+        )
+    };
+
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_promiscuous_linux(socket: i32, ifindex: i32, enable: bool) -> io::Result<()> {
+    let request: ccs::packet_mreq = ccs::packet_mreq {
+        mr_ifindex: ifindex,
+        mr_type: ccs::PACKET_MR_PROMISC,
+        mr_alen: 0,
+        mr_address: [0; 8],
+    };
+
+    let option: i32 = match enable {
+        true => ccs::PACKET_ADD_MEMBERSHIP,
+        false => ccs::PACKET_DROP_MEMBERSHIP,
+    };
+
+    let result: i32 = unsafe {
+        ccs::setsockopt(
+            socket,
+            ccs::SOL_PACKET,
+            option,
+            &request as *const ccs::packet_mreq as *const std::os::raw::c_void,
+            std::mem::size_of::<ccs::packet_mreq>() as ccs::SocklenT,
+        )
+    };
+
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "windows",
+    target_os = "macos"
+))]
+fn invalid_buffer_size(bytes: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("buffer size can't be {} bytes", bytes),
+    )
+}
+
+/// opens socket with close-on-exec flag, so raw socket isn't leaked into child processes
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn open_cloexec_socket(domain: i32, type_: i32, protocol: i32) -> io::Result<i32> {
+    let socket: i32 = unsafe { ccs::socket(domain, type_ | ccs::SOCK_CLOEXEC, protocol) };
+    if socket >= 0 {
+        return Ok(socket);
+    }
+
+    // kernels older than 2.6.27 don't know SOCK_CLOEXEC and fail with EINVAL
+    let error = io::Error::last_os_error();
+    if error.raw_os_error() != Some(ccs::EINVAL) {
+        return Err(error);
+    }
+
+    let socket: i32 = unsafe { ccs::socket(domain, type_, protocol) };
+    if socket < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    if unsafe { ccs::fcntl(socket, ccs::F_SETFD, ccs::FD_CLOEXEC) } < 0 {
+        let error = io::Error::last_os_error();
+        unsafe { ccs::close(socket) };
+
+        return Err(error);
+    }
+
+    Ok(socket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// writes bpf record with the header length, which macos uses for ethernet, and pads it to the word
+    fn push_bpf_record(data: &mut Vec<u8>, packet: &[u8], tv_sec: i32, tv_usec: i32) {
+        let header: ccs::bpf_hdr = ccs::bpf_hdr {
+            bh_tstamp: ccs::timeval32 { tv_sec, tv_usec },
+            bh_caplen: packet.len() as u32,
+            bh_datalen: packet.len() as u32,
+            bh_hdrlen: 18,
+        };
+
+        let start: usize = data.len();
+        data.resize(start + header.bh_hdrlen as usize, 0);
+        unsafe {
+            std::ptr::write_unaligned(data[start..].as_mut_ptr() as *mut ccs::bpf_hdr, header)
+        };
+        data.extend_from_slice(packet);
+        data.resize(data.len().next_multiple_of(ccs::BPF_ALIGNMENT), 0);
+    }
+
+    #[test]
+    fn bpf_buffer_splits_aligned_records() {
+        let mut data: Vec<u8> = Vec::new();
+        push_bpf_record(&mut data, &[1, 2, 3, 4, 5], 10, 1);
+        push_bpf_record(&mut data, &[6, 7, 8, 9, 10, 11, 12], 11, 2);
+        push_bpf_record(&mut data, &[13], 12, 3);
+        let length: usize = data.len();
+
+        let mut bpf_buffer: BpfBuffer = BpfBuffer {
+            data,
+            offset: 0,
+            length,
+        };
+        let mut buffer: [u8; 64] = [0; 64];
+
+        // 18 + 5 bytes are padded to 24
+        let (size, timestamp) = bpf_buffer.next_packet(&mut buffer).expect("no packet");
+        assert_eq!(&buffer[..size], &[1, 2, 3, 4, 5]);
+        assert_eq!(timestamp, time::UNIX_EPOCH + time::Duration::new(10, 1000));
+        assert_eq!(bpf_buffer.offset, 24);
+
+        // 18 + 7 bytes are padded to 28
+        let (size, timestamp) = bpf_buffer.next_packet(&mut buffer).expect("no packet");
+        assert_eq!(&buffer[..size], &[6, 7, 8, 9, 10, 11, 12]);
+        assert_eq!(timestamp, time::UNIX_EPOCH + time::Duration::new(11, 2000));
+        assert_eq!(bpf_buffer.offset, 52);
+
+        // packet is truncated to the buffer
+        let (size, _) = bpf_buffer.next_packet(&mut buffer[..0]).expect("no packet");
+        assert_eq!(size, 0);
+        assert_eq!(bpf_buffer.offset, 72);
+
+        assert!(bpf_buffer.is_empty());
+        assert!(bpf_buffer.next_packet(&mut buffer).is_none())
+    }
+
+    #[test]
+    fn bpf_buffer_drops_truncated_record() {
+        let mut data: Vec<u8> = Vec::new();
+        push_bpf_record(&mut data, &[1, 2, 3], 10, 0);
+        push_bpf_record(&mut data, &[4, 5, 6, 7, 8, 9], 10, 0);
+        // second record is cut in the middle of its packet
+        let length: usize = data.len() - 2;
+
+        let mut bpf_buffer: BpfBuffer = BpfBuffer {
+            data,
+            offset: 0,
+            length,
+        };
+        let mut buffer: [u8; 64] = [0; 64];
+
+        let (size, _) = bpf_buffer.next_packet(&mut buffer).expect("no packet");
+        assert_eq!(&buffer[..size], &[1, 2, 3]);
+        assert_eq!(bpf_buffer.offset, 24);
+
+        assert!(bpf_buffer.next_packet(&mut buffer).is_none());
+        assert!(bpf_buffer.is_empty())
     }
 }
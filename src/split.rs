@@ -0,0 +1,122 @@
+use std::io;
+use std::time;
+
+use crate::*;
+
+/// Receiving half of a socket, see [`Socket::split`]
+pub struct SocketReader {
+    socket: Socket,
+}
+
+/// Sending half of a socket, see [`Socket::split`]
+pub struct SocketWriter {
+    socket: Socket,
+}
+
+impl Socket {
+    /// Splits socket into halves, which can be moved to a reader and a writer thread
+    ///
+    /// Writer is created with [`Socket::try_clone`], so on linux and macos it shares the descriptor,
+    /// and on windows it has its own pcap handle, which is never read,
+    /// so sends don't wait for the reads of the other half
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use cursock::*;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     const FRAMES: usize = 10000;
+    ///
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let Ok(socket) = Socket::new("lo") {
+    ///         socket.set_filter("ether src 02:00:00:00:00:98").expect("filter error");
+    ///         // frames are sent faster than they are read
+    ///         socket.set_recv_buffer_size(16 * 1024 * 1024).expect("buffer size error");
+    ///
+    ///         let (reader, writer) = socket.split().expect("split error");
+    ///
+    ///         let receiver = std::thread::spawn(move || {
+    ///             let mut buffer = [0; 1514];
+    ///             let mut count: usize = 0;
+    ///
+    ///             while count < FRAMES {
+    ///                 match reader.read_raw_packet_timeout(&mut buffer, Duration::from_secs(1)) {
+    ///                     Ok(_) => count += 1,
+    ///                     Err(_) => break,
+    ///                 }
+    ///             }
+    ///
+    ///             count
+    ///         });
+    ///
+    ///         let sender = std::thread::spawn(move || {
+    ///             let mut frame = vec![0; 60];
+    ///             frame[6..12].copy_from_slice(&[0x02, 0, 0, 0, 0, 0x98]);
+    ///
+    ///             for _ in 0..FRAMES {
+    ///                 writer.send_raw_packet(&frame).expect("send error");
+    ///             }
+    ///         });
+    ///
+    ///         sender.join().unwrap();
+    ///         assert_eq!(receiver.join().unwrap(), FRAMES)
+    ///     }
+    /// }
+    /// ```
+    pub fn split(self) -> io::Result<(SocketReader, SocketWriter)> {
+        let writer: Socket = self.try_clone()?;
+
+        Ok((
+            SocketReader { socket: self },
+            SocketWriter { socket: writer },
+        ))
+    }
+}
+
+impl SocketReader {
+    /// See [`Socket::read_raw_packet`]
+    pub fn read_raw_packet(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.socket.read_raw_packet(buffer)
+    }
+    /// See [`Socket::read_raw_packet_vec`]
+    pub fn read_raw_packet_vec(&self, max: usize) -> io::Result<Vec<u8>> {
+        self.socket.read_raw_packet_vec(max)
+    }
+    /// See [`Socket::read_raw_packet_timeout`]
+    pub fn read_raw_packet_timeout(
+        &self,
+        buffer: &mut [u8],
+        timeout: time::Duration,
+    ) -> io::Result<usize> {
+        self.socket.read_raw_packet_timeout(buffer, timeout)
+    }
+    /// See [`Socket::try_read_raw_packet`]
+    pub fn try_read_raw_packet(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.socket.try_read_raw_packet(buffer)
+    }
+    /// Returns socket, which the reader was split from, e.g. for changing its filter
+    pub fn get_socket(&self) -> &Socket {
+        &self.socket
+    }
+}
+
+impl SocketWriter {
+    /// See [`Socket::send_raw_packet`]
+    pub fn send_raw_packet(&self, buffer: &[u8]) -> io::Result<usize> {
+        self.socket.send_raw_packet(buffer)
+    }
+    /// See [`Socket::send_raw_packet_timeout`]
+    pub fn send_raw_packet_timeout(
+        &self,
+        buffer: &[u8],
+        timeout: time::Duration,
+    ) -> io::Result<usize> {
+        self.socket.send_raw_packet_timeout(buffer, timeout)
+    }
+    /// Returns socket, which is used for sending
+    pub fn get_socket(&self) -> &Socket {
+        &self.socket
+    }
+}
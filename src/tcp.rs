@@ -0,0 +1,326 @@
+use std::io;
+use std::net;
+use std::time;
+
+use rand::Rng;
+
+use crate::*;
+
+const TCP_HEADER_SIZE: usize = 20;
+
+/// tcp header, checksum is computed when it's serialized
+///
+/// # Example
+/// ```
+/// use cursock::*;
+/// use std::net::Ipv4Addr;
+///
+/// let src = Ipv4Addr::new(10, 0, 0, 1);
+/// let dst = Ipv4Addr::new(10, 0, 0, 2);
+///
+/// let mut header = TcpHeader::new(40000, 80, 1000, TcpHeader::SYN);
+/// header.set_mss(1460);
+///
+/// let segment = header.to_bytes(&src, &dst, &[]);
+/// assert_eq!(segment.len(), 24);
+///
+/// let (parsed, payload) = TcpHeader::from_bytes(&segment).expect("parse error");
+///
+/// assert_eq!(parsed, header);
+/// assert_eq!(parsed.get_mss(), Some(1460));
+/// assert!(payload.is_empty());
+/// assert!(TcpHeader::verify(&src, &dst, &segment));
+/// assert!(!TcpHeader::verify(&dst, &dst, &segment))
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TcpHeader {
+    src_port: u16,
+    dst_port: u16,
+    seq: u32,
+    ack: u32,
+    flags: u8,
+    window: u16,
+    urgent: u16,
+    // raw options, padded with zeros to 4 bytes when serialized
+    options: Vec<u8>,
+}
+
+/// result of [`Socket::syn_probe`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PortState {
+    /// port answered with SYN-ACK
+    Open,
+    /// port answered with RST
+    Closed,
+    /// nothing answered until timeout
+    Filtered,
+}
+
+impl TcpHeader {
+    pub const FIN: u8 = 0x01;
+    pub const SYN: u8 = 0x02;
+    pub const RST: u8 = 0x04;
+    pub const PSH: u8 = 0x08;
+    pub const ACK: u8 = 0x10;
+    pub const URG: u8 = 0x20;
+
+    pub fn new(src_port: u16, dst_port: u16, seq: u32, flags: u8) -> Self {
+        Self {
+            src_port,
+            dst_port,
+            seq,
+            ack: 0,
+            flags,
+            window: 64240,
+            urgent: 0,
+            options: Vec::new(),
+        }
+    }
+
+    /// Parses header with options, returns it with the payload
+    pub fn from_bytes(segment: &[u8]) -> io::Result<(Self, &[u8])> {
+        if segment.len() < TCP_HEADER_SIZE {
+            return Err(invalid_header(format!(
+                "buffer has only {} bytes",
+                segment.len()
+            )));
+        }
+
+        let header_len: usize = (segment[12] >> 4) as usize * 4;
+        if header_len < TCP_HEADER_SIZE || header_len > segment.len() {
+            return Err(invalid_header(format!(
+                "header length is {} bytes, but buffer has {}",
+                header_len,
+                segment.len()
+            )));
+        }
+
+        let header: Self = Self {
+            src_port: u16::from_be_bytes([segment[0], segment[1]]),
+            dst_port: u16::from_be_bytes([segment[2], segment[3]]),
+            seq: u32::from_be_bytes([segment[4], segment[5], segment[6], segment[7]]),
+            ack: u32::from_be_bytes([segment[8], segment[9], segment[10], segment[11]]),
+            flags: segment[13] & 0x3f,
+            window: u16::from_be_bytes([segment[14], segment[15]]),
+            urgent: u16::from_be_bytes([segment[18], segment[19]]),
+            options: segment[TCP_HEADER_SIZE..header_len].to_vec(),
+        };
+
+        Ok((header, &segment[header_len..]))
+    }
+
+    /// Serializes header with the payload, checksum is computed with ipv4 pseudo header
+    pub fn to_bytes(&self, src: &net::Ipv4Addr, dst: &net::Ipv4Addr, payload: &[u8]) -> Vec<u8> {
+        let header_len: usize = TCP_HEADER_SIZE + self.options.len().div_ceil(4) * 4;
+        let mut segment: Vec<u8> = vec![0; header_len];
+
+        segment[0..2].copy_from_slice(&self.src_port.to_be_bytes());
+        segment[2..4].copy_from_slice(&self.dst_port.to_be_bytes());
+        segment[4..8].copy_from_slice(&self.seq.to_be_bytes());
+        segment[8..12].copy_from_slice(&self.ack.to_be_bytes());
+        segment[12] = ((header_len / 4) as u8) << 4;
+        segment[13] = self.flags;
+        segment[14..16].copy_from_slice(&self.window.to_be_bytes());
+        segment[18..20].copy_from_slice(&self.urgent.to_be_bytes());
+        segment[TCP_HEADER_SIZE..TCP_HEADER_SIZE + self.options.len()]
+            .copy_from_slice(&self.options);
+        segment.extend_from_slice(payload);
+
+        let checksum: u16 = tcp_checksum(src, dst, &segment);
+        segment[16..18].copy_from_slice(&checksum.to_be_bytes());
+
+        segment
+    }
+
+    /// Checks checksum of the received segment
+    pub fn verify(src: &net::Ipv4Addr, dst: &net::Ipv4Addr, segment: &[u8]) -> bool {
+        tcp_checksum(src, dst, segment) == 0
+    }
+
+    /// Appends maximum segment size option
+    pub fn set_mss(&mut self, mss: u16) {
+        const MSS_KIND: u8 = 2;
+        const MSS_LEN: u8 = 4;
+
+        let mss: [u8; 2] = mss.to_be_bytes();
+        self.options
+            .extend_from_slice(&[MSS_KIND, MSS_LEN, mss[0], mss[1]])
+    }
+
+    /// Returns maximum segment size option if it's present
+    pub fn get_mss(&self) -> Option<u16> {
+        const END_KIND: u8 = 0;
+        const NOP_KIND: u8 = 1;
+        const MSS_KIND: u8 = 2;
+
+        let mut options: &[u8] = &self.options;
+        loop {
+            match options {
+                [] | [END_KIND, ..] => return None,
+                [NOP_KIND, rest @ ..] => options = rest,
+                [MSS_KIND, 4, high, low, ..] => return Some(u16::from_be_bytes([*high, *low])),
+                [_, len, ..] if *len >= 2 && (*len as usize) <= options.len() => {
+                    options = &options[*len as usize..]
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    /// Returns true if all the flags are set
+    pub fn has_flags(&self, flags: u8) -> bool {
+        self.flags & flags == flags
+    }
+
+    getters!(
+        pub get_src_port(src_port) -> u16;
+        pub get_dst_port(dst_port) -> u16;
+        pub get_seq(seq) -> u32;
+        pub get_ack(ack) -> u32;
+        pub get_flags(flags) -> u8;
+        pub get_window(window) -> u16;
+        pub get_urgent(urgent) -> u16;
+        pub get_options(options) -> [u8];
+    );
+    setters!(
+        pub set_src_port(u16) -> src_port;
+        pub set_dst_port(u16) -> dst_port;
+        pub set_seq(u32) -> seq;
+        pub set_ack(u32) -> ack;
+        pub set_flags(u8) -> flags;
+        pub set_window(u16) -> window;
+        pub set_urgent(u16) -> urgent;
+        pub set_options(Vec<u8>) -> options;
+    );
+}
+
+impl Socket {
+    /// Sends SYN to the port and classifies the answer, like half-open scanners do
+    ///
+    /// Probe comes from a random port with a random sequence number and only answers,
+    /// which are addressed to that port and acknowledge that sequence number, are matched,
+    /// so several probes can run concurrently.
+    /// Kernel doesn't know about the probe, so it usually resets the connection itself after SYN-ACK
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    /// use std::net::Ipv4Addr;
+    /// use std::time::Duration;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    /// let gateway = Ipv4Addr::new(192, 168, 0, 1);
+    /// let gateway_mac = socket.arp_request(gateway, Duration::from_secs(1)).expect("arp error");
+    ///
+    /// for port in [22, 80, 443] {
+    ///     let state = socket
+    ///         .syn_probe((gateway, port), &gateway_mac, Duration::from_secs(1))
+    ///         .expect("probe error");
+    ///
+    ///     println!("{}: {:?}", port, state)
+    /// }
+    /// ```
+    pub fn syn_probe(
+        &self,
+        dst: (net::Ipv4Addr, u16),
+        dst_mac: &Mac,
+        timeout: time::Duration,
+    ) -> io::Result<PortState> {
+        let deadline: time::Instant = time::Instant::now() + timeout;
+
+        let src_ip: net::Ipv4Addr = *self.get_src_ip().ok_or(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "To send tcp probes you need to have ipv4 source address ({})",
+                self.get_adapter()
+            ),
+        ))?;
+
+        let mut rng = rand::thread_rng();
+        let src_port: u16 = rng.gen_range(32768..61000);
+        let seq: u32 = rng.gen();
+
+        let mut tcp_header: TcpHeader = TcpHeader::new(src_port, dst.1, seq, TcpHeader::SYN);
+        tcp_header.set_mss(1460);
+        let segment: Vec<u8> = tcp_header.to_bytes(&src_ip, &dst.0, &[]);
+
+        let mut ip_header: Ipv4Header = Ipv4Header::new(src_ip, dst.0, TCP_PROTO as u8);
+        ip_header.set_total_len((IPV4_HEADER_SIZE + segment.len()) as u16);
+        ip_header.set_flags(Ipv4Header::DONT_FRAGMENT);
+
+        let eth_header: EthernetHeader =
+            EthernetHeader::new(*dst_mac, *self.get_src_mac(), IPV4_PROTO);
+
+        let mut frame: Vec<u8> =
+            Vec::with_capacity(ETH_HEADER_SIZE + IPV4_HEADER_SIZE + segment.len());
+        frame.extend_from_slice(&eth_header.to_bytes());
+        frame.extend_from_slice(&ip_header.to_bytes());
+        frame.extend_from_slice(&segment);
+
+        self.send_raw_packet(&frame)?;
+
+        let mut buffer: [u8; 1514] = [0; 1514];
+
+        loop {
+            let timeout: time::Duration = deadline.saturating_duration_since(time::Instant::now());
+
+            let length: usize = match self.read_raw_packet_timeout(&mut buffer, timeout) {
+                Ok(length) => length,
+                Err(err) if err.kind() == io::ErrorKind::TimedOut => {
+                    return Ok(PortState::Filtered)
+                }
+                Err(err) => return Err(err),
+            };
+
+            let answer: TcpHeader = match parse_tcp(&buffer[..length], &dst.0, &src_ip) {
+                Some(answer) => answer,
+                None => continue,
+            };
+
+            let is_answer: bool = answer.src_port == dst.1
+                && answer.dst_port == src_port
+                && answer.has_flags(TcpHeader::ACK)
+                && answer.ack == seq.wrapping_add(1);
+
+            if !is_answer {
+                continue;
+            }
+
+            if answer.has_flags(TcpHeader::RST) {
+                return Ok(PortState::Closed);
+            }
+            if answer.has_flags(TcpHeader::SYN) {
+                return Ok(PortState::Open);
+            }
+        }
+    }
+}
+
+/// parses tcp header of the frame from src to dst
+fn parse_tcp(frame: &[u8], src: &net::Ipv4Addr, dst: &net::Ipv4Addr) -> Option<TcpHeader> {
+    let (eth_header, packet) = EthernetHeader::parse(frame).ok()?;
+    if *eth_header.get_ethertype() != IPV4_PROTO {
+        return None;
+    }
+
+    let (ip_header, _, segment) = Ipv4Header::from_bytes(packet).ok()?;
+    if *ip_header.get_protocol() != TCP_PROTO as u8
+        || ip_header.get_src() != src
+        || ip_header.get_dst() != dst
+        || *ip_header.get_fragment_offset() != 0
+    {
+        return None;
+    }
+
+    // checksum isn't checked, with checksum offload packet sockets see segments before it's computed
+    TcpHeader::from_bytes(segment)
+        .ok()
+        .map(|(header, _)| header)
+}
+
+fn invalid_header<S: Into<String>>(message: S) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid tcp header: {}", message.into()),
+    )
+}
@@ -0,0 +1,339 @@
+use std::io;
+use std::net;
+use std::time;
+
+use rand::Rng;
+
+use crate::*;
+
+const PROBE_PAYLOAD_SIZE: usize = 32;
+
+/// protocol of traceroute probes, see [`TracerouteOptions::probe`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TracerouteProbe {
+    /// udp datagrams to closed ports, like unix traceroute sends
+    Udp,
+    /// icmp echo requests, like windows tracert sends
+    Icmp,
+}
+
+/// options of [`Socket::traceroute`]
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use cursock::*;
+///
+/// let options = TracerouteOptions::new().probe(TracerouteProbe::Icmp).max_hops(10);
+///
+/// assert_eq!(options.get_probe(), &TracerouteProbe::Icmp);
+/// assert_eq!(options.get_max_hops(), &10);
+/// assert_eq!(options.get_timeout(), &Duration::from_secs(1))
+/// ```
+#[derive(Clone)]
+pub struct TracerouteOptions {
+    probe: TracerouteProbe,
+    max_hops: u8,
+    probes_per_hop: usize,
+    timeout: time::Duration,
+    // destination port of the first udp probe, it's incremented for every probe
+    port: u16,
+}
+
+/// result of probes with one ttl, see [`Socket::traceroute`]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Hop {
+    /// address, which answered first, with round trip times of the answered probes
+    Reply {
+        addr: net::Ipv4Addr,
+        rtts: Vec<time::Duration>,
+    },
+    NoReply,
+}
+
+/// what an answer has to quote to match a probe
+struct Probe {
+    src: net::Ipv4Addr,
+    dst: net::Ipv4Addr,
+    kind: TracerouteProbe,
+    // src port of udp probes or id of icmp probes
+    id: u16,
+    // dst port of udp probes or sequence number of icmp probes
+    sq: u16,
+}
+
+impl TracerouteOptions {
+    pub fn new() -> Self {
+        Self {
+            probe: TracerouteProbe::Udp,
+            max_hops: 30,
+            probes_per_hop: 3,
+            timeout: time::Duration::from_secs(1),
+            port: 33434,
+        }
+    }
+
+    /// sets protocol of the probes, udp is used by default
+    pub fn probe(mut self, probe: TracerouteProbe) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    /// sets the largest ttl, which is probed
+    pub fn max_hops(mut self, max_hops: u8) -> Self {
+        self.max_hops = max_hops;
+        self
+    }
+
+    /// sets count of probes, which are sent with every ttl
+    pub fn probes_per_hop(mut self, count: usize) -> Self {
+        self.probes_per_hop = count;
+        self
+    }
+
+    /// sets how long an answer to every probe is waited for
+    pub fn timeout(mut self, timeout: time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// sets destination port of the first udp probe
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    getters!(
+        pub get_probe(probe) -> TracerouteProbe;
+        pub get_max_hops(max_hops) -> u8;
+        pub get_probes_per_hop(probes_per_hop) -> usize;
+        pub get_timeout(timeout) -> time::Duration;
+        pub get_port(port) -> u16;
+    );
+}
+
+impl Default for TracerouteOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Socket {
+    /// Sends probes with growing ttl to the destination and records, who answered them
+    ///
+    /// Hop at index `i` is the answer to ttl `i + 1`, hops without answers are [`Hop::NoReply`].
+    /// Tracing stops when the destination answers, or destination unreachable is received.
+    /// Dst mac is the next hop, usually the gateway.
+    /// Answers are matched by the probe, which they quote: udp ports or icmp id and sequence number,
+    /// so several traceroutes can run concurrently
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    /// use std::net::Ipv4Addr;
+    /// use std::time::Duration;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    /// let gateway = socket.get_adapter().get_gateway().expect("no gateway");
+    /// let gateway_mac = socket.arp_request(gateway, Duration::from_secs(1)).expect("arp error");
+    ///
+    /// let options = TracerouteOptions::new().probe(TracerouteProbe::Icmp);
+    /// let hops = socket
+    ///     .traceroute(Ipv4Addr::new(8, 8, 8, 8), &gateway_mac, &options)
+    ///     .expect("traceroute error");
+    ///
+    /// for (i, hop) in hops.iter().enumerate() {
+    ///     match hop {
+    ///         Hop::Reply { addr, rtts } => println!("{} {} {:?}", i + 1, addr, rtts),
+    ///         Hop::NoReply => println!("{} *", i + 1),
+    ///     }
+    /// }
+    /// ```
+    pub fn traceroute(
+        &self,
+        dst: net::Ipv4Addr,
+        dst_mac: &Mac,
+        options: &TracerouteOptions,
+    ) -> io::Result<Vec<Hop>> {
+        let src: net::Ipv4Addr = *self.get_src_ip().ok_or(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "To send traceroute probes you need to have ipv4 source address ({})",
+                self.get_adapter()
+            ),
+        ))?;
+
+        let mut rng = rand::thread_rng();
+        let id: u16 = match options.probe {
+            TracerouteProbe::Udp => rng.gen_range(32768..61000),
+            TracerouteProbe::Icmp => rng.gen(),
+        };
+
+        let mut hops: Vec<Hop> = Vec::with_capacity(options.max_hops as usize);
+        let mut count: u16 = 0;
+
+        for ttl in 1..=options.max_hops {
+            let mut addr: Option<net::Ipv4Addr> = None;
+            let mut rtts: Vec<time::Duration> = Vec::new();
+            let mut reached: bool = false;
+
+            for _ in 0..options.probes_per_hop {
+                let probe: Probe = Probe {
+                    src,
+                    dst,
+                    kind: options.probe,
+                    id,
+                    sq: match options.probe {
+                        TracerouteProbe::Udp => options.port.wrapping_add(count),
+                        TracerouteProbe::Icmp => count,
+                    },
+                };
+                count = count.wrapping_add(1);
+
+                let sent: time::Instant = time::Instant::now();
+                self.send_raw_packet(&probe_frame(self, &probe, dst_mac, ttl, rng.gen())?)?;
+
+                if let Some((from, last)) =
+                    self.wait_probe_answer(&probe, sent + options.timeout)?
+                {
+                    rtts.push(sent.elapsed());
+                    addr.get_or_insert(from);
+                    reached |= last;
+                }
+            }
+
+            logger::debug(|| format!("hop {}: {:?} {:?}", ttl, addr, rtts));
+
+            hops.push(match addr {
+                Some(addr) => Hop::Reply { addr, rtts },
+                None => Hop::NoReply,
+            });
+
+            if reached {
+                break;
+            }
+        }
+
+        Ok(hops)
+    }
+    /// reads frames until the probe is answered, returns address of the answer
+    /// and whether tracing is finished, None is returned after deadline
+    fn wait_probe_answer(
+        &self,
+        probe: &Probe,
+        deadline: time::Instant,
+    ) -> io::Result<Option<(net::Ipv4Addr, bool)>> {
+        let mut buffer: [u8; 1514] = [0; 1514];
+
+        loop {
+            let timeout: time::Duration = deadline.saturating_duration_since(time::Instant::now());
+
+            let length: usize = match self.read_raw_packet_timeout(&mut buffer, timeout) {
+                Ok(length) => length,
+                Err(err) if err.kind() == io::ErrorKind::TimedOut => return Ok(None),
+                Err(err) => return Err(err),
+            };
+
+            if let Some(answer) = match_answer(&buffer[..length], probe) {
+                return Ok(Some(answer));
+            }
+        }
+    }
+}
+
+fn probe_frame(
+    socket: &Socket,
+    probe: &Probe,
+    dst_mac: &Mac,
+    ttl: u8,
+    ip_id: u16,
+) -> io::Result<Vec<u8>> {
+    let (protocol, message): (u16, Vec<u8>) = match probe.kind {
+        TracerouteProbe::Udp => {
+            let udp_len: u16 = (8 + PROBE_PAYLOAD_SIZE) as u16;
+
+            let mut datagram: Vec<u8> = Vec::with_capacity(udp_len as usize);
+            datagram.extend_from_slice(&probe.id.to_be_bytes());
+            datagram.extend_from_slice(&probe.sq.to_be_bytes());
+            datagram.extend_from_slice(&udp_len.to_be_bytes());
+            datagram.extend_from_slice(&[0; 2 + PROBE_PAYLOAD_SIZE]);
+
+            let checksum: u16 = udp_checksum(&probe.src, &probe.dst, &datagram);
+            datagram[6..8].copy_from_slice(&checksum.to_be_bytes());
+
+            (UDP_PROTO, datagram)
+        }
+        TracerouteProbe::Icmp => {
+            let echo: IcmpMessage = IcmpMessage::EchoRequest {
+                id: probe.id,
+                sq: probe.sq,
+                data: vec![0; PROBE_PAYLOAD_SIZE],
+            };
+
+            (ICMP_PROTO, IcmpPacket::new(echo, 0).to_bytes())
+        }
+    };
+
+    let mut ip_header: Ipv4Header = Ipv4Header::new(probe.src, probe.dst, protocol as u8);
+    ip_header.set_ttl(ttl);
+    ip_header.set_id(ip_id);
+
+    let eth_header: EthernetHeader =
+        EthernetHeader::new(*dst_mac, *socket.get_src_mac(), IPV4_PROTO);
+
+    let mut frame: Vec<u8> = eth_header.to_bytes();
+    frame.extend_from_slice(&ip_header.to_packet(&message)?);
+
+    Ok(frame)
+}
+
+/// checks whether the frame is an icmp answer to the probe
+fn match_answer(frame: &[u8], probe: &Probe) -> Option<(net::Ipv4Addr, bool)> {
+    let (eth_header, packet) = EthernetHeader::parse(frame).ok()?;
+    if *eth_header.get_ethertype() != IPV4_PROTO {
+        return None;
+    }
+
+    let (ip_header, message) = Ipv4Header::parse(packet).ok()?;
+    if *ip_header.get_protocol() != ICMP_PROTO as u8 || *ip_header.get_dst() != probe.src {
+        return None;
+    }
+
+    let answer: IcmpPacket = IcmpPacket::from_bytes(message).ok()?;
+    if !answer.get_checksum_valid() {
+        return None;
+    }
+
+    let last: bool = match answer.get_message() {
+        IcmpMessage::EchoReply { id, sq, .. } => {
+            let matches: bool = probe.kind == TracerouteProbe::Icmp
+                && *ip_header.get_src() == probe.dst
+                && (*id, *sq) == (probe.id, probe.sq);
+
+            return matches.then_some((probe.dst, true));
+        }
+        IcmpMessage::TimeExceeded { .. } => false,
+        IcmpMessage::DestinationUnreachable { .. } => true,
+        _ => return None,
+    };
+
+    // quoted header with at least 8 bytes of the probe
+    let (quoted, payload) = Ipv4Header::parse(answer.get_original()?).ok()?;
+    if *quoted.get_src() != probe.src || *quoted.get_dst() != probe.dst || payload.len() < 8 {
+        return None;
+    }
+
+    let matches: bool = match probe.kind {
+        TracerouteProbe::Udp => {
+            *quoted.get_protocol() == UDP_PROTO as u8
+                && payload[..4] == [probe.id.to_be_bytes(), probe.sq.to_be_bytes()].concat()
+        }
+        TracerouteProbe::Icmp => {
+            *quoted.get_protocol() == ICMP_PROTO as u8
+                && payload[0] == ICMP_ECHO_REQUEST
+                && payload[4..8] == [probe.id.to_be_bytes(), probe.sq.to_be_bytes()].concat()
+        }
+    };
+
+    matches.then_some((*ip_header.get_src(), last))
+}
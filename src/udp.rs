@@ -0,0 +1,200 @@
+use std::io;
+use std::net;
+
+use crate::checksum::pseudo_header_checksum;
+use crate::*;
+
+const UDP_HEADER_SIZE: usize = 8;
+
+/// udp datagram received by [`Socket::recv_udp`], payload stays in the read buffer
+#[derive(Clone)]
+pub struct UdpPacket {
+    src_mac: Mac,
+    dst_mac: Mac,
+    ip: Ipv4Header,
+    src_port: u16,
+    dst_port: u16,
+    // true if checksum matches or wasn't computed by the sender
+    checksum_valid: bool,
+    // offset of the payload in the read buffer
+    payload_offset: usize,
+    payload_len: usize,
+}
+
+impl UdpPacket {
+    getters!(
+        pub get_src_mac(src_mac) -> Mac;
+        pub get_dst_mac(dst_mac) -> Mac;
+        pub get_ip(ip) -> Ipv4Header;
+        pub get_src_port(src_port) -> u16;
+        pub get_dst_port(dst_port) -> u16;
+        pub get_checksum_valid(checksum_valid) -> bool;
+        pub get_payload_offset(payload_offset) -> usize;
+        pub get_payload_len(payload_len) -> usize;
+    );
+
+    /// Returns payload from the buffer, which the packet was read into
+    pub fn payload<'a>(&self, buffer: &'a [u8]) -> &'a [u8] {
+        &buffer[self.payload_offset..self.payload_offset + self.payload_len]
+    }
+}
+
+impl Socket {
+    /// Sends udp datagram with any source address and port, checksums are computed
+    ///
+    /// Ethernet source is the mac address of the socket interface
+    ///
+    /// # Examples
+    /// ```
+    /// use cursock::*;
+    /// use std::net::Ipv4Addr;
+    ///
+    /// #[cfg(target_os = "linux")]
+    /// {
+    ///     // opening raw sockets requires root or CAP_NET_RAW
+    ///     if let (Ok(reader), Ok(writer)) = (Socket::new("lo"), Socket::new("lo")) {
+    ///         let src = Ipv4Addr::new(10, 0, 0, 1);
+    ///         let dst = Ipv4Addr::new(10, 0, 0, 2);
+    ///
+    ///         reader.set_filter("udp src port 40001").expect("filter error");
+    ///
+    ///         // odd length payload
+    ///         writer
+    ///             .send_udp((src, 40001), (dst, 53), &Mac::from([0xff; MAC_LEN]), b"hello")
+    ///             .expect("send error");
+    ///
+    ///         let mut buffer = [0; 1514];
+    ///         let packet = reader.recv_udp(&mut buffer).expect("receive error");
+    ///
+    ///         assert_eq!(packet.get_dst_port(), &53);
+    ///         assert_eq!(packet.get_ip().get_src(), &src);
+    ///         assert_eq!(packet.payload(&buffer), b"hello");
+    ///         assert!(*packet.get_checksum_valid());
+    ///         // checksum of 10.0.0.1:40001 -> 10.0.0.2:53 "hello"
+    ///         assert_eq!(&buffer[packet.get_payload_offset() - 2..][..2], &[0x0b, 0x89])
+    ///     }
+    /// }
+    /// ```
+    pub fn send_udp(
+        &self,
+        src: (net::Ipv4Addr, u16),
+        dst: (net::Ipv4Addr, u16),
+        dst_mac: &Mac,
+        payload: &[u8],
+    ) -> io::Result<()> {
+        let udp_len: usize = UDP_HEADER_SIZE + payload.len();
+        if IPV4_HEADER_SIZE + udp_len > u16::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("udp payload of {} bytes is too long", payload.len()),
+            ));
+        }
+
+        let mut ip_header: Ipv4Header = Ipv4Header::new(src.0, dst.0, UDP_PROTO as u8);
+        ip_header.set_total_len((IPV4_HEADER_SIZE + udp_len) as u16);
+
+        let mut datagram: Vec<u8> = Vec::with_capacity(udp_len);
+        datagram.extend_from_slice(&src.1.to_be_bytes());
+        datagram.extend_from_slice(&dst.1.to_be_bytes());
+        datagram.extend_from_slice(&(udp_len as u16).to_be_bytes());
+        datagram.extend_from_slice(&[0, 0]);
+        datagram.extend_from_slice(payload);
+
+        let checksum: u16 = udp_checksum(&src.0, &dst.0, &datagram);
+        datagram[6..8].copy_from_slice(&checksum.to_be_bytes());
+
+        let eth_header: EthernetHeader =
+            EthernetHeader::new(*dst_mac, *self.get_src_mac(), IPV4_PROTO);
+
+        let mut frame: Vec<u8> = Vec::with_capacity(ETH_HEADER_SIZE + IPV4_HEADER_SIZE + udp_len);
+        frame.extend_from_slice(&eth_header.to_bytes());
+        frame.extend_from_slice(&ip_header.to_bytes());
+        frame.extend_from_slice(&datagram);
+
+        self.send_raw_packet(&frame)?;
+
+        Ok(())
+    }
+    /// Reads frames until a udp datagram is received
+    ///
+    /// Frames with other protocols and ipv4 fragments are skipped.
+    /// Datagrams with invalid checksums are returned too and flagged with
+    /// [`UdpPacket::get_checksum_valid`] set to false, since with checksum offload
+    /// packet sockets see locally sent and virtual interface datagrams before checksum is computed,
+    /// check the flag to drop them
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use cursock::*;
+    ///
+    /// let socket = Socket::new("eth0").expect("initialize error");
+    /// let mut buffer = [0; 1514];
+    ///
+    /// let packet = socket.recv_udp(&mut buffer).expect("read error");
+    ///
+    /// println!(
+    ///     "{}:{} -> {}:{} {:?}",
+    ///     packet.get_ip().get_src(),
+    ///     packet.get_src_port(),
+    ///     packet.get_ip().get_dst(),
+    ///     packet.get_dst_port(),
+    ///     packet.payload(&buffer)
+    /// )
+    /// ```
+    pub fn recv_udp(&self, buffer: &mut [u8]) -> io::Result<UdpPacket> {
+        loop {
+            let length: usize = self.read_raw_packet(buffer)?;
+
+            if let Some(packet) = parse_udp(&buffer[..length]) {
+                return Ok(packet);
+            }
+        }
+    }
+}
+
+pub(crate) fn parse_udp(frame: &[u8]) -> Option<UdpPacket> {
+    let (eth_header, packet) = EthernetHeader::parse(frame).ok()?;
+    if *eth_header.get_ethertype() != IPV4_PROTO {
+        return None;
+    }
+
+    let (ip_header, _, datagram) = Ipv4Header::from_bytes(packet).ok()?;
+    let is_fragment: bool = *ip_header.get_fragment_offset() != 0
+        || ip_header.get_flags() & Ipv4Header::MORE_FRAGMENTS != 0;
+
+    if *ip_header.get_protocol() != UDP_PROTO as u8 || is_fragment {
+        return None;
+    }
+
+    if datagram.len() < UDP_HEADER_SIZE {
+        return None;
+    }
+
+    let udp_len: usize = u16::from_be_bytes([datagram[4], datagram[5]]) as usize;
+    if udp_len < UDP_HEADER_SIZE || udp_len > datagram.len() {
+        return None;
+    }
+
+    let datagram: &[u8] = &datagram[..udp_len];
+    let checksum: u16 = u16::from_be_bytes([datagram[6], datagram[7]]);
+
+    // sum of the datagram with its checksum is zero, zero checksum means it wasn't computed
+    let checksum_valid: bool = checksum == 0
+        || pseudo_header_checksum(
+            ip_header.get_src(),
+            ip_header.get_dst(),
+            UDP_PROTO as u8,
+            datagram,
+        ) == 0;
+
+    Some(UdpPacket {
+        src_mac: *eth_header.get_src(),
+        dst_mac: *eth_header.get_dst(),
+        src_port: u16::from_be_bytes([datagram[0], datagram[1]]),
+        dst_port: u16::from_be_bytes([datagram[2], datagram[3]]),
+        checksum_valid,
+        payload_offset: ETH_HEADER_SIZE + (packet[0] & 0x0f) as usize * 4 + UDP_HEADER_SIZE,
+        payload_len: udp_len - UDP_HEADER_SIZE,
+        ip: ip_header,
+    })
+}
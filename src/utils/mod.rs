@@ -1,3 +1,4 @@
+use std::io;
 use std::net;
 use std::ptr;
 
@@ -36,28 +37,42 @@ pub const ARP_REQUEST: u16 = 1;
 pub const IPV4_PROTO: u16 = 0x0800;
 pub const IPV6_PROTO: u16 = 0x86dd;
 pub const ARP_PROTO: u16 = 0x0806;
+pub const VLAN_PROTO: u16 = 0x8100;
+pub const QINQ_PROTO: u16 = 0x88a8;
 pub const ICMP_PROTO: u16 = 0x0001;
+pub const TCP_PROTO: u16 = 0x0006;
+pub const UDP_PROTO: u16 = 0x0011;
 pub const ICMP_ECHO_REQUEST: u8 = 8;
 pub const ICMP_ECHO_RESPONSE: u8 = 0;
+pub const ICMP_DESTINATION_UNREACHABLE: u8 = 3;
+pub const ICMP_REDIRECT: u8 = 5;
+pub const ICMP_TIME_EXCEEDED: u8 = 11;
 pub const EMPTY_ARRAY: [i8; 1] = [0];
 pub const IPV4_HEADER_SIZE: usize = std::mem::size_of::<IpV4Header>();
 pub const ARP_HEADER_SIZE: usize = std::mem::size_of::<ArpHeader>();
 pub const ETH_HEADER_SIZE: usize = std::mem::size_of::<EthHeader>();
 pub const ICMP_HEADER_SIZE: usize = std::mem::size_of::<IcmpHeader>();
+pub const VLAN_HEADER_SIZE: usize = 4;
 
 /// struct for representing mac addresses
 ///
 /// # Example
 /// ```
+/// use std::collections::HashSet;
 /// use cursock::utils::*;
 ///
-/// let mac_addr: Mac = Handle::from([0xff; MAC_LEN]);
+/// let mac_addr: Mac = Mac::from([0xff; MAC_LEN]);
 ///
-/// let mac_octets: [u8; MAC_LEN] = mac_addr.to();
+/// let mac_octets: [u8; MAC_LEN] = mac_addr.into();
 ///
-/// assert_eq!(mac_octets, [0xff; MAC_LEN])
+/// assert_eq!(mac_octets, [0xff; MAC_LEN]);
+///
+/// // mac addresses are copied, so they can be kept in sets and maps
+/// let seen: HashSet<Mac> = [mac_addr, Mac::BROADCAST, Mac::ZERO].into_iter().collect();
+/// assert_eq!(seen.len(), 2);
+/// assert!(seen.contains(&mac_addr))
 /// ```
-#[derive(Clone)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Mac {
     mac_addr: [u8; MAC_LEN],
 }
@@ -120,13 +135,14 @@ pub struct IpV4Header {
 /// # Example
 /// ```
 /// use cursock::utils::*;
+/// use std::net::Ipv4Addr;
 ///
-/// let response = ArpResponse::new(Handle::from([192, 168, 1, 1]), Handle::from([0; MAC_LEN]), Handle::from([192, 168, 1, 2]), Handle::from([0; MAC_LEN]));
+/// let response = ArpResponse::new(Ipv4Addr::from([192, 168, 1, 1]), Mac::from([0; MAC_LEN]), Ipv4Addr::from([192, 168, 1, 2]), Mac::from([0; MAC_LEN]));
 ///
-/// let src_ip: [u8; IPV4_LEN] = response.get_src_ip().to();
-/// let dst_ip: [u8; IPV4_LEN] = response.get_dst_ip().to();
-/// let src_mac: [u8; MAC_LEN] = response.get_src_mac().to();
-/// let dst_mac: [u8; MAC_LEN] = response.get_dst_mac().to();
+/// let src_ip: [u8; IPV4_LEN] = response.get_src_ip().octets();
+/// let dst_ip: [u8; IPV4_LEN] = response.get_dst_ip().octets();
+/// let src_mac: [u8; MAC_LEN] = (*response.get_src_mac()).into();
+/// let dst_mac: [u8; MAC_LEN] = (*response.get_dst_mac()).into();
 ///
 /// assert_eq!(src_ip, [192, 168, 1, 1]);
 /// assert_eq!(dst_ip, [192, 168, 1, 2]);
@@ -169,7 +185,7 @@ pub struct IcmpData {
 /// use cursock::utils::*;
 ///
 /// let echo_reply = IcmpType::EchoReply;
-/// let raw_echo_reply: u8 = echo_reply.to();
+/// let raw_echo_reply: u8 = echo_reply.into();
 ///
 /// assert_eq!(raw_echo_reply, 0)
 /// ```
@@ -364,6 +380,128 @@ impl From<Mac> for [u8; MAC_LEN] {
     }
 }
 
+impl Mac {
+    pub const BROADCAST: Self = Self {
+        mac_addr: [0xff; MAC_LEN],
+    };
+    pub const ZERO: Self = Self {
+        mac_addr: [0; MAC_LEN],
+    };
+
+    /// Parses mac address from colon or dash separated hex octets, like "aa:bb:cc:dd:ee:ff",
+    /// or from dot separated groups of 4 hex digits, like "aabb.ccdd.eeff"
+    ///
+    /// # Example
+    /// ```
+    /// use cursock::utils::*;
+    ///
+    /// let mac: Mac = Mac::parse("02:00:5E:0a:0B:ff").expect("parse error");
+    ///
+    /// assert_eq!(<[u8; MAC_LEN]>::from(mac), [0x02, 0x00, 0x5e, 0x0a, 0x0b, 0xff]);
+    /// assert!(mac.to_string().parse::<Mac>().ok() == Some(mac));
+    /// assert!(Mac::parse("02-00-5e-0a-0b-ff").ok() == Some(mac));
+    /// assert!(Mac::parse("0200.5E0a.0BFF").ok() == Some(mac));
+    ///
+    /// assert!(Mac::parse("02:00:5e:0a:0b").is_err());
+    /// assert!(Mac::parse("02:00:5e:0a:0b:ff:01").is_err());
+    /// assert!(Mac::parse("02:00:5e:0a:0b:fg").is_err());
+    /// assert!(Mac::parse("02:00:5e:0a:0b:100").is_err());
+    /// assert!(Mac::parse("02:00:5e::0b:ff").is_err());
+    /// assert!(Mac::parse("02:00:5e:0a:0b:+f").is_err());
+    /// assert!(Mac::parse("0200.5e0a.0bf").is_err());
+    /// assert!(Mac::parse("0200.5e0a.0bff.0000").is_err());
+    ///
+    /// let err = Mac::parse("0200.5e0a").err().expect("parsed");
+    /// assert_eq!(err.to_string(), "invalid mac address \"0200.5e0a\"")
+    /// ```
+    pub fn parse(mac: &str) -> io::Result<Self> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid mac address \"{}\"", mac),
+            )
+        };
+        // from_str_radix also accepts a sign
+        let is_hex = |part: &str| -> bool { part.bytes().all(|byte| byte.is_ascii_hexdigit()) };
+
+        let mut mac_addr: [u8; MAC_LEN] = [0; MAC_LEN];
+
+        if mac.contains('.') {
+            let mut groups = mac.split('.');
+
+            for pair in mac_addr.chunks_mut(2) {
+                let part: &str = groups.next().ok_or_else(invalid)?;
+                if part.len() != 4 || !is_hex(part) {
+                    return Err(invalid());
+                }
+
+                let group: u16 = u16::from_str_radix(part, 16).map_err(|_| invalid())?;
+                pair.copy_from_slice(&group.to_be_bytes());
+            }
+
+            return match groups.next() {
+                Some(_) => Err(invalid()),
+                None => Ok(Self { mac_addr }),
+            };
+        }
+
+        let mut octets = mac.split([':', '-']);
+
+        for octet in mac_addr.iter_mut() {
+            let part: &str = octets.next().ok_or_else(invalid)?;
+            if part.is_empty() || part.len() > 2 || !is_hex(part) {
+                return Err(invalid());
+            }
+
+            *octet = u8::from_str_radix(part, 16).map_err(|_| invalid())?;
+        }
+
+        match octets.next() {
+            Some(_) => Err(invalid()),
+            None => Ok(Self { mac_addr }),
+        }
+    }
+
+    /// Returns true for ff:ff:ff:ff:ff:ff
+    ///
+    /// # Example
+    /// ```
+    /// use cursock::utils::*;
+    ///
+    /// let multicast = Mac::parse("01:00:5e:00:00:fb").expect("parse error");
+    /// let unicast = Mac::parse("02:00:5e:0a:0b:ff").expect("parse error");
+    ///
+    /// assert!(Mac::BROADCAST.is_broadcast() && Mac::BROADCAST.is_multicast());
+    /// assert!(!multicast.is_broadcast() && multicast.is_multicast() && !multicast.is_unicast());
+    /// assert!(unicast.is_unicast() && Mac::ZERO.is_unicast());
+    ///
+    /// let mut macs = std::collections::HashSet::new();
+    /// macs.insert(unicast);
+    /// assert!(macs.contains(&unicast) && !macs.contains(&multicast));
+    /// assert!(Mac::ZERO < unicast && unicast < Mac::BROADCAST)
+    /// ```
+    pub fn is_broadcast(&self) -> bool {
+        *self == Self::BROADCAST
+    }
+
+    /// Returns true if group bit of the first octet is set, broadcast is multicast too
+    pub fn is_multicast(&self) -> bool {
+        self.mac_addr[0] & 0x01 != 0
+    }
+
+    pub fn is_unicast(&self) -> bool {
+        !self.is_multicast()
+    }
+}
+
+impl std::str::FromStr for Mac {
+    type Err = io::Error;
+
+    fn from_str(mac: &str) -> Result<Self, Self::Err> {
+        Self::parse(mac)
+    }
+}
+
 impl std::fmt::Display for Mac {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -468,25 +606,26 @@ pub fn str_from_cstr(cstr: *const i8) -> String {
     string
 }
 
+/// Computes internet checksum of the memory, result is in memory order, so it can be stored as is
+///
+/// Memory has to be valid for len bytes, it's a wrapper over [`crate::icmp_checksum`],
+/// so prefer it or [`crate::ipv4_checksum`], which take slices
+///
+/// # Examples
+/// ```
+/// use cursock::utils::*;
+///
+/// let data: [u8; 3] = [0x01, 0x02, 0x03];
+/// let sum: u16 = checksum(data.as_ptr(), data.len());
+///
+/// // odd byte is padded with zero: !(0x0102 + 0x0300)
+/// assert_eq!(sum.to_ne_bytes(), [0xfb, 0xfd])
+/// ```
+// kept safe, since it's been a safe function before checksums took slices
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub fn checksum(header: *const u8, len: usize) -> u16 {
-    let mut sum: i32 = 0;
-    let mut left: usize = len;
-    let words: *const u16 = header as *const u16;
-
-    let mut i: usize = 0;
-    while left > 1 {
-        sum += unsafe { *((words as usize + i) as *const u16) } as i32;
-
-        left -= 2;
-        i += 2
-    }
-
-    if left == 1 {
-        sum += unsafe { *((words as usize + i - 1) as *const u8) } as i32;
-    }
-
-    sum = (sum >> 16) + (sum & 0xffff);
-    sum += sum >> 16;
+    let bytes: &[u8] = unsafe { std::slice::from_raw_parts(header, len) };
 
-    (!sum) as u16
+    // icmp checksum is the plain internet checksum of the bytes
+    u16::from_ne_bytes(crate::icmp_checksum(bytes).to_be_bytes())
 }